@@ -0,0 +1,506 @@
+//! Module for reading and writing Extended Position Description (EPD) records
+//
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+use crate::*;
+use crate::game::MoveSequence;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// An EPD record, consisting of a position and a list of operations.
+///
+/// The position is given by the first four fields of FEN. The half-move clock and full-move number
+/// are taken from the `hmvc` and `fmvn` operations if present, and are written as those operations
+/// if they aren't 0 and 1.
+///
+/// # Example
+/// ```rust
+/// use chess::epd::{Epd, Operation};
+///
+/// let epd: Epd = "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - bm Rd8#; ce 32766; id \"back rank\";"
+///     .parse()?;
+///
+/// assert_eq!(epd.best_moves()[0].to_string(), "Rd8#");
+/// assert_eq!(epd.centipawn_eval(), Some(32766));
+/// assert_eq!(epd.id(), Some("back rank"));
+/// assert_eq!(epd.to_string(),
+///     "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - bm Rd8#; ce 32766; id \"back rank\";");
+/// # Ok::<(), chess::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epd {
+    position: Rc<Position>,
+    operations: Vec<Operation>,
+}
+
+impl Epd {
+    /// Creates an `Epd` for `position`, with no operations.
+    pub fn new(position: Rc<Position>) -> Self {
+        Epd {
+            position,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Returns the position.
+    pub fn position(&self) -> &Rc<Position> {
+        &self.position
+    }
+
+    /// Returns all operations, in order.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Adds an operation, replacing any existing operation with the same opcode.
+    ///
+    /// # Errors
+    /// Returns an error if the operation contains moves which are not valid from the position, or
+    /// if it can't be written as EPD: a comment numbered above 9, or a string containing `"`.
+    pub fn set_operation(&mut self, op: Operation) -> Result<&mut Self> {
+        op.validate(&self.position)?;
+
+        if let Some(existing) = self.operations.iter_mut().find(|o| o.opcode() == op.opcode()) {
+            *existing = op;
+        } else {
+            self.operations.push(op);
+        }
+
+        Ok(self)
+    }
+
+    /// Removes and returns the operation with the given opcode, if any.
+    pub fn remove_operation(&mut self, opcode: &str) -> Option<Operation> {
+        let index = self.operations.iter().position(|op| op.opcode() == opcode)?;
+
+        Some(self.operations.remove(index))
+    }
+
+    /// Returns the operation with the given opcode, if any.
+    pub fn operation(&self, opcode: &str) -> Option<&Operation> {
+        self.operations.iter().find(|op| op.opcode() == opcode)
+    }
+
+    /// Returns the best moves (`bm`), or an empty slice if none were given.
+    pub fn best_moves(&self) -> &[MoveRc] {
+        match self.operation("bm") {
+            Some(Operation::BestMoves(moves)) => moves,
+            _ => &[],
+        }
+    }
+
+    /// Returns the moves to avoid (`am`), or an empty slice if none were given.
+    pub fn avoid_moves(&self) -> &[MoveRc] {
+        match self.operation("am") {
+            Some(Operation::AvoidMoves(moves)) => moves,
+            _ => &[],
+        }
+    }
+
+    /// Returns the predicted moves (`pm`), or an empty slice if none were given.
+    pub fn predicted_moves(&self) -> &[MoveRc] {
+        match self.operation("pm") {
+            Some(Operation::PredictedMoves(moves)) => moves,
+            _ => &[],
+        }
+    }
+
+    /// Returns the predicted variation (`pv`), if any.
+    pub fn predicted_variation(&self) -> Option<&MoveSequence> {
+        match self.operation("pv") {
+            Some(Operation::PredictedVariation(pv)) => Some(pv),
+            _ => None,
+        }
+    }
+
+    /// Returns the evaluation in centipawns (`ce`), if any.
+    pub fn centipawn_eval(&self) -> Option<i32> {
+        match self.operation("ce") {
+            Some(Operation::CentipawnEval(ce)) => Some(*ce),
+            _ => None,
+        }
+    }
+
+    /// Returns the analysis depth (`acd`), if any.
+    pub fn analysis_depth(&self) -> Option<usize> {
+        match self.operation("acd") {
+            Some(Operation::AnalysisDepth(depth)) => Some(*depth),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of moves in which the side to move can force mate (`dm`), if any.
+    pub fn direct_mate(&self) -> Option<usize> {
+        match self.operation("dm") {
+            Some(Operation::DirectMate(moves)) => Some(*moves),
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier (`id`), if any.
+    pub fn id(&self) -> Option<&str> {
+        match self.operation("id") {
+            Some(Operation::Id(id)) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns comment number `n` (`c0` through `c9`), if any.
+    pub fn comment(&self, n: u8) -> Option<&str> {
+        self.operations.iter()
+            .find_map(|op| match op {
+                Operation::Comment(num, comment) if *num == n => Some(comment.as_str()),
+                _ => None,
+            })
+    }
+}
+
+impl FromStr for Epd {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut fields = s.trim().splitn(5, char::is_whitespace);
+        let mut fen: Vec<_> = fields.by_ref().take(4).collect();
+        if fen.len() < 4 {
+            return Err(Error::ParseError);
+        }
+
+        let operations = split_operations(fields.next().unwrap_or(""))?;
+        let counter = |opcode, default| {
+            operations.iter()
+                .find(|(op, _)| op == opcode)
+                .and_then(|(_, operands)| operands.first())
+                .map_or(default, |counter| counter.as_str())
+        };
+        fen.push(counter("hmvc", "0"));
+        fen.push(counter("fmvn", "1"));
+
+        let mut epd = Epd::new(Rc::new(fen.join(" ").parse()?));
+        for (opcode, operands) in operations.iter() {
+            let op = Operation::parse(&epd.position, opcode, operands)?;
+            epd.operations.push(op);
+        }
+
+        Ok(epd)
+    }
+}
+
+impl fmt::Display for Epd {
+    /// Writes the record as a single line of EPD.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fen = self.position.to_fen_str();
+        let mut s: Vec<_> = fen.split(' ').take(4).map(str::to_owned).collect();
+        s.extend(self.operations.iter().map(|op| format!("{};", op)));
+
+        // keep counters which would otherwise be lost
+        if self.operation("hmvc").is_none() && self.position.draw_plies() != 0 {
+            s.push(format!("hmvc {};", self.position.draw_plies()));
+        }
+        if self.operation("fmvn").is_none() && self.position.move_number() != 1 {
+            s.push(format!("fmvn {};", self.position.move_number()));
+        }
+
+        s.join(" ").fmt(f)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// An EPD operation, consisting of an opcode and its operands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// `bm`: The best moves, one of which should be found.
+    BestMoves(Vec<MoveRc>),
+    /// `am`: Moves to avoid.
+    AvoidMoves(Vec<MoveRc>),
+    /// `pm`: The predicted moves.
+    PredictedMoves(Vec<MoveRc>),
+    /// `pv`: The predicted variation.
+    PredictedVariation(MoveSequence),
+    /// `ce`: The evaluation in centipawns, from the perspective of the side to move.
+    CentipawnEval(i32),
+    /// `acd`: The depth of the analysis which produced this record.
+    AnalysisDepth(usize),
+    /// `dm`: The side to move can force mate in this number of moves.
+    DirectMate(usize),
+    /// `id`: An identifier for the record.
+    Id(String),
+    /// `c0` through `c9`: Comments.
+    Comment(u8, String),
+    /// Any other operation, including `hmvc` and `fmvn`.
+    Other {
+        /// The opcode.
+        opcode: String,
+        /// The operands, with quotes removed.
+        operands: Vec<String>,
+    },
+}
+
+impl Operation {
+    /// Returns the opcode for this operation.
+    pub fn opcode(&self) -> &str {
+        use Operation::*;
+
+        match self {
+            BestMoves(_) => "bm",
+            AvoidMoves(_) => "am",
+            PredictedMoves(_) => "pm",
+            PredictedVariation(_) => "pv",
+            CentipawnEval(_) => "ce",
+            AnalysisDepth(_) => "acd",
+            DirectMate(_) => "dm",
+            Id(_) => "id",
+            Comment(n, _) => ["c0", "c1", "c2", "c3", "c4", "c5", "c6", "c7", "c8", "c9"]
+                .get(usize::from(*n))
+                .unwrap_or(&"c?"),
+            Other{ opcode, .. } => opcode,
+        }
+    }
+
+    /// Parses an operation, validating any moves against `pos`.
+    fn parse(pos: &Rc<Position>, opcode: &str, operands: &[String]) -> Result<Self> {
+        use Operation::*;
+
+        let text = || operands.join(" ");
+
+        Ok(match opcode {
+            "bm" => BestMoves(parse_moves(pos, operands)?),
+            "am" => AvoidMoves(parse_moves(pos, operands)?),
+            "pm" => PredictedMoves(parse_moves(pos, operands)?),
+            "pv" => {
+                let mut pv = MoveSequence::starting_at(pos.clone());
+                for mv in operands {
+                    let mv = MoveBuilder::from_str(mv)?.validate(pv.final_position())?;
+                    pv.push(mv.into())?;
+                }
+                PredictedVariation(pv)
+            },
+            "ce" => CentipawnEval(parse_number(operands)?),
+            "acd" => AnalysisDepth(parse_number(operands)?),
+            "dm" => DirectMate(parse_number(operands)?),
+            "id" => Id(text()),
+            c if c.len() == 2 && c.starts_with('c') && c.as_bytes()[1].is_ascii_digit() => {
+                Comment(c.as_bytes()[1] - b'0', text())
+            },
+            _ => Other{ opcode: opcode.to_owned(), operands: operands.to_vec() },
+        })
+    }
+
+    /// Checks that any moves in the operation are valid from `pos`, and that the operation can be
+    /// written as EPD, which has no way to escape quotes.
+    fn validate(&self, pos: &Position) -> Result<()> {
+        use Operation::*;
+
+        let moves = match self {
+            Comment(n, _) if *n > 9 => return Err(Error::InvalidOperation),
+            Id(s) | Comment(_, s) if s.contains('"') => return Err(Error::InvalidOperation),
+            Other{ operands, .. } if operands.iter().any(|operand| operand.contains('"')) => {
+                return Err(Error::InvalidOperation);
+            },
+            BestMoves(moves) | AvoidMoves(moves) | PredictedMoves(moves) => moves.as_slice(),
+            PredictedVariation(pv) if pv.initial_position().as_ref() != pos => {
+                return Err(Error::MovePositionMismatch);
+            },
+            _ => &[],
+        };
+
+        if moves.iter().all(|mv| mv.position().as_ref() == pos) {
+            Ok(())
+        } else {
+            Err(Error::MovePositionMismatch)
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    /// Writes the opcode followed by its operands, without the terminating semicolon.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Operation::*;
+
+        let operands: Vec<String> = match self {
+            BestMoves(moves) | AvoidMoves(moves) | PredictedMoves(moves) => {
+                moves.iter().map(|mv| mv.to_string()).collect()
+            },
+            PredictedVariation(pv) => pv.iter().map(|mv| mv.to_string()).collect(),
+            CentipawnEval(ce) => vec![ce.to_string()],
+            AnalysisDepth(depth) => vec![depth.to_string()],
+            DirectMate(moves) => vec![moves.to_string()],
+            Id(s) | Comment(_, s) => vec![format!("\"{}\"", s)],
+            Other{ operands, .. } => operands.iter()
+                .map(|operand| {
+                    if operand.is_empty()
+                        || operand.contains(|c: char| c.is_whitespace() || c == ';')
+                    {
+                        format!("\"{}\"", operand)
+                    } else {
+                        operand.clone()
+                    }
+                })
+                .collect(),
+        };
+
+        if operands.is_empty() {
+            self.opcode().fmt(f)
+        } else {
+            format!("{} {}", self.opcode(), operands.join(" ")).fmt(f)
+        }
+    }
+}
+
+/// Splits the operations section of an EPD record into opcodes and their operands. Operands in
+/// double quotes may contain spaces and semicolons; the quotes are removed.
+fn split_operations(s: &str) -> Result<Vec<(String, Vec<String>)>> {
+    let mut operations = Vec::new();
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in s.chars() {
+        match c {
+            '"' => {
+                if quoted && token.is_empty() {
+                    tokens.push(String::new()); // empty string
+                }
+                quoted = !quoted;
+            },
+            ';' if !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+                if !tokens.is_empty() {
+                    let opcode = tokens.remove(0);
+                    operations.push((opcode, std::mem::take(&mut tokens)));
+                }
+            },
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            },
+            c => token.push(c),
+        }
+    }
+
+    if quoted || !token.is_empty() || !tokens.is_empty() {
+        return Err(Error::ParseError); // unterminated string or operation
+    }
+
+    Ok(operations)
+}
+
+/// Parses a single numeric operand.
+fn parse_number<T: FromStr>(operands: &[String]) -> Result<T> {
+    match operands {
+        [operand] => operand.parse().map_err(|_| Error::ParseError),
+        _ => Err(Error::ParseError),
+    }
+}
+
+/// Parses a list of moves in standard algebraic notation, validating them against `pos`.
+fn parse_moves(pos: &Rc<Position>, moves: &[String]) -> Result<Vec<MoveRc>> {
+    moves.iter()
+        .map(|mv| {
+            let mv = MoveBuilder::from_str(mv)?.validate(pos)?;
+            mv.make()?;
+            Ok(mv.into())
+        })
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let epd: Epd = "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - \
+            bm Qxf7; am Qf6 d3; pm Qxf7; pv d3 d6; ce 32766; acd 3; dm 1; \
+            id \"mate; in one\"; c0 \"scholar's mate\"; c3 third; hmvc 2; fmvn 4; noop;"
+            .parse().expect("valid EPD");
+
+        assert_eq!(epd.position().move_number(), 4);
+        assert_eq!(epd.best_moves().len(), 1);
+        assert_eq!(epd.avoid_moves().len(), 2);
+        assert_eq!(epd.predicted_moves().len(), 1);
+        assert_eq!(epd.predicted_variation().map(MoveSequence::len), Some(2));
+        assert_eq!(epd.centipawn_eval(), Some(32766));
+        assert_eq!(epd.analysis_depth(), Some(3));
+        assert_eq!(epd.direct_mate(), Some(1));
+        assert_eq!(epd.id(), Some("mate; in one"));
+        assert_eq!(epd.comment(0), Some("scholar's mate"));
+        assert_eq!(epd.comment(3), Some("third"));
+        assert_eq!(epd.comment(1), None);
+        assert_eq!(epd.operation("noop"),
+            Some(&Operation::Other{ opcode: "noop".to_owned(), operands: vec![] }));
+    }
+
+    #[test]
+    fn round_trip() {
+        let s = "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - \
+            bm Qxf7#; am Qf6 d3; pv d3 d6 Qxf7#; ce -12; id \"a b\"; c1 \"\"; hmvc 2;";
+        let epd: Epd = s.parse().expect("valid EPD");
+
+        assert_eq!(epd.to_string(), s);
+        assert_eq!(epd.to_string().parse(), Ok(epd));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!("8/8/8/8/8/8/8/8 w -".parse::<Epd>(), Err(Error::ParseError));
+        assert!("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5;".parse::<Epd>().is_err());
+        assert!("4k3/8/8/8/8/8/8/4K3 w - - pv Kd2 Kd2;".parse::<Epd>().is_err());
+        assert!("4k3/8/8/8/8/8/8/4K3 w - - id \"unterminated;".parse::<Epd>().is_err());
+        assert!("4k3/8/8/8/8/8/8/4K3 w - - id missing".parse::<Epd>().is_err());
+        assert!("4k3/8/8/8/8/8/8/4K3 w - - acd x;".parse::<Epd>().is_err());
+    }
+
+    #[test]
+    fn set_operation() {
+        let mut epd = Epd::new(Position::new().into());
+        let other: Epd = "4k3/8/8/8/8/8/8/4K3 w - - bm Kd2;".parse().expect("valid EPD");
+
+        epd.set_operation(Operation::AnalysisDepth(5)).expect("INFALLIBLE");
+        epd.set_operation(Operation::AnalysisDepth(6)).expect("INFALLIBLE");
+        assert_eq!(epd.analysis_depth(), Some(6));
+        assert_eq!(epd.operations().len(), 1);
+
+        let bm = other.operation("bm").expect("INFALLIBLE").clone();
+        assert_eq!(epd.set_operation(bm).err(), Some(Error::MovePositionMismatch));
+        assert!(epd.remove_operation("acd").is_some());
+        assert!(epd.operations().is_empty());
+
+        // operations which can't be written
+        let invalid = [
+            Operation::Comment(10, "too many".to_owned()),
+            Operation::Id("a \"quote\"".to_owned()),
+            Operation::Comment(0, "\"".to_owned()),
+            Operation::Other{ opcode: "x".to_owned(), operands: vec!["\"".to_owned()] },
+        ];
+        for op in invalid.iter() {
+            assert_eq!(epd.set_operation(op.clone()).err(), Some(Error::InvalidOperation));
+        }
+        assert!(epd.operations().is_empty());
+    }
+
+    #[test]
+    fn counters() {
+        let s = "4k3/8/8/8/8/8/8/4K3 w - - 12 40";
+        let epd = Epd::new(Rc::new(s.parse().expect("valid FEN")));
+        assert_eq!(epd.to_string(), "4k3/8/8/8/8/8/8/4K3 w - - hmvc 12; fmvn 40;");
+
+        let parsed: Epd = epd.to_string().parse().expect("valid EPD");
+        assert_eq!(parsed.position().to_fen_str(), s);
+        assert_eq!(parsed.to_string(), epd.to_string());
+
+        let epd: Epd = "4k3/8/8/8/8/8/8/4K3 w - - c10 ten;".parse().expect("valid EPD");
+        assert_eq!(epd.comment(1), None);
+        assert_eq!(epd.to_string(), "4k3/8/8/8/8/8/8/4K3 w - - c10 ten;");
+    }
+}
@@ -37,6 +37,18 @@ pub enum Error {
     EnPassantSquareOccupied,
     /// Move does not correspond to the current position
     MovePositionMismatch,
+    /// Number of checks given isn't valid for the variant
+    InvalidCheckCount,
+    /// Pieces in hand aren't valid for the variant
+    InvalidPocket,
+    /// Capture is available, and must be made
+    CaptureRequired,
+    /// Capture explodes the mover's own king
+    KingExploded,
+    /// Check or checkmate mark doesn't match the move
+    IncorrectCheckMark,
+    /// EPD operation can't be written
+    InvalidOperation,
 }
 
 impl fmt::Display for Error {
@@ -56,6 +68,12 @@ impl fmt::Display for Error {
             MissingEnPassantPawn => "en-passant square without capturable pawn",
             EnPassantSquareOccupied => "en-passant square is occupied",
             MovePositionMismatch => "move does not correspond to the current position",
+            InvalidCheckCount => "number of checks given isn't valid for the variant",
+            InvalidPocket => "pieces in hand aren't valid for the variant",
+            CaptureRequired => "a capture is available and must be made",
+            KingExploded => "capture would explode the mover's king",
+            IncorrectCheckMark => "check or checkmate mark doesn't match the move",
+            InvalidOperation => "EPD operation can't be written",
         }.fmt(f)
     }
 }
@@ -198,24 +198,28 @@ impl MoveSequence {
         false
     }
 
+    /// Returns the number of times the final position has occurred within the sequence, including
+    /// the final position itself.
+    pub fn repetitions(&self) -> usize {
+        let key = self.final_pos.zobrist_key();
+
+        1 + self.moves.iter().filter(|m| key == m.position().zobrist_key()).count()
+    }
+
     /// Returns true if the sequence ends in three-fold repetition.
     ///
     /// In other words, returns true if `self.final_position()` matches at least two other
     /// positions within the sequence.
     pub fn three_fold_repetition(&self) -> bool {
-        let key = self.final_pos.zobrist_key();
-
-        let mut count = 1;
-        for m in self.moves.iter() {
-            if key == m.position().zobrist_key() {
-                count += 1;
-                if count >= 3 {
-                    return true;
-                }
-            }
-        }
+        self.repetitions() >= 3
+    }
 
-        false
+    /// Returns true if the sequence ends in five-fold repetition.
+    ///
+    /// In other words, returns true if `self.final_position()` matches at least four other
+    /// positions within the sequence.
+    pub fn five_fold_repetition(&self) -> bool {
+        self.repetitions() >= 5
     }
 }
 
@@ -379,7 +383,7 @@ impl FusedIterator for Positions<'_> { }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// Time controls for a game
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TimeControl {
     /// Time is not limited. Typically the engine should continue searching until told to stop or
     /// a maximum depth has been reached.
@@ -405,6 +409,64 @@ pub enum TimeControl {
     },
     /// Each player must make each move in the specified number of seconds.
     Exact(Duration),
+    /// Each player begins with `base` time, but their clock only starts running once `delay` has
+    /// passed on each move. This is the delay commonly used in the US.
+    Delay{
+        /// The amount of time each player has at the beginning of the game.
+        base: Duration,
+        /// The time each move may take without using any of the player's remaining time.
+        delay: Duration,
+    },
+    /// Each player begins with `base` time, and after each move, the time used on it is given back,
+    /// up to `delay`. This is the delay devised by David Bronstein.
+    Bronstein{
+        /// The amount of time each player has at the beginning of the game.
+        base: Duration,
+        /// The most time that can be given back after each move.
+        delay: Duration,
+    },
+    /// The game is played in stages, each of which adds its base time to the players' clocks when
+    /// it begins. If the last stage has a number of moves, it is repeated for the rest of the game.
+    /// An empty list of stages is treated like `Infinite`.
+    Stages(Vec<TimeStage>),
+}
+
+impl TimeControl {
+    /// Parses the time control format used by PGN `TimeControl` tags and tournament managers, in
+    /// which stages are separated by colons. Each stage is either `moves/seconds` or
+    /// `seconds+increment`, or both as in `moves/seconds+increment`, or just `seconds`.
+    ///
+    /// Returns the simplest variant which represents the time control.
+    ///
+    /// # Example
+    /// ```
+    /// # use chess::game::{TimeControl, TimeStage};
+    /// # use std::time::Duration;
+    /// let classical = TimeControl::from_pgn_str("40/5400+30:1800+30").unwrap();
+    /// assert_eq!(classical, TimeControl::Stages(vec![
+    ///     TimeStage::new(Some(40), Duration::from_secs(5400), Duration::from_secs(30)),
+    ///     TimeStage::new(None, Duration::from_secs(1800), Duration::from_secs(30)),
+    /// ]));
+    ///
+    /// let blitz = TimeControl::from_pgn_str("180+2").unwrap();
+    /// assert_eq!(blitz, TimeControl::Incremental{
+    ///     base: Duration::from_secs(180),
+    ///     inc: Duration::from_secs(2),
+    /// });
+    /// ```
+    pub fn from_pgn_str(s: &str) -> Result<TimeControl> {
+        let stages = s.split(':').map(TimeStage::from_pgn_str).collect::<Result<Vec<_>>>()?;
+
+        Ok(match stages.as_slice() {
+            [TimeStage{ moves: None, base, inc }] if inc.as_nanos() == 0 =>
+                TimeControl::SuddenDeath(*base),
+            [TimeStage{ moves: None, base, inc }] =>
+                TimeControl::Incremental{ base: *base, inc: *inc },
+            [TimeStage{ moves: Some(mps), base, inc }] if inc.as_nanos() == 0 =>
+                TimeControl::Session{ base: *base, mps: *mps },
+            _ => TimeControl::Stages(stages),
+        })
+    }
 }
 
 impl Default for TimeControl {
@@ -413,9 +475,168 @@ impl Default for TimeControl {
     }
 }
 
+/// Formats the time control in the same format accepted by `from_str`.
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TimeControl::*;
+
+        match self {
+            Infinite => "inf".fmt(f),
+            SuddenDeath(base) => seconds(*base).fmt(f),
+            Incremental{ base, inc } => format!("{}+{}", seconds(*base), seconds(*inc)).fmt(f),
+            Session{ base, mps } => format!("{}/{}", mps, seconds(*base)).fmt(f),
+            Exact(time) => format!("st={}", seconds(*time)).fmt(f),
+            Delay{ base, delay } => format!("{}d{}", seconds(*base), seconds(*delay)).fmt(f),
+            Bronstein{ base, delay } => format!("{}b{}", seconds(*base), seconds(*delay)).fmt(f),
+            Stages(stages) if stages.is_empty() => "inf".fmt(f),
+            Stages(stages) => stages.iter()
+                .map(|stage| {
+                    let mut s = seconds(stage.base);
+                    if let Some(moves) = stage.moves {
+                        s = format!("{}/{}", moves, s);
+                    }
+                    if stage.inc.as_nanos() > 0 {
+                        s = format!("{}+{}", s, seconds(stage.inc));
+                    }
+                    s
+                })
+                .collect::<Vec<_>>()
+                .join(":")
+                .fmt(f),
+        }
+    }
+}
+
+/// Parses a time control. Times are given in seconds, and may have a fractional part. The
+/// following formats are accepted, along with any accepted by
+/// [from_pgn_str](enum.TimeControl.html#method.from_pgn_str):
+///
+///  - `inf` or `-`: infinite
+///  - `st=5`: exactly 5 seconds per move
+///  - `300`: 300 seconds for the whole game
+///  - `60+1`: 60 seconds plus a 1 second increment
+///  - `40/300`: 300 seconds for every 40 moves
+///  - `300d5`: 300 seconds with a 5 second delay
+///  - `300b5`: 300 seconds with a 5 second Bronstein delay
+///
+/// # Example
+/// ```
+/// # use chess::game::TimeControl;
+/// # use std::time::Duration;
+/// let tc: TimeControl = "60+0.5".parse().unwrap();
+/// assert_eq!(tc, TimeControl::Incremental{
+///     base: Duration::from_secs(60),
+///     inc: Duration::from_millis(500),
+/// });
+/// assert_eq!(tc.to_string(), "60+0.5");
+/// ```
+impl FromStr for TimeControl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TimeControl> {
+        if s == "inf" || s == "-" {
+            Ok(TimeControl::Infinite)
+        } else if let Some(time) = s.strip_prefix("st=") {
+            Ok(TimeControl::Exact(parse_seconds(time)?))
+        } else if let Some(i) = s.find('d') {
+            Ok(TimeControl::Delay{
+                base: parse_seconds(&s[..i])?,
+                delay: parse_seconds(&s[i + 1..])?,
+            })
+        } else if let Some(i) = s.find('b') {
+            Ok(TimeControl::Bronstein{
+                base: parse_seconds(&s[..i])?,
+                delay: parse_seconds(&s[i + 1..])?,
+            })
+        } else {
+            TimeControl::from_pgn_str(s)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A stage of a multi-stage time control
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimeStage {
+    /// The number of moves to be made in the stage, or `None` if the stage lasts for the rest of
+    /// the game.
+    pub moves: Option<usize>,
+    /// The amount of time added to each player's time at the beginning of the stage.
+    pub base: Duration,
+    /// The amount of time added to each player's time after each move made during the stage.
+    pub inc: Duration,
+}
+
+impl TimeStage {
+    /// Creates a stage of `moves` moves (or the rest of the game), adding `base` time when it
+    /// begins and `inc` after each move.
+    pub fn new(moves: Option<usize>, base: Duration, inc: Duration) -> Self {
+        TimeStage{ moves, base, inc }
+    }
+
+    /// Parses a single stage of the PGN time control format.
+    fn from_pgn_str(s: &str) -> Result<TimeStage> {
+        let (moves, rest) = match s.find('/') {
+            Some(i) => match s[..i].parse() {
+                Ok(moves) if moves > 0 => (Some(moves), &s[i + 1..]),
+                _ => return Err(Error::ParseError),
+            },
+            None => (None, s),
+        };
+        let (base, inc) = match rest.find('+') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, "0"),
+        };
+
+        Ok(TimeStage{ moves, base: parse_seconds(base)?, inc: parse_seconds(inc)? })
+    }
+}
+
+/// Parses a non-negative number of seconds, which may have a fractional part.
+fn parse_seconds(s: &str) -> Result<Duration> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 && !s.starts_with('+') =>
+            Ok(Duration::from_secs_f64(secs)),
+        _ => Err(Error::ParseError),
+    }
+}
+
+/// Formats `time` as a number of seconds, with a fractional part only if needed.
+fn seconds(time: Duration) -> String {
+    if time.subsec_millis() == 0 {
+        time.as_secs().to_string()
+    } else {
+        let s = format!("{}.{:03}", time.as_secs(), time.subsec_millis());
+        s.trim_end_matches('0').to_owned()
+    }
+}
+
+/// Returns the stage of `stages` in effect for a player's `move_num`th move (counting from one),
+/// along with the number of moves left to make in the stage, including that one. The number is
+/// `None` if the stage lasts for the rest of the game.
+fn current_stage(stages: &[TimeStage], move_num: usize) -> Option<(&TimeStage, Option<usize>)> {
+    let move_num = move_num.max(1);
+    let mut first = 1; // the first move of the stage
+    for (i, stage) in stages.iter().enumerate() {
+        match stage.moves {
+            None => return Some((stage, None)),
+            Some(moves) if i + 1 == stages.len() => {
+                let played = (move_num - first) % moves.max(1);
+                return Some((stage, Some(moves.max(1) - played)));
+            },
+            Some(moves) if move_num < first + moves => {
+                return Some((stage, Some(first + moves - move_num)));
+            },
+            Some(moves) => first += moves,
+        }
+    }
+
+    None
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// Chess clock for a game
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Clock {
     remaining: [ Duration; Color::COUNT ],
     tc: TimeControl,
@@ -426,12 +647,15 @@ impl Clock {
     pub fn new(tc: TimeControl) -> Self {
         use TimeControl::*;
 
-        let remaining = match tc {
+        let remaining = match &tc {
             Infinite => Duration::default(),
-            SuddenDeath(base) => base,
-            Incremental{ base, .. } => base,
-            Session{ base, .. } => base,
-            Exact(base) => base,
+            SuddenDeath(base) => *base,
+            Incremental{ base, .. } => *base,
+            Session{ base, .. } => *base,
+            Exact(base) => *base,
+            Delay{ base, .. } => *base,
+            Bronstein{ base, .. } => *base,
+            Stages(stages) => stages.first().map(|stage| stage.base).unwrap_or_default(),
         };
 
         Clock {
@@ -442,7 +666,7 @@ impl Clock {
 
     /// Returns the time control associated with this clock.
     pub fn time_control(&self) -> TimeControl {
-        self.tc
+        self.tc.clone()
     }
 
     /// Get the remaining time for `color`.
@@ -450,20 +674,50 @@ impl Clock {
         self.remaining[color as usize]
     }
 
-    /// Update the clock based on the `elapsed` time and the time control being used.
+    /// Returns the stage of a multi-stage time control which a player's `move_num`th move
+    /// (counting from one) belongs to, along with the number of moves left to make in the stage,
+    /// including that one. Returns `None` if the time control doesn't have stages.
+    pub fn stage(&self, move_num: usize) -> Option<(TimeStage, Option<usize>)> {
+        match &self.tc {
+            TimeControl::Stages(stages) => current_stage(stages, move_num)
+                .map(|(stage, moves_left)| (*stage, moves_left)),
+            _ => None,
+        }
+    }
+
+    /// Update the clock based on the `elapsed` time and the time control being used. `moves` is
+    /// the number of moves `color` has made, including this one. Returns `false` if `color` ran
+    /// out of time.
     pub fn update(&mut self, color: Color, elapsed: Duration, moves: usize) -> bool {
-        if let Some(remaining) = self.remaining[color as usize].checked_sub(elapsed) {
+        let charged = match self.tc {
+            TimeControl::Delay{ delay, .. } => elapsed.checked_sub(delay).unwrap_or_default(),
+            _ => elapsed,
+        };
+        if let Some(remaining) = self.remaining[color as usize].checked_sub(charged) {
             self.remaining[color as usize] = remaining;
         } else {
             self.remaining[color as usize] = Duration::from_secs(0);
             return false; // no time remaining
         }
 
-        match self.tc {
-            TimeControl::Incremental{ inc, .. } => self.remaining[color as usize] += inc,
+        match &self.tc {
+            TimeControl::Incremental{ inc, .. } => self.remaining[color as usize] += *inc,
             TimeControl::Session{ base, mps } => {
                 if moves % mps == 0 {
-                    self.remaining[color as usize] += base;
+                    self.remaining[color as usize] += *base;
+                }
+            },
+            TimeControl::Bronstein{ delay, .. } => {
+                self.remaining[color as usize] += elapsed.min(*delay);
+            },
+            TimeControl::Stages(stages) => {
+                if let Some((stage, moves_left)) = current_stage(stages, moves) {
+                    self.remaining[color as usize] += stage.inc;
+                    if moves_left == Some(1) {
+                        if let Some((next, _)) = current_stage(stages, moves + 1) {
+                            self.remaining[color as usize] += next.base;
+                        }
+                    }
                 }
             },
             _ => { }
@@ -512,7 +766,19 @@ pub enum WinReason {
     /// One player's time ran out.
     Time,
     /// The opponent forfeits.
-    Forfeiture
+    Forfeiture,
+    /// The winner gave check the number of times needed to win in the game's variant.
+    Checks,
+    /// The winner's king reached the center of the board in King of the Hill.
+    KingOfTheHill,
+    /// The loser's king was exploded in Atomic.
+    Explosion,
+    /// The winner lost all of their pieces in one of the losing variants, or captured all of the
+    /// loser's pieces in Horde.
+    PiecesLost,
+    /// The player to move had no moves in one of the losing variants, and the winner was either
+    /// that player in Giveaway, or the player with fewer pieces in Suicide.
+    NoMoves,
 }
 
 impl fmt::Display for WinReason {
@@ -522,6 +788,11 @@ impl fmt::Display for WinReason {
             WinReason::Resignation => "by resignation".fmt(f),
             WinReason::Time => "time expired".fmt(f),
             WinReason::Forfeiture => "by forfeiture".fmt(f),
+            WinReason::Checks => "by checks".fmt(f),
+            WinReason::KingOfTheHill => "king reached the center".fmt(f),
+            WinReason::Explosion => "king exploded".fmt(f),
+            WinReason::PiecesLost => "all pieces lost".fmt(f),
+            WinReason::NoMoves => "no moves left".fmt(f),
         }
     }
 }
@@ -532,11 +803,15 @@ impl fmt::Display for WinReason {
 pub enum DrawReason {
     /// Stalemate
     Stalemate,
-    /// Draw by the fifty-move rule
+    /// Draw claimed by the fifty-move rule
     FiftyMoves,
-    /// Draw by three-fold repetition
+    /// Draw claimed by three-fold repetition
     Repetition,
-    /// Insufficient material
+    /// Draw by the seventy-five-move rule
+    SeventyFiveMoves,
+    /// Draw by five-fold repetition
+    FiveFoldRepetition,
+    /// Insufficient material, so that neither player can checkmate (a dead position)
     Material,
     /// Draw by agreement
     Agreement,
@@ -548,6 +823,8 @@ impl fmt::Display for DrawReason {
             DrawReason::Stalemate => "stalemate".fmt(f),
             DrawReason::FiftyMoves => "fifty-move rule".fmt(f),
             DrawReason::Repetition => "repetition".fmt(f),
+            DrawReason::SeventyFiveMoves => "seventy-five-move rule".fmt(f),
+            DrawReason::FiveFoldRepetition => "five-fold repetition".fmt(f),
             DrawReason::Material => "insufficient material".fmt(f),
             DrawReason::Agreement => "by agreement".fmt(f),
         }
@@ -663,48 +940,110 @@ impl Game {
         self.moves.pop().is_some()
     }
 
+    /// Returns the reason for which a draw can be claimed in the current position, or `None` if
+    /// it can't be claimed or the game is already over.
+    ///
+    /// As in the FIDE Laws of Chess, a draw can be claimed when the current position has occurred
+    /// three times, or when the last fifty moves by each player were made without a pawn move or
+    /// capture. Unlike five-fold repetition and the seventy-five-move rule, these don't end the
+    /// game unless claimed.
+    ///
+    /// ```rust
+    /// use chess::game::{Game, GameResult, DrawReason};
+    ///
+    /// let mut game = Game::new();
+    /// for mv in &["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+    ///     game.make_move_from_str(mv)?;
+    ///     assert!(game.claim_draw().is_none());
+    /// }
+    /// game.make_move_from_str("Ng8")?;
+    /// assert_eq!(game.claimable_draw(), Some(DrawReason::Repetition));
+    /// assert_eq!(game.result(), None);
+    /// assert_eq!(game.claim_draw(), Some(GameResult::Draw(Some(DrawReason::Repetition))));
+    /// # Ok::<(), chess::Error>(())
+    /// ```
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.result.is_some() {
+            None
+        } else if self.moves.three_fold_repetition() {
+            Some(DrawReason::Repetition)
+        } else if self.position().fifty_moves() {
+            Some(DrawReason::FiftyMoves)
+        } else {
+            None
+        }
+    }
+
+    /// Claims a draw in the current position, ending the game and returning the result if the
+    /// claim is valid. Otherwise, returns `None` and the game continues.
+    pub fn claim_draw(&mut self) -> Option<GameResult> {
+        let reason = self.claimable_draw()?;
+        self.result = Some(GameResult::Draw(Some(reason)));
+
+        self.result
+    }
+
     /// Checks if the game is over, and sets `self.result` appropriately
     fn check_game_result(&mut self) {
-        if self.position().fifty_moves() {
-            self.result = Some(GameResult::Draw(Some(DrawReason::FiftyMoves)));
-            return;
-        } else if self.position().insufficient_material() {
-            self.result = Some(GameResult::Draw(Some(DrawReason::Material)));
-            return;
-        } else if self.moves.three_fold_repetition() {
-            self.result = Some(GameResult::Draw(Some(DrawReason::Repetition)));
+        let pos = self.position();
+        if let Some(winner) = pos.variant_winner() {
+            let reason = match pos.variant() {
+                Variant::KingOfTheHill => WinReason::KingOfTheHill,
+                Variant::Atomic => WinReason::Explosion,
+                _ => WinReason::Checks,
+            };
+            self.result = Some(GameResult::Win(winner, Some(reason)));
             return;
         }
 
-        for mv in self.position().moves() {
-            if mv.make().is_ok() {
-                self.result = None;
-                return;
-            }
+        // checkmate and stalemate take precedence over the draws below
+        if self.position().moves().any(|mv| mv.make().is_ok()) {
+            self.result = if self.position().insufficient_material() {
+                Some(GameResult::Draw(Some(DrawReason::Material)))
+            } else if self.moves.five_fold_repetition() {
+                Some(GameResult::Draw(Some(DrawReason::FiveFoldRepetition)))
+            } else if self.position().seventy_five_moves() {
+                Some(GameResult::Draw(Some(DrawReason::SeventyFiveMoves)))
+            } else {
+                None
+            };
+            return;
         }
 
         // no legal moves if we reach this point
-        if self.position().in_check() {
-            self.result = Some(GameResult::Win(
-                !self.position().turn(),
-                Some(WinReason::Checkmate)
-            ));
-        } else {
-            self.result = Some(GameResult::Draw(Some(DrawReason::Stalemate)));
-        }
+        let pos = self.position();
+        self.result = Some(match pos.winner_without_moves() {
+            Some(winner) if pos.in_check() => GameResult::Win(winner, Some(WinReason::Checkmate)),
+            Some(winner) if pos.occupied_by(winner).is_empty()
+                || pos.occupied_by(!winner).is_empty() => {
+                GameResult::Win(winner, Some(WinReason::PiecesLost))
+            },
+            Some(winner) => GameResult::Win(winner, Some(WinReason::NoMoves)),
+            None => GameResult::Draw(Some(DrawReason::Stalemate)),
+        });
     }
 
     /// Returns a PGN representation of the game, using the given tags.
     ///
     /// This method will add a "Result" tag, and, if needed, the "SetUp" and "FEN" tags to the
-    /// given tags.
+    /// given tags. A "TimeControl" tag is also added unless one is given.
     pub fn to_pgn(&self, tags: &HashMap<String, String>) -> String {
         let mut tags = tags.to_owned();
 
+        tags.entry("TimeControl".to_owned()).or_insert_with(|| {
+            match self.clock.time_control() {
+                TimeControl::Infinite => "-".to_owned(), // as written by PGN for no time control
+                tc => tc.to_string(),
+            }
+        });
+
         if self.moves.initial_position().zobrist_key() != Position::default().zobrist_key() {
             tags.insert("SetUp".to_owned(), "1".to_owned());
             tags.insert("FEN".to_owned(), self.moves.initial_position().to_string());
         }
+        if self.moves.initial_position().variant() != Variant::Normal {
+            tags.insert("Variant".to_owned(), self.moves.initial_position().variant().to_string());
+        }
 
         let result = match self.result {
             Some(GameResult::Win(Color::White, _)) => "1-0",
@@ -758,3 +1097,118 @@ impl Game {
         pgn
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_delays_and_stages() {
+        let secs = Duration::from_secs;
+
+        let mut clock = Clock::new(TimeControl::Delay{ base: secs(60), delay: secs(5) });
+        assert!(clock.update(Color::White, secs(3), 1));
+        assert_eq!(clock.remaining(Color::White), secs(60));
+        assert!(clock.update(Color::White, secs(8), 2));
+        assert_eq!(clock.remaining(Color::White), secs(57));
+
+        let mut clock = Clock::new(TimeControl::Bronstein{ base: secs(60), delay: secs(5) });
+        assert!(clock.update(Color::White, secs(3), 1));
+        assert_eq!(clock.remaining(Color::White), secs(60));
+        assert!(clock.update(Color::White, secs(8), 2));
+        assert_eq!(clock.remaining(Color::White), secs(57));
+        assert!(!clock.update(Color::White, secs(58), 3));
+
+        // the second stage's time is added after the 2nd move, and the last stage repeats
+        let tc = TimeControl::from_pgn_str("2/60+1:30+2:3/10").expect("valid time control");
+        let mut clock = Clock::new(tc);
+        assert_eq!(clock.remaining(Color::Black), secs(60));
+        assert!(clock.update(Color::Black, secs(10), 1));
+        assert_eq!(clock.remaining(Color::Black), secs(51));
+        assert!(clock.update(Color::Black, secs(10), 2));
+        assert_eq!(clock.remaining(Color::Black), secs(72));
+        assert_eq!(clock.stage(3), Some((TimeStage::new(None, secs(30), secs(2)), None)));
+
+        let tc = TimeControl::from_pgn_str("2/60:3/10").expect("valid time control");
+        let clock = Clock::new(tc);
+        assert_eq!(clock.stage(2), Some((TimeStage::new(Some(2), secs(60), secs(0)), Some(1))));
+        assert_eq!(clock.stage(3), Some((TimeStage::new(Some(3), secs(10), secs(0)), Some(3))));
+        assert_eq!(clock.stage(9), Some((TimeStage::new(Some(3), secs(10), secs(0)), Some(3))));
+        assert_eq!(clock.stage(10), Some((TimeStage::new(Some(3), secs(10), secs(0)), Some(2))));
+
+        assert_eq!(TimeControl::from_pgn_str("40/300"),
+            Ok(TimeControl::Session{ base: secs(300), mps: 40 }));
+        assert_eq!(TimeControl::from_pgn_str("90.5"),
+            Ok(TimeControl::SuddenDeath(Duration::from_millis(90_500))));
+        for s in &["", "40/", "/300", "0/300", "300+", "300++1", "-5", "60:", "x"] {
+            assert_eq!(TimeControl::from_pgn_str(s), Err(Error::ParseError), "{}", s);
+        }
+    }
+
+    #[test]
+    fn time_control_strings() {
+        let secs = Duration::from_secs;
+        let controls = [
+            ("inf", TimeControl::Infinite),
+            ("st=5", TimeControl::Exact(secs(5))),
+            ("300", TimeControl::SuddenDeath(secs(300))),
+            ("60+1", TimeControl::Incremental{ base: secs(60), inc: secs(1) }),
+            ("40/300", TimeControl::Session{ base: secs(300), mps: 40 }),
+            ("300d5", TimeControl::Delay{ base: secs(300), delay: secs(5) }),
+            ("300b2.5",
+                TimeControl::Bronstein{ base: secs(300), delay: Duration::from_millis(2500) }),
+            ("40/5400+30:1800+30", TimeControl::Stages(vec![
+                TimeStage::new(Some(40), secs(5400), secs(30)),
+                TimeStage::new(None, secs(1800), secs(30)),
+            ])),
+        ];
+
+        for (s, tc) in controls.iter() {
+            assert_eq!(s.parse::<TimeControl>().as_ref(), Ok(tc));
+            assert_eq!(tc.to_string(), *s);
+        }
+        assert_eq!("-".parse(), Ok(TimeControl::Infinite));
+        for s in &["", "infinite", "st=", "st=x", "d5", "300d", "300b5b5"] {
+            assert_eq!(s.parse::<TimeControl>(), Err(Error::ParseError), "{}", s);
+        }
+
+        let mut game = Game::new();
+        assert!(game.to_pgn(&HashMap::new()).contains("[TimeControl \"-\"]"));
+        game.set_time_control(TimeControl::Incremental{ base: secs(60), inc: secs(1) });
+        assert!(game.to_pgn(&HashMap::new()).contains("[TimeControl \"60+1\"]"));
+    }
+
+    #[test]
+    fn five_fold_repetition_ends_the_game() {
+        let mut game = Game::new();
+        for _ in 0..4 {
+            assert_eq!(game.result(), None);
+            for mv in &["Nf3", "Nf6", "Ng1", "Ng8"] {
+                game.make_move_from_str(mv).expect("legal move");
+            }
+        }
+        assert_eq!(game.result(), Some(GameResult::Draw(Some(DrawReason::FiveFoldRepetition))));
+        assert_eq!(game.claimable_draw(), None);
+    }
+
+    #[test]
+    fn move_rules() {
+        let fen = "6k1/5ppp/8/8/8/8/8/3R2K1 w - - 149 100";
+        let start = || Game::starting_at(Position::from_fen_str(fen).expect("valid fen"));
+
+        // the fifty-move rule must be claimed, but the seventy-five-move rule is automatic
+        let mut game = start();
+        game.make_move_from_str("Kf1").expect("legal move");
+        assert_eq!(game.result(), Some(GameResult::Draw(Some(DrawReason::SeventyFiveMoves))));
+        let mut game = Game::starting_at(
+            Position::from_fen_str(&fen.replace("149", "99")).expect("valid fen"));
+        game.make_move_from_str("Kf1").expect("legal move");
+        assert_eq!(game.result(), None);
+        assert_eq!(game.claim_draw(), Some(GameResult::Draw(Some(DrawReason::FiftyMoves))));
+
+        // checkmate takes precedence
+        let mut game = start();
+        game.make_move_from_str("Rd8#").expect("legal move");
+        assert_eq!(game.result(), Some(GameResult::Win(Color::White, Some(WinReason::Checkmate))));
+    }
+}
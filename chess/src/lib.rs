@@ -532,15 +532,19 @@ impl From<Square> for usize {
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 pub mod bitboard;
 mod moves;
-pub use moves::{ValidMove, Move, MoveRc, MoveType, Promotion, MoveBuilder};
+pub use moves::{ValidMove, Move, MoveRc, MoveType, Promotion, MoveBuilder, CheckMark};
 mod position;
 pub use position::Position;
 pub use position::builder::PositionBuilder;
 pub use position::zobrist::Zobrist;
 pub use position::move_iter::{Moves, PromotionsAndCaptures};
+mod variant;
+pub use variant::Variant;
 pub mod game;
 
 pub mod variations;
+pub mod perft;
+pub mod epd;
 
 mod error;
 
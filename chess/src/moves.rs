@@ -12,6 +12,7 @@ use super::*;
 use bitboard::*;
 use Piece::*;
 use Color::*;
+use position::Castle;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// Indicates whether a move is of a special type, such as castling.
@@ -27,6 +28,9 @@ pub enum MoveType {
     EnPassant,
     /// A pawn promotion to the given piece type.
     Promotion(Promotion),
+    /// A piece dropped from the mover's pocket, in variants with drops. The origin of a drop is
+    /// the same as its destination.
+    Drop,
     /// Skip this player's move (not legal, but useful to the engine).
     NullMove,
 }
@@ -57,7 +61,8 @@ impl Default for MoveType {
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
-/// Which piece to promote to for a promotion move.
+/// Which piece to promote to for a promotion move. Promotions to a king are only allowed in
+/// variants where the king isn't royal.
 ///
 /// Can easily be converted to [`Piece`](enum.Piece.html).
 ///
@@ -73,6 +78,7 @@ pub enum Promotion {
     ToBishop = 2,
     ToRook = 3,
     ToQueen = 4,
+    ToKing = 5,
 }
 
 use Promotion::*;
@@ -89,6 +95,55 @@ impl From<Promotion> for Piece {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Whether a move gives check or checkmate, as indicated by a `+` or `#` after the move in SAN.
+///
+/// When given to a [`MoveBuilder`](struct.MoveBuilder.html), the mark is verified against the
+/// position after the move.
+///
+/// ```
+/// use chess::{CheckMark, Error, MoveBuilder, MoveRc, Position};
+///
+/// let fen = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2";
+/// let pos = Position::from_fen_str(fen)?;
+/// let mv = MoveRc::from("Qh4#".parse::<MoveBuilder>()?.validate(&pos)?);
+/// assert_eq!(mv.to_string(), "Qh4#");
+/// assert_eq!("Qh4+".parse::<MoveBuilder>()?.validate(&pos), Err(Error::IncorrectCheckMark));
+/// assert_eq!(CheckMark::Check.to_string(), "+");
+/// # Ok::<(), chess::Error>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CheckMark {
+    /// The move gives check, written `+`
+    Check,
+    /// The move gives checkmate, or otherwise ends the game with the opponent in check, written
+    /// `#`
+    Checkmate,
+}
+
+impl CheckMark {
+    /// Returns the mark for the move which led to `pos`, or `None` if the player to move isn't in
+    /// check.
+    fn after(pos: &Position) -> Option<CheckMark> {
+        if !pos.in_check() {
+            None
+        } else if pos.moves().any(|mv| mv.make().is_ok()) {
+            Some(CheckMark::Check)
+        } else {
+            Some(CheckMark::Checkmate)
+        }
+    }
+}
+
+impl fmt::Display for CheckMark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckMark::Check => "+",
+            CheckMark::Checkmate => "#",
+        }.fmt(f)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// A valid (pseudo-legal) move from a specific position.
 ///
@@ -179,7 +234,7 @@ impl<'a> Move<'a> {
     ///
     /// Null moves are not actually legal moves, but are useful to the engine.
     pub fn null_move(pos: &'a Position) -> Move<'a> {
-        let orig = pos.king_location(pos.turn());
+        let orig = pos.occupied_by_piece(pos.turn(), King).peek().unwrap_or_default();
 
         Move {
             pos,
@@ -223,13 +278,17 @@ impl<'a> fmt::Display for Move<'a> {
     /// "{:#}" -- Coordinate Notation (eg g1f3, e7e8q, or e1g1)
     ///
     /// "{:+#}" -- Alternate Long Algebraic Notation (eg Ng1f3, e7e8Q, or Ke1g1)
+    ///
+    /// Drops are written the same way in every format (eg N@f3 or P@e4).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.move_type == MoveType::Castling && !f.alternate() {
-            match self.dest.file() {
-                File::G => return "O-O".fmt(f),
-                File::C => return "O-O-O".fmt(f),
-                _ => unreachable!(),
+            if self.dest.file() > self.orig.file() {
+                return "O-O".fmt(f);
+            } else {
+                return "O-O-O".fmt(f);
             }
+        } else if self.move_type == MoveType::Drop {
+            return format!("{}@{}", self.piece, self.dest).fmt(f);
         }
 
         let mut s = String::new();
@@ -360,77 +419,88 @@ impl From<Move<'_>> for MoveRc {
 impl fmt::Display for MoveRc {
     /// The move is formatted as follows:
     ///
-    /// "{}" -- Standard Algebraic Notation (eg Nf3, e8=Q, or O-O)
+    /// "{}" -- Standard Algebraic Notation (eg Nf3, e8=Q, Qxf7#, or O-O)
     ///
-    /// "{:+}" -- Long Algebraic Notation (eg Ng1-f3, e7-e8=Q, or O-O)
+    /// "{:+}" -- Long Algebraic Notation (eg Ng1-f3, e7-e8=Q, Qh5xf7#, or O-O)
     ///
     /// "{:#}" -- Coordinate Notation (eg g1f3, e7e8q, or e1g1)
     ///
     /// "{:+#}" -- Alternate Long Algebraic Notation (eg Ng1f3, e7e8Q, or Ke1g1)
+    ///
+    /// Drops are written the same way in every format (eg N@f3 or P@e4), except that Standard and
+    /// Long Algebraic Notation are followed by `+` or `#` for moves which give check or checkmate.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.move_type == MoveType::Castling && !f.alternate() {
-            match self.dest.file() {
-                File::G => return "O-O".fmt(f),
-                File::C => return "O-O-O".fmt(f),
-                _ => unreachable!(),
-            }
-        }
-
         let mut s = String::new();
 
-        if self.piece != Pawn && (!f.alternate() || f.sign_plus()) {
-            s += &self.piece.to_string();
-        }
-
-        if f.alternate() || f.sign_plus() {
-            s += &self.orig.to_string();
-        } else if self.piece == Pawn {
-            if self.capt_pc.is_some() {
-                s += &self.orig.file().to_string();
+        if self.move_type == MoveType::Castling && !f.alternate() {
+            if self.dest.file() > self.orig.file() {
+                s += "O-O";
+            } else {
+                s += "O-O-O";
             }
+        } else if self.move_type == MoveType::Drop {
+            s += &format!("{}@{}", self.piece, self.dest);
         } else {
-            let all_pieces = self.pos.occupied_by_piece(self.pos.turn(), self.piece);
-            let attacks = match self.piece {
-                Pawn => unreachable!(),
-                Knight => knight_attacks(self.dest),
-                Bishop => bishop_attacks(self.dest, self.pos.occupied()),
-                Rook => rook_attacks(self.dest, self.pos.occupied()),
-                Queen => queen_attacks(self.dest, self.pos.occupied()),
-                King => king_attacks(self.dest),
-            };
-            let eligible = all_pieces & attacks;
+            if self.piece != Pawn && (!f.alternate() || f.sign_plus()) {
+                s += &self.piece.to_string();
+            }
 
-            if eligible != self.orig.into() {
-                if eligible & self.orig.file().into() == self.orig.into() {
-                    s += &self.orig.file().to_string()
-                } else if eligible & self.orig.rank().into() == self.orig.into() {
-                    s += &self.orig.rank().to_string()
-                } else {
-                    s += &self.orig.to_string();
+            if f.alternate() || f.sign_plus() {
+                s += &self.orig.to_string();
+            } else if self.piece == Pawn {
+                if self.capt_pc.is_some() {
+                    s += &self.orig.file().to_string();
+                }
+            } else {
+                let all_pieces = self.pos.occupied_by_piece(self.pos.turn(), self.piece);
+                let attacks = match self.piece {
+                    Pawn => unreachable!(),
+                    Knight => knight_attacks(self.dest),
+                    Bishop => bishop_attacks(self.dest, self.pos.occupied()),
+                    Rook => rook_attacks(self.dest, self.pos.occupied()),
+                    Queen => queen_attacks(self.dest, self.pos.occupied()),
+                    King => king_attacks(self.dest),
+                };
+                let eligible = all_pieces & attacks;
+
+                if eligible != self.orig.into() {
+                    if eligible & self.orig.file().into() == self.orig.into() {
+                        s += &self.orig.file().to_string()
+                    } else if eligible & self.orig.rank().into() == self.orig.into() {
+                        s += &self.orig.rank().to_string()
+                    } else {
+                        s += &self.orig.to_string();
+                    }
                 }
             }
-        }
 
-        if !f.alternate() {
-            if self.capt_pc.is_some() {
-                s += "x";
-            } else if f.sign_plus() {
-                s += "-";
+            if !f.alternate() {
+                if self.capt_pc.is_some() {
+                    s += "x";
+                } else if f.sign_plus() {
+                    s += "-";
+                }
             }
-        }
 
-        s += &self.dest.to_string();
+            s += &self.dest.to_string();
 
-        if let MoveType::Promotion(prom_pc) = self.move_type {
-            if !f.alternate() {
-                s += "=";
+            if let MoveType::Promotion(prom_pc) = self.move_type {
+                if !f.alternate() {
+                    s += "=";
+                }
+
+                s += &Piece::from(prom_pc).to_string();
             }
 
-            s += &Piece::from(prom_pc).to_string();
+            if f.alternate() && !f.sign_plus() {
+                s.make_ascii_lowercase();
+            }
         }
 
-        if f.alternate() && !f.sign_plus() {
-            s.make_ascii_lowercase();
+        if !f.alternate() {
+            if let Some(mark) = self.make().ok().and_then(|pos| CheckMark::after(&pos)) {
+                s += &mark.to_string();
+            }
         }
 
         s.fmt(f)
@@ -505,7 +575,9 @@ pub struct MoveBuilder {
     orig_rank: Option<Rank>,
     dest: Option<Square>,
     prom_pc: Option<Promotion>,
-    castle_dest: Option<File>,
+    castle: Option<Castle>,
+    drop: bool,
+    check_mark: Option<CheckMark>,
 }
 
 impl<'a> MoveBuilder {
@@ -517,7 +589,9 @@ impl<'a> MoveBuilder {
             orig_rank: None,
             dest: None,
             prom_pc: None,
-            castle_dest: None,
+            castle: None,
+            drop: false,
+            check_mark: None,
         }
     }
 
@@ -561,10 +635,10 @@ impl<'a> MoveBuilder {
     /// Sets this as a king-side castling move for `turn`
     pub fn castle_king_side(&mut self) -> &mut Self {
         self.piece = Some(King);
-        self.orig_file = Some(File::E);
+        self.orig_file = None;
         self.orig_rank = None;
         self.dest = None;
-        self.castle_dest = Some(File::G);
+        self.castle = Some(Castle::KingSide);
         self.prom_pc = None;
         self
     }
@@ -572,27 +646,91 @@ impl<'a> MoveBuilder {
     /// Sets this as a queen-side castling move for `turn`
     pub fn castle_queen_side(&mut self) -> &mut Self {
         self.piece = Some(King);
-        self.orig_file = Some(File::E);
+        self.orig_file = None;
         self.orig_rank = None;
         self.dest = None;
-        self.castle_dest = Some(File::C);
+        self.castle = Some(Castle::QueenSide);
+        self.prom_pc = None;
+        self
+    }
+
+    /// Sets this as a drop of `piece` from the pocket of `turn`
+    pub fn drop_piece(&mut self, piece: Piece) -> &mut Self {
+        self.piece = Some(piece);
+        self.orig_file = None;
+        self.orig_rank = None;
+        self.castle = None;
         self.prom_pc = None;
+        self.drop = true;
+        self
+    }
+
+    /// Sets or clears whether the move is expected to give check or checkmate
+    pub fn check_mark(&mut self, mark: Option<CheckMark>) -> &mut Self {
+        self.check_mark = mark;
         self
     }
 
     /// Validates the pseudo-legality of the move, and returns a `Move` tied to `pos`
     ///
     /// Note that this function does not validate if the move leaves the mover in check or if it
-    /// involves castling through check. Use `Move::make()` to perform those validations.
+    /// involves castling through check. Use `Move::make()` to perform those validations. If a
+    /// check mark was given, it must match the position after the move, unless the move is illegal.
     pub fn validate(&self, pos: &'a Position) -> Result<Move<'a>> {
+        let mv = self.validate_move(pos)?;
+
+        if let (Some(mark), Ok(new_pos)) = (self.check_mark, mv.make()) {
+            if CheckMark::after(&new_pos) != Some(mark) {
+                return Err(Error::IncorrectCheckMark);
+            }
+        }
+
+        Ok(mv)
+    }
+
+    /// Validates the pseudo-legality of the move, ignoring the check mark.
+    fn validate_move(&self, pos: &'a Position) -> Result<Move<'a>> {
         let mut move_type = MoveType::Standard;
 
+        // castling given by side, as in O-O
+        if let Some(side) = self.castle {
+            if !pos.can_castle(side) {
+                return Err(Error::IllegalMove);
+            } else if pos.variant().has_forced_captures() && pos.capture_available() {
+                return Err(Error::CaptureRequired);
+            }
+
+            return Ok(Move{
+                pos,
+                piece: King,
+                orig: pos.castling_king(pos.turn()),
+                dest: pos.castling_destination(side),
+                capt_pc: None,
+                move_type: MoveType::Castling,
+            });
+        }
+
+        // drop from the pocket, as in N@f3
+        if self.drop {
+            let piece = self.piece.unwrap_or(Pawn);
+            let dest = self.dest.ok_or(Error::AmbiguousMove)?;
+            if !pos.drop_squares(piece).contains(dest) || self.prom_pc.is_some() {
+                return Err(Error::IllegalMove);
+            }
+
+            return Ok(Move{
+                pos,
+                piece,
+                orig: dest,
+                dest,
+                capt_pc: None,
+                move_type: MoveType::Drop,
+            });
+        }
+
         // Step 1: Disambiguation
         let dest = if let Some(dest) = self.dest {
             dest
-        } else if let Some(dest_file) = self.castle_dest {
-            let rank = if pos.turn() == White { Rank::R1 } else { Rank::R8 };
-            Square::from_coord(dest_file, rank)
         } else {
             return Err(Error::AmbiguousMove);
         };
@@ -600,8 +738,6 @@ impl<'a> MoveBuilder {
         let orig;
         if let (Some(file), Some(rank)) = (self.orig_file, self.orig_rank) {
             orig = Square::from_coord(file, rank);
-        } else if let (Some(file), None) = (self.orig_file, self.dest) {
-            orig = Square::from_coord(file, dest.rank());
         } else {
             let mask: Bitboard = match (self.orig_file, self.orig_rank) {
                 (Some(file), None) => file.into(),
@@ -654,6 +790,26 @@ impl<'a> MoveBuilder {
             None => return Err(Error::IllegalMove),
         };
 
+        // castling given by the king's origin and destination
+        if piece == King {
+            if let Some(side) = pos.castling_side(orig, dest) {
+                if !pos.can_castle(side) || self.prom_pc.is_some() {
+                    return Err(Error::IllegalMove);
+                } else if pos.variant().has_forced_captures() && pos.capture_available() {
+                    return Err(Error::CaptureRequired);
+                }
+
+                return Ok(Move{
+                    pos,
+                    piece,
+                    orig,
+                    dest,
+                    capt_pc: None,
+                    move_type: MoveType::Castling,
+                });
+            }
+        }
+
         // Step 3: determine capture piece, if any, including en passant
         let capt_pc = match pos.piece_at(dest) {
             Some((color, capt_pc)) => {
@@ -680,18 +836,22 @@ impl<'a> MoveBuilder {
         // Step 4: validate piece movement
         match piece {
             Pawn => {
-                let (forward, initial) = if pos.turn() == White {
-                    (1, Rank::R2)
+                let (forward, initial, first) = if pos.turn() == White {
+                    (1, Rank::R2, Rank::R1)
                 } else {
-                    (-1, Rank::R7)
+                    (-1, Rank::R7, Rank::R8)
                 };
+                let first_rank_pawns = pos.variant().has_first_rank_pawns(pos.turn());
+                let passed = Bitboard::from(dest).shift_y(-forward);
 
                 let rank_diff = (dest.rank() as i8 - orig.rank() as i8) * forward;
                 let file_diff = dest.file() as i8 - orig.file() as i8;
                 let file_diff = file_diff * file_diff;
 
                 match (file_diff, rank_diff, capt_pc) {
-                    (0, 2, None) if orig.rank() == initial => move_type = MoveType::Advance2,
+                    (0, 2, None) if (orig.rank() == initial
+                        || first_rank_pawns && orig.rank() == first)
+                        && passed.is_disjoint(pos.occupied()) => move_type = MoveType::Advance2,
                     (0, 1, None) | (1, 1, Some(_)) => {
                         // check for promotions
                         match dest.rank() {
@@ -730,39 +890,23 @@ impl<'a> MoveBuilder {
                 }
             },
             King => {
-                match (orig.file(), dest.file()) {
-                    (File::E, File::G) => {
-                        if pos.has_king_side_castling_rights(pos.turn())
-                            && rank_attacks(orig, pos.occupied())
-                            .intersects(File::H.into()) {
-                            move_type = MoveType::Castling;
-                        } else {
-                            return Err(Error::IllegalMove);
-                        }
-                    },
-                    (File::E, File::C) => {
-                        if pos.has_queen_side_castling_rights(pos.turn())
-                            && rank_attacks(orig, pos.occupied())
-                            .intersects(File::A.into()) {
-                            move_type = MoveType::Castling;
-                        } else {
-                            return Err(Error::IllegalMove);
-                        }
-                    },
-                    _ => {
-                        if !king_attacks(orig).contains(dest) {
-                            return Err(Error::IllegalMove);
-                        }
-                    }
+                if !king_attacks(orig).contains(dest) {
+                    return Err(Error::IllegalMove);
                 }
             },
         }
 
         // Step 5: validate promotions
-        if self.prom_pc.is_some() && !move_type.is_promotion() {
+        if self.prom_pc.is_some() && !move_type.is_promotion()
+            || move_type == MoveType::Promotion(ToKing) && pos.variant().has_royal_king() {
             return Err(Error::IllegalMove);
         }
 
+        // Step 6: captures are compulsory in some variants
+        if capt_pc.is_none() && pos.variant().has_forced_captures() && pos.capture_available() {
+            return Err(Error::CaptureRequired);
+        }
+
         Ok(Move{
             pos,
             piece,
@@ -780,6 +924,20 @@ impl FromStr for MoveBuilder {
     fn from_str(s: &str) -> Result<MoveBuilder> {
         let mut builder = MoveBuilder::new();
 
+        // remove any annotation glyph, as in Nf3!?, then the check or checkmate mark
+        let s = ["!!", "??", "!?", "?!", "!", "?"].iter()
+            .find_map(|glyph| s.strip_suffix(glyph))
+            .unwrap_or(s);
+        let s = if let Some(s) = s.strip_suffix('+') {
+            builder.check_mark(Some(CheckMark::Check));
+            s
+        } else if let Some(s) = s.strip_suffix('#') {
+            builder.check_mark(Some(CheckMark::Checkmate));
+            s
+        } else {
+            s
+        };
+
         // handle PGN/SAN-style castling notation
         match s {
             "O-O" | "0-0" => {
@@ -793,6 +951,17 @@ impl FromStr for MoveBuilder {
             _ => {},
         }
 
+        // handle drops, as in N@f3 (the piece may be omitted for pawns)
+        if let Some(i) = s.find('@') {
+            let piece = match &s[..i] {
+                "" => Pawn,
+                piece => piece.parse()?,
+            };
+            let dest = s[i+1..].parse()?;
+            builder.drop_piece(piece).destination(dest);
+            return Ok(builder);
+        }
+
         let mut chars = s.chars();
 
         let mut next = chars.next_back();
@@ -803,22 +972,13 @@ impl FromStr for MoveBuilder {
             return Err(Error::ParseError);
         };
 
-        // remove check or checkmate characters
-        if c == "+" || c == "#" {
-            next = chars.next_back();
-            c = if let Some(c) = next {
-                c.to_string()
-            } else {
-                return Err(Error::ParseError);
-            };
-        }
-
         // promotion piece
         let prom_pc = match c.as_str() {
             "Q" | "q" => Some(Promotion::ToQueen),
             "R" | "r" => Some(Promotion::ToRook),
             "B" | "b" => Some(Promotion::ToBishop),
             "N" | "n" => Some(Promotion::ToKnight),
+            "K" | "k" => Some(Promotion::ToKing),
             _ => None, // let validate move determine move type
         };
 
@@ -950,4 +1110,56 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn validate_drop() -> Result<(), crate::Error> {
+        use crate::{Position, MoveBuilder, ValidMove, MoveType, Piece, Square, Variant};
+
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3[NP] w - - 0 1",
+            Variant::Crazyhouse)?;
+        let mv = "N@f3".parse::<MoveBuilder>()?
+            .validate(&pos)?;
+        assert_eq!(mv.move_type(), MoveType::Drop);
+        assert_eq!(mv.origin(), Square::F3);
+
+        let mv = "@e4".parse::<MoveBuilder>()?
+            .validate(&pos)?;
+        assert_eq!(mv.piece(), Piece::Pawn);
+        assert_eq!(mv.to_string(), "P@e4");
+
+        assert!("P@e8".parse::<MoveBuilder>()?.validate(&pos).is_err());
+        assert!("B@e4".parse::<MoveBuilder>()?.validate(&pos).is_err());
+        assert!("N@e1".parse::<MoveBuilder>()?.validate(&pos).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_marks() -> Result<(), crate::Error> {
+        use crate::{Position, MoveBuilder, MoveRc, Error, Variant};
+
+        let pos = Position::from_fen_str("3k4/P7/8/8/8/8/8/R3K2R w KQ - 0 1")?;
+        let san = |s: &str, pos: &Position| -> Result<MoveRc, Error> {
+            Ok(s.parse::<MoveBuilder>()?.validate(pos)?.into())
+        };
+        assert_eq!(san("a8=Q+", &pos)?.to_string(), "a8=Q+");
+        assert_eq!(san("a8=Q", &pos)?.to_string(), "a8=Q+");
+        assert_eq!(format!("{:+}", san("O-O-O+", &pos)?), "O-O-O+");
+        assert_eq!(format!("{:+}", san("Rh8+!?", &pos)?), "Rh1-h8+");
+        assert_eq!(format!("{:#}", san("Rh8+", &pos)?), "h1h8");
+        assert_eq!(san("Rh7??", &pos)?.to_string(), "Rh7");
+        assert_eq!(san("Rh7+", &pos), Err(Error::IncorrectCheckMark));
+        assert_eq!(san("a8=Q#", &pos), Err(Error::IncorrectCheckMark));
+        assert_eq!(san("Rh8!!!", &pos), Err(Error::ParseError));
+
+        // the third check ends the game in Three-check
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0",
+            Variant::ThreeCheck)?;
+        assert_eq!(san("Ra8", &pos)?.to_string(), "Ra8#");
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3[R] w - - 0 1",
+            Variant::Crazyhouse)?;
+        assert_eq!(san("R@e2+?!", &pos)?.to_string(), "R@e2+");
+
+        Ok(())
+    }
 }
@@ -0,0 +1,235 @@
+//! Module for performance testing (perft) of the move generator
+//
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use crate::*;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Counts the number of variations of a given depth from a position, optionally using a hash table
+/// and multiple threads.
+///
+/// # Example
+/// ```rust
+/// use chess::Position;
+/// use chess::perft::Perft;
+///
+/// let pos = Position::new();
+/// let mut perft = Perft::new();
+/// perft.threads(2).hash_size(0x10_0000);
+///
+/// assert_eq!(perft.count(&pos, 4), 197_281);
+///
+/// let divide = perft.divide(&pos, 2);
+/// assert_eq!(divide.len(), 20);
+/// assert!(divide.iter().all(|(_, count)| *count == 20));
+/// ```
+#[derive(Debug)]
+pub struct Perft {
+    threads: usize,
+    hash: Option<PerftHash>,
+}
+
+impl Perft {
+    /// Creates a single-threaded `Perft` with no hash table.
+    pub fn new() -> Self {
+        Perft {
+            threads: 1,
+            hash: None,
+        }
+    }
+
+    /// Sets the number of threads to use. The moves from the root position are divided among the
+    /// threads.
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets the size of the hash table in bytes. A size of zero disables the hash table.
+    pub fn hash_size(&mut self, bytes: usize) -> &mut Self {
+        self.hash = PerftHash::new(bytes);
+        self
+    }
+
+    /// Count the number of variations of the given `depth` from `pos`.
+    pub fn count(&self, pos: &Position, depth: usize) -> u64 {
+        if depth < 2 {
+            perft(pos, depth, None)
+        } else {
+            self.divide(pos, depth).iter().map(|(_, count)| count).sum()
+        }
+    }
+
+    /// Count the number of variations of the given `depth` for each legal move from `pos`. The
+    /// moves are given in coordinate notation, in the order they were generated.
+    ///
+    /// Each move counts as the first ply of its variations, so a `depth` of 0 gives no moves.
+    pub fn divide(&self, pos: &Position, depth: usize) -> Vec<(String, u64)> {
+        if depth < 1 {
+            return Vec::new();
+        }
+
+        let children: Vec<_> = pos.moves()
+            .filter_map(|mv| mv.make().ok().map(|child| (format!("{:#}", mv), child)))
+            .collect();
+
+        let next = AtomicUsize::new(0);
+        let mut counts = vec![0; children.len()];
+        let hash = self.hash.as_ref();
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0 .. self.threads.min(children.len()))
+                .map(|_| scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if let Some((_, child)) = children.get(index) {
+                            results.push((index, perft(child, depth - 1, hash)));
+                        } else {
+                            return results;
+                        }
+                    }
+                }))
+                .collect();
+
+            for worker in workers {
+                for (index, count) in worker.join().expect("perft thread panicked") {
+                    counts[index] = count;
+                }
+            }
+        });
+
+        children.into_iter().map(|(mv, _)| mv).zip(counts).collect()
+    }
+}
+
+impl Default for Perft {
+    fn default() -> Self {
+        Perft::new()
+    }
+}
+
+/// Count the number of variations of the given `depth` from `pos`.
+fn perft(pos: &Position, depth: usize, hash: Option<&PerftHash>) -> u64 {
+    match depth {
+        0 => return 1,
+        1 => return pos.moves().filter(|mv| mv.make().is_ok()).count() as u64,
+        _ => { },
+    }
+
+    let key = u64::from(pos.zobrist_key());
+    if let Some(count) = hash.and_then(|hash| hash.probe(key, depth)) {
+        return count;
+    }
+
+    let mut total = 0;
+    for mv in pos.moves() {
+        if let Ok(pos) = mv.make() {
+            total += perft(&pos, depth - 1, hash);
+        }
+    }
+
+    if let Some(hash) = hash {
+        hash.store(key, depth, total);
+    }
+
+    total
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A hash table of perft results, which can be shared between threads without locking.
+///
+/// Each entry stores the data (count and depth) along with the key XORed with the data, so that an
+/// entry torn by simultaneous writes from two threads fails verification rather than returning
+/// an incorrect count.
+#[derive(Debug)]
+struct PerftHash(Vec<[AtomicU64; 2]>);
+
+impl PerftHash {
+    const DEPTH_BITS: u32 = 8;
+    const DEPTH_MASK: u64 = (1 << Self::DEPTH_BITS) - 1;
+
+    /// Creates a hash table of approximately `bytes` bytes, or `None` if `bytes` is too small.
+    fn new(bytes: usize) -> Option<Self> {
+        let len = bytes / std::mem::size_of::<[AtomicU64; 2]>();
+        if len == 0 {
+            return None;
+        }
+
+        Some(PerftHash((0 .. len).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect()))
+    }
+
+    fn probe(&self, key: u64, depth: usize) -> Option<u64> {
+        let entry = &self.0[(key % self.0.len() as u64) as usize];
+        let check = entry[0].load(Ordering::Relaxed);
+        let data = entry[1].load(Ordering::Relaxed);
+
+        if check ^ data == key && data & Self::DEPTH_MASK == depth as u64 {
+            Some(data >> Self::DEPTH_BITS)
+        } else {
+            None
+        }
+    }
+
+    fn store(&self, key: u64, depth: usize, count: u64) {
+        if depth as u64 > Self::DEPTH_MASK || count >> (64 - Self::DEPTH_BITS) != 0 {
+            return;
+        }
+
+        let entry = &self.0[(key % self.0.len() as u64) as usize];
+        let data = count << Self::DEPTH_BITS | depth as u64;
+        entry[0].store(key ^ data, Ordering::Relaxed);
+        entry[1].store(data, Ordering::Relaxed);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn matches_variations() {
+        let pos: Position = KIWIPETE.parse().expect("INFALLIBLE");
+
+        for depth in 0 ..= 3 {
+            assert_eq!(Perft::new().count(&pos, depth), variations::count(&pos, depth) as u64);
+        }
+    }
+
+    #[test]
+    fn hash_and_threads() {
+        let pos: Position = KIWIPETE.parse().expect("INFALLIBLE");
+        let mut perft = Perft::new();
+        assert_eq!(perft.count(&pos, 4), 4_085_603);
+
+        perft.hash_size(0x10_0000);
+        assert_eq!(perft.count(&pos, 4), 4_085_603);
+
+        perft.threads(4);
+        assert_eq!(perft.count(&pos, 4), 4_085_603);
+    }
+
+    #[test]
+    fn divide() {
+        let pos: Position = KIWIPETE.parse().expect("INFALLIBLE");
+        let divide = Perft::new().threads(3).divide(&pos, 3);
+
+        assert_eq!(divide.len(), 48);
+        assert!(divide.contains(&("e1g1".to_owned(), 2059)));
+        assert!(divide.contains(&("e5f7".to_owned(), 2080)));
+        assert_eq!(divide.iter().map(|(_, count)| count).sum::<u64>(), 97_862);
+
+        assert!(Perft::new().divide(&pos, 0).is_empty());
+        assert_eq!(Perft::new().divide(&pos, 1).len(), 48);
+    }
+}
@@ -33,9 +33,14 @@ pub struct PositionBuilder {
     turn: Color,
     castle_king_side: [ bool; Color::COUNT ],
     castle_queen_side: [ bool; Color::COUNT ],
+    castle_rook_files: [ u8; Color::COUNT ],
     ep_square: Option<Square>,
     draw_plies: usize,
     move_num: usize,
+    variant: Variant,
+    checks: [ u8; Color::COUNT ],
+    pockets: [ [ u8; Piece::COUNT ]; Color::COUNT ],
+    promoted: Bitboard,
 }
 
 impl PositionBuilder {
@@ -46,9 +51,14 @@ impl PositionBuilder {
             turn: White,
             castle_king_side: [ false; Color::COUNT ],
             castle_queen_side: [ false; Color::COUNT ],
+            castle_rook_files: [ 0; Color::COUNT ],
             ep_square: None,
             draw_plies: 0,
             move_num: 1,
+            variant: Variant::Normal,
+            checks: [ 0; Color::COUNT ],
+            pockets: [ [ 0; Piece::COUNT ]; Color::COUNT ],
+            promoted: Bitboard::new(),
         }
     }
 
@@ -82,6 +92,16 @@ impl PositionBuilder {
         self
     }
 
+    /// Sets castling rights for `color` with the rook on `file` of its first rank. Whether the
+    /// rights are king side or queen side depends on which side of the king the rook is on.
+    ///
+    /// This is only needed in Fischer Random Chess, when the castling rook is not the outermost
+    /// rook on its side of the king. Otherwise the rook can be found from the side alone.
+    pub fn can_castle_with_rook(&mut self, color: Color, file: File) -> &mut Self {
+        self.castle_rook_files[color as usize] |= 1 << file as u8;
+        self
+    }
+
     /// Sets or clears the en-passant square (default is `None`)
     pub fn en_passant_square(&mut self, square: Option<Square>) -> &mut Self {
         self.ep_square = square;
@@ -101,6 +121,36 @@ impl PositionBuilder {
         self
     }
 
+    /// Sets the rules used for the position (default is `Variant::Normal`). Castling rights are
+    /// ignored in variants without castling.
+    pub fn variant(&mut self, variant: Variant) -> &mut Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Sets the number of times `color` has given check (default is 0). This may only be non-zero
+    /// in variants where giving check can win the game, and may not be more than the number of
+    /// checks needed to win.
+    pub fn checks_given(&mut self, color: Color, checks: u8) -> &mut Self {
+        self.checks[color as usize] = checks;
+        self
+    }
+
+    /// Sets the number of pieces of type `piece` in the pocket of `color` (default is 0). Pieces
+    /// may only be held in variants with drops, and kings may never be held.
+    pub fn pocket(&mut self, color: Color, piece: Piece, count: u8) -> &mut Self {
+        self.pockets[color as usize][piece as usize] = count;
+        self
+    }
+
+    /// Marks the piece at `square` as having been promoted from a pawn, so that it returns to the
+    /// pocket as a pawn when captured. This is ignored for pawns and kings, and in variants
+    /// without drops.
+    pub fn promoted(&mut self, square: Square) -> &mut Self {
+        self.promoted.insert(square);
+        self
+    }
+
     /// Validates legality and returns a `Position`
     pub fn validate(&self) -> Result<Position> {
         use Error::*;
@@ -119,32 +169,43 @@ impl PositionBuilder {
         }
 
         pos.turn = self.turn;
-        for c in 0..Color::COUNT {
-            if self.castle_king_side[c] {
-                pos.castling_rights[c] |= CASTLE_KING_SIDE;
-            }
-            if self.castle_queen_side[c] {
-                pos.castling_rights[c] |= CASTLE_QUEEN_SIDE;
-            }
-        }
+        pos.variant = self.variant;
         pos.ep_square = self.ep_square;
         pos.draw_plies = self.draw_plies;
         pos.move_num = self.move_num;
+        pos.checks = self.checks;
+        pos.pockets = self.pockets;
+        if self.variant.has_drops() {
+            let pawns_and_kings = pos.occupied_by_piece(White, Pawn)
+                | pos.occupied_by_piece(Black, Pawn)
+                | pos.occupied_by_piece(White, King)
+                | pos.occupied_by_piece(Black, King);
+            pos.promoted = self.promoted & pos.occupied() & !pawns_and_kings;
+        }
 
         // validate position legality
         for c in &[White, Black] {
-            // Step 1: verify exactly one king per side
-            if pos.occupied_by_piece(*c, King).len() != 1 {
+            // Step 1: verify exactly one king per side, where the king is royal and the side has
+            //         one (in Atomic, the player to move may have lost theirs to an explosion)
+            let kings = pos.occupied_by_piece(*c, King).len();
+            let expected = if self.variant.has_king(*c) { 1 } else { 0 };
+            let exploded = self.variant.has_explosions() && kings == 0 && *c == pos.turn;
+            if self.variant.has_royal_king() && kings != expected && !exploded {
                 return Err(InvalidKingCount);
             }
-            // Step 2: no pawns on ranks 1 and 8
-            if pos.occupied_by_piece(*c, Pawn)
-                .intersects(Bitboard::from(Rank::R1) | Rank::R8.into()) {
+            // Step 2: no pawns on ranks 1 and 8, except on the first rank where allowed
+            let mut ranks = Bitboard::from(Rank::R1) | Rank::R8.into();
+            if self.variant.has_first_rank_pawns(*c) {
+                ranks &= !Bitboard::from(back_rank(*c));
+            }
+            if pos.occupied_by_piece(*c, Pawn).intersects(ranks) {
                 return Err(InvalidPawnRank);
             }
         }
-        // Step 3: opponent's king is not attacked
-        if pos.square_attacked_by(pos.king_location(!pos.turn), pos.turn) {
+        // Step 3: opponent's king is not attacked, unless the game was won by an explosion
+        let exploded = self.variant.has_explosions()
+            && pos.occupied_by_piece(pos.turn, King).is_empty();
+        if pos.king_attacked(!pos.turn) && !exploded {
             return Err(KingCapturable);
         }
         // Step 4: if there is an EP square, it must be empty and there must be a pawn to capture
@@ -158,28 +219,109 @@ impl PositionBuilder {
             }
         }
         // Step 5: if castling rights exist, king and rook must be in the correct squares
+        if self.variant.has_castling() {
+            for c in &[White, Black] {
+                self.set_castling_rights(&mut pos, *c)?;
+            }
+        }
+
+        // Step 6: no more checks given than are needed to win
         for c in &[White, Black] {
-            if pos.has_castling_rights(*c) {
-                let r = if *c == White { Rank::R1 } else { Rank::R8 };
+            if pos.checks[*c as usize] > self.variant.winning_checks().unwrap_or(0) {
+                return Err(InvalidCheckCount);
+            }
+        }
 
-                if !pos.occupied_by_piece(*c, King).contains(Square::from_coord(File::E, r)) {
-                    return Err(InvalidCastlingFlags);
+        // Step 7: pieces in hand only in variants with drops, and never a king
+        for c in &[White, Black] {
+            let pocket = &pos.pockets[*c as usize];
+            if pocket.iter().any(|&count| count as usize > MAX_POCKET)
+                || pocket[King as usize] > 0
+                || !self.variant.has_drops() && pocket.iter().any(|&count| count > 0) {
+                return Err(InvalidPocket);
+            }
+        }
+
+        pos.in_check = pos.king_attacked(pos.turn);
+        pos.calc_zobrist();
+
+        Ok(pos)
+    }
+}
+
+impl PositionBuilder {
+    /// Sets the castling rights and castling rook files for `c` in `pos`, verifying that the king
+    /// and rooks are in the correct squares.
+    fn set_castling_rights(&self, pos: &mut Position, c: Color) -> Result<()> {
+        use Error::*;
+
+        if pos.occupied_by_piece(c, King).is_empty() {
+            // a player without a king can't castle
+            let given = self.castle_king_side[c as usize] || self.castle_queen_side[c as usize]
+                || self.castle_rook_files[c as usize] != 0;
+            return if given { Err(InvalidCastlingFlags) } else { Ok(()) };
+        }
+
+        let chess960 = self.variant == Variant::FischerRandom;
+        let rank = back_rank(c);
+        let king = pos.castling_king(c);
+        let rooks = pos.occupied_by_piece(c, Rook) & rank.into();
+        let mut files = [None; 2];
+
+        // rooks given by their files
+        for sq in rooks {
+            if self.castle_rook_files[c as usize] & 1 << sq.file() as u8 != 0 {
+                if sq.file() > king.file() {
+                    files[Castle::KingSide as usize] = Some(sq.file());
+                } else {
+                    files[Castle::QueenSide as usize] = Some(sq.file());
                 }
-                if pos.has_queen_side_castling_rights(*c)
-                    && !pos.occupied_by_piece(*c, Rook).contains(Square::from_coord(File::A, r)) {
-                    return Err(InvalidCastlingFlags);
+            }
+        }
+        let found = files.iter().flatten().count() as u32;
+        if self.castle_rook_files[c as usize].count_ones() != found {
+            return Err(InvalidCastlingFlags);
+        }
+
+        // rooks given by their side, which is the outermost rook on that side
+        for (side, given) in Castle::BOTH.iter().zip(&[
+            self.castle_king_side[c as usize],
+            self.castle_queen_side[c as usize],
+        ]) {
+            if *given {
+                let standard = STANDARD_CASTLING_FILES[*side as usize];
+                let candidates = if chess960 {
+                    rooks & rank_span(rank, king.file(), standard)
+                } else {
+                    rooks & Square::from_coord(standard, rank).into()
+                };
+                let outermost = candidates.into_iter()
+                    .map(|sq| sq.file())
+                    .filter(|&f| f != king.file())
+                    .max_by_key(|&f| (f as i8 - king.file() as i8).abs());
+
+                match outermost {
+                    Some(file) => files[*side as usize] = Some(file),
+                    None => return Err(InvalidCastlingFlags),
                 }
-                if pos.has_king_side_castling_rights(*c)
-                    && !pos.occupied_by_piece(*c, Rook).contains(Square::from_coord(File::H, r)) {
+            }
+        }
+
+        for side in &Castle::BOTH {
+            if let Some(file) = files[*side as usize] {
+                if !pos.occupied_by_piece(c, King).contains(king)
+                    || king.rank() != rank
+                    || !chess960 && (king.file() != File::E
+                        || file != STANDARD_CASTLING_FILES[*side as usize]) {
                     return Err(InvalidCastlingFlags);
                 }
+
+                pos.castling_rights[c as usize] |= side.flag();
+                pos.castling_files[c as usize][*side as usize] = file;
             }
         }
 
-        pos.in_check = pos.square_attacked_by(pos.king_location(pos.turn), !pos.turn);
-        pos.calc_zobrist();
-
-        Ok(pos)
+        Ok(())
     }
 }
 
@@ -61,9 +61,15 @@ pub struct Position {
     in_check: bool,
     ep_square: Option<Square>,
     castling_rights: [u8; Color::COUNT],
+    castling_files: [[File; 2]; Color::COUNT],
 
     draw_plies: usize,
     move_num: usize,
+
+    variant: Variant,
+    checks: [u8; Color::COUNT],
+    pockets: [[u8; Piece::COUNT]; Color::COUNT],
+    promoted: Bitboard,
 }
 
 
@@ -71,6 +77,64 @@ const CASTLE_KING_SIDE: u8 = 0x1;
 const CASTLE_QUEEN_SIDE: u8 = 0x2;
 const CASTLE_BOTH_SIDES: u8 = CASTLE_KING_SIDE | CASTLE_QUEEN_SIDE;
 
+/// The most pieces of one type that a pocket can hold.
+const MAX_POCKET: usize = 16;
+
+/// The starting position of Horde.
+const HORDE_FEN: &str =
+    "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+
+/// The files of the castling rooks in the standard starting position, indexed by `Castle`.
+const STANDARD_CASTLING_FILES: [File; 2] = [File::H, File::A];
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A side of the board to castle on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub (crate) enum Castle {
+    KingSide = 0,
+    QueenSide = 1,
+}
+
+impl Castle {
+    /// Both sides, king side first.
+    pub (crate) const BOTH: [Castle; 2] = [Castle::KingSide, Castle::QueenSide];
+
+    /// Returns the castling rights flag for this side.
+    fn flag(self) -> u8 {
+        match self {
+            Castle::KingSide => CASTLE_KING_SIDE,
+            Castle::QueenSide => CASTLE_QUEEN_SIDE,
+        }
+    }
+
+    /// Returns the files where the king and rook end up after castling on this side.
+    fn destination_files(self) -> (File, File) {
+        match self {
+            Castle::KingSide => (File::G, File::F),
+            Castle::QueenSide => (File::C, File::D),
+        }
+    }
+}
+
+/// Returns the squares of `rank` from file `a` to file `b`, inclusive, in either order.
+fn rank_span(rank: Rank, a: File, b: File) -> Bitboard {
+    let mut span = Bitboard::new();
+    for f in a.min(b) as usize ..= a.max(b) as usize {
+        span.insert(Square::from_coord(f.try_into().expect("INFALLIBLE"), rank));
+    }
+    span
+}
+
+/// Returns the four central squares, which a king must reach to win in King of the Hill.
+fn hill() -> Bitboard {
+    Bitboard::from(Square::D4) | Square::D5.into() | Square::E4.into() | Square::E5.into()
+}
+
+/// Returns the rank where pieces of color `c` start.
+fn back_rank(c: Color) -> Rank {
+    if c == White { Rank::R1 } else { Rank::R8 }
+}
+
 impl Position {
 
     /// Returns the standard starting Position.
@@ -120,8 +184,13 @@ impl Position {
             in_check: false,
             ep_square: None,
             castling_rights: [CASTLE_BOTH_SIDES, CASTLE_BOTH_SIDES],
+            castling_files: [STANDARD_CASTLING_FILES; Color::COUNT],
             draw_plies: 0,
             move_num: 1,
+            variant: Variant::Normal,
+            checks: [0; Color::COUNT],
+            pockets: [[0; Piece::COUNT]; Color::COUNT],
+            promoted: Bitboard::new(),
         };
 
         pos.calc_zobrist();
@@ -140,23 +209,93 @@ impl Position {
             in_check: false,
             ep_square: None,
             castling_rights: [0, 0],
+            castling_files: [STANDARD_CASTLING_FILES; Color::COUNT],
             draw_plies: 0,
             move_num: 1,
+            variant: Variant::Normal,
+            checks: [0; Color::COUNT],
+            pockets: [[0; Piece::COUNT]; Color::COUNT],
+            promoted: Bitboard::new(),
         }
     }
 
+    /// Returns the starting position of the given variant. For Fischer Random Chess, which has no
+    /// single starting position, this is the standard starting position.
+    pub fn new_variant(variant: Variant) -> Position {
+        if variant == Variant::Horde {
+            return Position::from_fen_str_variant(HORDE_FEN, variant).expect("INFALLIBLE");
+        }
+
+        let mut pos = Position::new();
+
+        pos.variant = variant;
+        if !variant.has_castling() {
+            pos.castling_rights = [0, 0];
+        }
+        pos.calc_zobrist();
+
+        pos
+    }
+
     /// Parse a position from a string containing [Forsyth-Edwards
     /// Notation (FEN)](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation).
     pub fn from_fen_str(s: &str) -> Result<Position> {
+        Position::from_fen_str_variant(s, Variant::Normal)
+    }
+
+    /// Parse a position using the rules of `variant` from a FEN string.
+    ///
+    /// For Fischer Random Chess, the castling flags may also be given as the files of the castling
+    /// rooks (as in `HAha`), which is required when the castling rook is not the outermost rook on
+    /// its side of the king. For Crazyhouse, the pieces in hand follow the board in brackets (as in
+    /// `[Qn]`), and promoted pieces are followed by `~`.
+    ///
+    /// ```rust
+    /// use chess::{Position, Variant};
+    ///
+    /// let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+    /// let pos = Position::from_fen_str_variant(fen, Variant::FischerRandom)?;
+    /// assert_eq!(pos.to_fen_str(), fen.replace("HFhf", "KQkq"));
+    /// assert!(Position::from_fen_str(fen).is_err());
+    /// # Ok::<(), chess::Error>(())
+    /// ```
+    pub fn from_fen_str_variant(s: &str, variant: Variant) -> Result<Position> {
         use Error::*;
 
         let mut pos = PositionBuilder::new();
+        pos.variant(variant);
         let mut fields = s.trim().split_whitespace();
 
         // parse the board
         if let Some(board) = fields.next() {
+            // parse the pieces in hand, if present, either in brackets or as a ninth rank
+            let (board, pockets) = match (board.find('['), board.strip_suffix(']')) {
+                (Some(i), Some(board)) => (&board[..i], Some(&board[i+1..])),
+                (None, None) if board.matches('/').count() == Rank::COUNT => {
+                    let i = board.rfind('/').expect("INFALLIBLE");
+                    (&board[..i], Some(&board[i+1..]))
+                },
+                (None, None) => (board, None),
+                _ => return Err(ParseError),
+            };
+            if let Some(pockets) = pockets {
+                let mut counts = [[0u8; Piece::COUNT]; Color::COUNT];
+                for c in pockets.chars() {
+                    let color = if c.is_uppercase() { White } else { Black };
+                    let piece: Piece = c.to_string().parse()?;
+                    let count = &mut counts[color as usize][piece as usize];
+                    *count = count.saturating_add(1);
+                }
+                for c in &[White, Black] {
+                    for p in &[Pawn, Knight, Bishop, Rook, Queen, King] {
+                        pos.pocket(*c, *p, counts[*c as usize][*p as usize]);
+                    }
+                }
+            }
+
             let mut r = Rank::COUNT - 1;
             let mut f = 0;
+            let mut last = None;
             for c in board.chars() {
                 match c {
                     '1' ..= '8' => {
@@ -173,6 +312,12 @@ impl Position {
                             return Err(ParseError);
                         }
                     }
+                    '~' => {
+                        match last.take() {
+                            Some(sq) => { pos.promoted(sq); },
+                            None => return Err(ParseError),
+                        }
+                    }
                     _ => {
                         let sq = match (f.try_into(), r.try_into()) {
                             (Ok(f), Ok(r)) => Square::from_coord(f, r),
@@ -183,6 +328,7 @@ impl Position {
 
                         // set the `sq` as occupied
                         pos.piece(color, piece, sq);
+                        last = Some(sq);
 
                         f += 1;
                     }
@@ -211,6 +357,10 @@ impl Position {
                         'Q' => { pos.can_castle_queen_side(White, true); },
                         'k' => { pos.can_castle_king_side(Black, true); },
                         'q' => { pos.can_castle_queen_side(Black, true); },
+                        'A' ..= 'H' => {
+                            pos.can_castle_with_rook(White, c.to_lowercase().to_string().parse()?);
+                        },
+                        'a' ..= 'h' => { pos.can_castle_with_rook(Black, c.to_string().parse()?); },
                         _ => return Err(ParseError),
                     }
                 }
@@ -241,6 +391,18 @@ impl Position {
             }
         }
 
+        // parse the number of checks given by each player (as in `+2+0`), if present
+        if let Some(checks) = fields.next() {
+            let counts: Vec<_> = checks.split('+').collect();
+            match counts.as_slice() {
+                ["", white, black] => {
+                    pos.checks_given(White, white.parse().map_err(|_| ParseError)?);
+                    pos.checks_given(Black, black.parse().map_err(|_| ParseError)?);
+                },
+                _ => return Err(ParseError),
+            }
+        }
+
         Ok(pos.validate()?)
     }
 
@@ -261,7 +423,7 @@ impl Position {
 
         for r in (0..Rank::COUNT).rev() {
             let mut count = 0;
-            for file in arr.iter() {
+            for (f, file) in arr.iter().enumerate() {
                 if let Some((c, p)) = file[r] {
                     if count > 0 {
                         board += &count.to_string();
@@ -273,6 +435,12 @@ impl Position {
                     } else {
                         board += &p.to_string().to_lowercase();
                     }
+
+                    let sq = Square::from_coord(f.try_into().expect("INFALLIBLE"),
+                        r.try_into().expect("INFALLIBLE"));
+                    if self.promoted.contains(sq) {
+                        board += "~";
+                    }
                 }
                 else {
                     count += 1;
@@ -286,23 +454,57 @@ impl Position {
             }
         }
 
+        // pieces in hand
+        if self.variant.has_drops() {
+            board += "[";
+            for c in &[White, Black] {
+                for p in &[Queen, Rook, Bishop, Knight, Pawn] {
+                    for _ in 0..self.pocket(*c, *p) {
+                        if *c == White {
+                            board += &p.to_string();
+                        } else {
+                            board += &p.to_string().to_lowercase();
+                        }
+                    }
+                }
+            }
+            board += "]";
+        }
+
         // whose turn it is
         let turn = self.turn.to_string();
 
-        // castling rights
+        // castling rights, using the rook's file if it isn't the outermost rook on its side
         let mut castling = String::new();
-        castling += match self.castling_rights[White as usize] {
-            CASTLE_KING_SIDE => "K",
-            CASTLE_QUEEN_SIDE => "Q",
-            CASTLE_BOTH_SIDES => "KQ",
-            _ => "",
-        };
-        castling += match self.castling_rights[Black as usize] {
-            CASTLE_KING_SIDE => "k",
-            CASTLE_QUEEN_SIDE => "q",
-            CASTLE_BOTH_SIDES => "kq",
-            _ => "",
-        };
+        for c in &[White, Black] {
+            let rooks = self.occupied_by_piece(*c, Rook) & back_rank(*c).into();
+            for side in &Castle::BOTH {
+                if self.castling_rights[*c as usize] & side.flag() == 0 {
+                    continue;
+                }
+
+                let king = self.castling_king(*c);
+                let rook = self.castling_rook(*c, *side);
+                let outer = match side {
+                    Castle::KingSide => rank_span(rook.rank(), rook.file(), File::H),
+                    Castle::QueenSide => rank_span(rook.rank(), File::A, rook.file()),
+                };
+                let flag = if (outer & rooks) == rook.into() && king.rank() == rook.rank() {
+                    match side {
+                        Castle::KingSide => "K".to_string(),
+                        Castle::QueenSide => "Q".to_string(),
+                    }
+                } else {
+                    rook.file().to_string().to_uppercase()
+                };
+
+                if *c == White {
+                    castling += &flag;
+                } else {
+                    castling += &flag.to_lowercase();
+                }
+            }
+        }
         if castling == "" {
             castling += "-";
         }
@@ -319,9 +521,16 @@ impl Position {
         // fullmove number
         let full_move_number = self.move_num.to_string();
 
+        // checks given by each player, only in variants where they can win the game
+        let checks = if self.variant.winning_checks().is_some() {
+            format!(" +{}+{}", self.checks[White as usize], self.checks[Black as usize])
+        } else {
+            String::new()
+        };
+
         // return the full fen string
-        format!("{} {} {} {} {} {}", board, turn, castling, ep_square,
-                                     half_move_clock, full_move_number)
+        format!("{} {} {} {} {} {}{}", board, turn, castling, ep_square,
+                                       half_move_clock, full_move_number, checks)
     }
 
     /// Returns the color whose turn it is.
@@ -354,12 +563,157 @@ impl Position {
         self.castling_rights[c as usize] != 0
     }
 
+    /// Returns the rules used for this position.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Returns the number of times `c` has given check. This is only counted in variants where
+    /// giving check can win the game.
+    pub fn checks_given(&self, c: Color) -> u8 {
+        self.checks[c as usize]
+    }
+
+    /// Returns the number of pieces of type `p` in the pocket of `c`, which `c` may drop on the
+    /// board. Pieces are only held in variants with drops.
+    pub fn pocket(&self, c: Color, p: Piece) -> u8 {
+        self.pockets[c as usize][p as usize]
+    }
+
+    /// Returns the squares of pieces which were promoted from pawns, and which return to the pocket
+    /// as pawns when captured. These are only tracked in variants with drops.
+    pub fn promoted(&self) -> Bitboard {
+        self.promoted
+    }
+
+    /// Returns the squares where the player to move can drop a piece of type `p`.
+    pub (crate) fn drop_squares(&self, p: Piece) -> Bitboard {
+        if self.pocket(self.turn, p) == 0 {
+            Bitboard::new()
+        } else if p == Pawn {
+            !self.occ_squares & !(Bitboard::from(Rank::R1) | Rank::R8.into())
+        } else {
+            !self.occ_squares
+        }
+    }
+
+    /// Returns the player who has won by a rule of the position's variant, other than checkmate.
+    ///
+    /// In Three-check, this is the player who has given check three times, in King of the Hill,
+    /// it is the player whose king has reached the center, and in Atomic, it is the player who
+    /// has exploded the opponent's king. No moves are generated once a player has won.
+    pub fn variant_winner(&self) -> Option<Color> {
+        let players = [!self.turn, self.turn];
+
+        if let Some(checks) = self.variant.winning_checks() {
+            players.iter().copied().find(|&c| self.checks[c as usize] >= checks)
+        } else if self.variant == Variant::KingOfTheHill {
+            players.iter().copied().find(|&c| hill().contains(self.king_location(c)))
+        } else if self.variant.has_explosions() {
+            players.iter().copied().find(|&c| self.occupied_by_piece(!c, King).is_empty())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the winner when the player to move has no legal moves, or `None` if the game is
+    /// drawn.
+    ///
+    /// This is the opponent if the player is checkmated, and nobody if they are stalemated, except
+    /// in the losing variants. There, the player without moves wins in Giveaway, and the player
+    /// with fewer pieces wins in Suicide. In Horde, Black wins once White has no pieces left.
+    pub fn winner_without_moves(&self) -> Option<Color> {
+        let pieces = |c: Color| self.occupied_by(c).len();
+
+        match self.variant {
+            Variant::Giveaway => Some(self.turn),
+            Variant::Suicide if pieces(self.turn) < pieces(!self.turn) => Some(self.turn),
+            Variant::Suicide if pieces(self.turn) > pieces(!self.turn) => Some(!self.turn),
+            Variant::Horde if pieces(self.turn) == 0 => Some(!self.turn),
+            _ if self.in_check => Some(!self.turn),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the player to move can capture a piece, which is compulsory in the losing
+    /// variants.
+    pub (crate) fn capture_available(&self) -> bool {
+        self.promotions_and_captures().any(|mv| mv.is_capture())
+    }
+
+    /// Returns the square of the king which castles for `c`. Where the king isn't royal, there may
+    /// be several, and it is the one on its starting square, which it keeps while castling rights
+    /// remain.
+    pub (crate) fn castling_king(&self, c: Color) -> Square {
+        if self.variant.has_royal_king() {
+            self.king_location(c)
+        } else {
+            Square::from_coord(File::E, back_rank(c))
+        }
+    }
+
+    /// Returns the square of the rook `c` castles with on `side`.
+    pub (crate) fn castling_rook(&self, c: Color, side: Castle) -> Square {
+        Square::from_coord(self.castling_files[c as usize][side as usize], back_rank(c))
+    }
+
+    /// Returns the destination square of a castling move on `side` for the player to move.
+    ///
+    /// This is the king's destination, except in Fischer Random Chess, where it is the square of
+    /// the castling rook, since the king may not move at all.
+    pub (crate) fn castling_destination(&self, side: Castle) -> Square {
+        if self.variant == Variant::FischerRandom {
+            self.castling_rook(self.turn, side)
+        } else {
+            let (king_file, _) = side.destination_files();
+            Square::from_coord(king_file, back_rank(self.turn))
+        }
+    }
+
+    /// Returns the side the player to move would castle on by moving the king from `orig` to
+    /// `dest`, if that is a castling move and the player has castling rights on that side.
+    pub (crate) fn castling_side(&self, orig: Square, dest: Square) -> Option<Castle> {
+        if orig != self.castling_king(self.turn) {
+            return None;
+        }
+
+        Castle::BOTH.iter().copied().find(|side| {
+            self.castling_rights[self.turn as usize] & side.flag() != 0
+                && dest == self.castling_destination(*side)
+        })
+    }
+
+    /// Returns `true` if the player to move has castling rights on `side` and every square
+    /// that the king and rook move through or to is empty, other than those they already
+    /// occupy. Whether the king is in or passes through check is not verified.
+    pub (crate) fn can_castle(&self, side: Castle) -> bool {
+        if self.castling_rights[self.turn as usize] & side.flag() == 0 {
+            return false;
+        }
+
+        let rank = back_rank(self.turn);
+        let king = self.castling_king(self.turn);
+        let rook = self.castling_rook(self.turn, side);
+        let (king_file, rook_file) = side.destination_files();
+        let path = rank_span(rank, king.file(), king_file)
+            | rank_span(rank, rook.file(), rook_file);
+        let others = self.occ_squares & !Bitboard::from(king) & !Bitboard::from(rook);
+
+        path.is_disjoint(others)
+    }
+
     /// Returns `true` if a draw by the fifty move rule can be claimed (assuming the game isn't
     /// already over).
     pub fn fifty_moves(&self) -> bool {
         self.draw_plies >= 100
     }
 
+    /// Returns `true` if the game is drawn by the seventy-five move rule (assuming the game isn't
+    /// already over by checkmate).
+    pub fn seventy_five_moves(&self) -> bool {
+        self.draw_plies >= 150
+    }
+
     /// Returns the number of plies which count toward the fifty move rule.
     pub fn draw_plies(&self) -> usize {
         self.draw_plies
@@ -414,11 +768,20 @@ impl Position {
         self.zobrist
     }
 
-    /// Returns `true` if there is insufficient material for checkmate.
+    /// Returns `true` if there is insufficient material for checkmate. This is never the case in
+    /// King of the Hill, where either king may still reach the center, in the losing variants,
+    /// where there is no checkmate, in Horde, where Black wins by capturing every white piece, or
+    /// while there are pieces in hand. In Three-check, where a lone minor piece can still give
+    /// three checks, only bare kings are insufficient.
     pub fn insufficient_material(&self) -> bool {
-        if self.occ_squares.len() == 2 {
+        if self.variant == Variant::KingOfTheHill
+            || self.variant == Variant::Horde
+            || !self.variant.has_royal_king()
+            || self.pockets.iter().flatten().any(|&count| count > 0) {
+            return false;
+        } else if self.occ_squares.len() == 2 {
             return true;
-        } else if self.occ_squares.len() == 3 {
+        } else if self.occ_squares.len() == 3 && self.variant != Variant::ThreeCheck {
             let bishops_and_knights =
                   self.occupied_by_piece(White, Knight)
                 | self.occupied_by_piece(Black, Knight)
@@ -473,86 +836,125 @@ impl Position {
             pos.occ_by_piece[!pos.turn() as usize][capt_pc as usize] ^= mask;
             pos.zobrist.toggle_piece_placement(!pos.turn(), capt_pc, sq);
 
-            // update opponent's castling rights if applicable
-            match (!pos.turn(), sq) {
-                (White, Square::A1) | (Black, Square::A8) => {
-                    if pos.has_queen_side_castling_rights(!pos.turn()) {
-                        pos.castling_rights[!pos.turn() as usize] &= !CASTLE_QUEEN_SIDE;
-                        pos.zobrist.toggle_castling_rights(!pos.turn(), CASTLE_QUEEN_SIDE);
-                    }
-                },
-                (White, Square::H1) | (Black, Square::H8) => {
-                    if pos.has_king_side_castling_rights(!pos.turn()) {
-                        pos.castling_rights[!pos.turn() as usize] &= !CASTLE_KING_SIDE;
-                        pos.zobrist.toggle_castling_rights(!pos.turn(), CASTLE_KING_SIDE);
-                    }
-                },
-                _ => {},
+            // the captured piece goes to the mover's pocket, as a pawn if it was promoted
+            if pos.variant.has_drops() {
+                let piece = if pos.promoted.contains(sq) {
+                    pos.promoted.remove(sq);
+                    pos.zobrist.toggle_promoted(!pos.turn(), sq);
+                    Pawn
+                } else {
+                    capt_pc
+                };
+                pos.add_to_pocket(pos.turn(), piece);
             }
-        }
 
-        // move piece to new location (update piece type if promotion)
-        let mask = Bitboard::from(mv.origin()) | mv.destination().into();
-        pos.occ_squares ^= mask;
-        pos.occ_by_color[pos.turn() as usize] ^= mask;
-        pos.zobrist.toggle_piece_placement(pos.turn(), mv.piece(), mv.origin());
-        match mv.move_type() {
-            MoveType::Promotion(prom_pc) => {
-                pos.occ_by_piece[pos.turn() as usize][mv.piece() as usize] ^= mv.origin().into();
-                pos.occ_by_piece[pos.turn() as usize][prom_pc as usize] ^= mv.destination().into();
-                pos.zobrist.toggle_piece_placement(pos.turn(), prom_pc.into(), mv.destination());
-            },
-            _ => {
-                pos.occ_by_piece[pos.turn() as usize][mv.piece() as usize] ^= mask;
-                pos.zobrist.toggle_piece_placement(pos.turn(), mv.piece(), mv.destination());
-            },
+            // update opponent's castling rights if applicable
+            if capt_pc == King {
+                pos.clear_castling_rights(!pos.turn(), CASTLE_BOTH_SIDES);
+            } else {
+                pos.clear_castling_rights_for_rook(!pos.turn(), sq);
+            }
         }
 
-        // move rook for castling moves
         if mv.move_type() == MoveType::Castling {
+            // move king and rook for castling moves
+            let side = if mv.destination().file() > mv.origin().file() {
+                Castle::KingSide
+            } else {
+                Castle::QueenSide
+            };
+            let (king_file, rook_file) = side.destination_files();
             let rank = mv.origin().rank();
-            let (orig, dest);
-            match mv.destination().file() {
-                File::C => {
-                    orig = Square::from_coord(File::A, rank);
-                    dest = Square::from_coord(File::D, rank);
-                },
-                File::G => {
-                    orig = Square::from_coord(File::H, rank);
-                    dest = Square::from_coord(File::F, rank);
-                },
-                _ => unreachable!(),
+            let king_dest = Square::from_coord(king_file, rank);
+            let rook_orig = pos.castling_rook(pos.turn(), side);
+            let rook_dest = Square::from_coord(rook_file, rank);
+
+            // squares the king passes through, not including its origin and destination
+            let mut passed = rank_span(rank, mv.origin().file(), king_file)
+                & !Bitboard::from(mv.origin()) & !Bitboard::from(king_dest);
+            if pos.variant.has_explosions() {
+                // squares next to the opponent's king can't be attacked in Atomic
+                passed &= !king_attacks(pos.king_location(!pos.turn()));
             }
-
-            if pos.square_attacked_by(dest, !pos.turn()) {
-                // castling through check
+            let attacker = !pos.turn();
+            if pos.variant.has_royal_king() && (pos.in_check()
+                || passed.into_iter().any(|sq| pos.square_attacked_by(sq, attacker))) {
+                // castling out of or through check
                 return Err(Error::CastlingThroughCheck);
             }
 
-            let mask = Bitboard::from(orig) | dest.into();
+            pos.toggle_piece(pos.turn(), King, mv.origin());
+            pos.toggle_piece(pos.turn(), Rook, rook_orig);
+            pos.toggle_piece(pos.turn(), King, king_dest);
+            pos.toggle_piece(pos.turn(), Rook, rook_dest);
+        } else if mv.move_type() == MoveType::Drop {
+            // move piece from the pocket to the board
+            pos.remove_from_pocket(pos.turn(), mv.piece());
+            pos.toggle_piece(pos.turn(), mv.piece(), mv.destination());
+        } else {
+            // move piece to new location (update piece type if promotion)
+            let mask = Bitboard::from(mv.origin()) | mv.destination().into();
             pos.occ_squares ^= mask;
             pos.occ_by_color[pos.turn() as usize] ^= mask;
-            pos.occ_by_piece[pos.turn() as usize][Rook as usize] ^= mask;
-            pos.zobrist.toggle_piece_placement(pos.turn(), Rook, orig);
-            pos.zobrist.toggle_piece_placement(pos.turn(), Rook, dest);
+            pos.zobrist.toggle_piece_placement(pos.turn(), mv.piece(), mv.origin());
+            match mv.move_type() {
+                MoveType::Promotion(prom_pc) => {
+                    pos.occ_by_piece[pos.turn() as usize][mv.piece() as usize]
+                        ^= mv.origin().into();
+                    pos.occ_by_piece[pos.turn() as usize][prom_pc as usize]
+                        ^= mv.destination().into();
+                    pos.zobrist.toggle_piece_placement(pos.turn(), prom_pc.into(),
+                        mv.destination());
+                },
+                _ => {
+                    pos.occ_by_piece[pos.turn() as usize][mv.piece() as usize] ^= mask;
+                    pos.zobrist.toggle_piece_placement(pos.turn(), mv.piece(), mv.destination());
+                },
+            }
+
+            // promoted pieces stay promoted when moved
+            if pos.variant.has_drops()
+                && (pos.promoted.contains(mv.origin()) || mv.is_promotion()) {
+                if pos.promoted.contains(mv.origin()) {
+                    pos.promoted.remove(mv.origin());
+                    pos.zobrist.toggle_promoted(pos.turn(), mv.origin());
+                }
+                pos.promoted.insert(mv.destination());
+                pos.zobrist.toggle_promoted(pos.turn(), mv.destination());
+            }
+        }
+
+        // explode the pieces around the capture square
+        if pos.variant.has_explosions() && mv.is_capture() {
+            pos.explode(mv.destination());
         }
 
         // verify mover is not in check
-        let king_attacked = if mv.piece() != King && !pos.in_check() {
-            pos.square_attacked_by_sliding(pos.king_location(pos.turn()), !pos.turn())
-        } else {
-            pos.square_attacked_by(pos.king_location(pos.turn()), !pos.turn())
-        };
-        if king_attacked {
-            // own king is under attack
-            return Err(Error::KingCapturable);
+        if pos.variant.has_explosions() {
+            if pos.occupied_by_piece(pos.turn(), King).is_empty() {
+                return Err(Error::KingExploded);
+            } else if !pos.occupied_by_piece(!pos.turn(), King).is_empty()
+                && pos.king_attacked(pos.turn()) {
+                // own king is under attack, and the opponent's king hasn't exploded
+                return Err(Error::KingCapturable);
+            }
+        } else if pos.variant.has_royal_king() && pos.variant.has_king(pos.turn()) {
+            let king_attacked = if mv.piece() != King && !pos.in_check() {
+                pos.square_attacked_by_sliding(pos.king_location(pos.turn()), !pos.turn())
+            } else {
+                pos.square_attacked_by(pos.king_location(pos.turn()), !pos.turn())
+            };
+            if king_attacked {
+                // own king is under attack
+                return Err(Error::KingCapturable);
+            }
         }
 
         // update en passant square
         if let Some(ep_sq) = pos.en_passant_square() {
             pos.zobrist.toggle_ep_square(ep_sq);
         }
-        if mv.move_type() == MoveType::Advance2 {
+        if mv.move_type() == MoveType::Advance2 && mv.origin().rank() != back_rank(pos.turn()) {
             pos.ep_square = match pos.turn() {
                 White => Some(Square::from_coord(mv.destination().file(), Rank::R3)),
                 Black => Some(Square::from_coord(mv.destination().file(), Rank::R6)),
@@ -563,27 +965,10 @@ impl Position {
         }
 
         // update castling rights if applicable
-        match (pos.turn(), mv.origin()) {
-            (White, Square::A1) | (Black, Square::A8) => {
-                if pos.has_queen_side_castling_rights(pos.turn()) {
-                    pos.castling_rights[pos.turn() as usize] &= !CASTLE_QUEEN_SIDE;
-                    pos.zobrist.toggle_castling_rights(pos.turn(), CASTLE_QUEEN_SIDE);
-                }
-            },
-            (White, Square::H1) | (Black, Square::H8) => {
-                if pos.has_king_side_castling_rights(pos.turn()) {
-                    pos.castling_rights[pos.turn() as usize] &= !CASTLE_KING_SIDE;
-                    pos.zobrist.toggle_castling_rights(pos.turn(), CASTLE_KING_SIDE);
-                }
-            },
-            (White, Square::E1) | (Black, Square::E8) => {
-                if pos.has_castling_rights(pos.turn()) {
-                    let castling_rights = pos.castling_rights[pos.turn() as usize];
-                    pos.castling_rights[pos.turn() as usize] = 0;
-                    pos.zobrist.toggle_castling_rights(pos.turn(), castling_rights);
-                }
-            },
-            _ => {},
+        if mv.piece() == King {
+            pos.clear_castling_rights(pos.turn(), CASTLE_BOTH_SIDES);
+        } else {
+            pos.clear_castling_rights_for_rook(pos.turn(), mv.origin());
         }
 
         // switch turns
@@ -602,6 +987,10 @@ impl Position {
 
         // determine if opponent is now in check
         pos.in_check = match mv.piece() {
+            _ if pos.variant.has_explosions() || !pos.variant.has_royal_king()
+                || !pos.variant.has_king(pos.turn()) => {
+                pos.king_attacked(pos.turn())
+            },
             Pawn | Knight => {
                 pos.square_attacked_by(pos.king_location(pos.turn()), !pos.turn())
             },
@@ -609,10 +998,98 @@ impl Position {
                 pos.square_attacked_by_sliding(pos.king_location(pos.turn()), !pos.turn())
             }
         };
+        if let (true, Some(winning_checks)) = (pos.in_check, pos.variant.winning_checks()) {
+            let c = !pos.turn();
+            let checks = pos.checks[c as usize];
+            if checks < winning_checks {
+                pos.zobrist.toggle_checks(c, checks);
+                pos.zobrist.toggle_checks(c, checks + 1);
+                pos.checks[c as usize] = checks + 1;
+            }
+        }
 
         Ok(pos)
     }
 
+    /// Adds or removes a piece at `sq`, updating the Zobrist key.
+    fn toggle_piece(&mut self, c: Color, p: Piece, sq: Square) {
+        self.occ_squares.toggle(sq);
+        self.occ_by_color[c as usize].toggle(sq);
+        self.occ_by_piece[c as usize][p as usize].toggle(sq);
+        self.zobrist.toggle_piece_placement(c, p, sq);
+    }
+
+    /// Returns `true` if the king of `c` is in check. There is no check without a royal king, and
+    /// in Atomic, kings next to each other aren't in check, since capturing either would also
+    /// explode the other.
+    pub (crate) fn king_attacked(&self, c: Color) -> bool {
+        if !self.variant.has_royal_king() {
+            return false;
+        }
+
+        match self.occupied_by_piece(c, King).peek() {
+            Some(king) if self.variant.has_explosions()
+                && king_attacks(king).intersects(self.occupied_by_piece(!c, King)) => false,
+            Some(king) => self.square_attacked_by(king, !c),
+            None => false,
+        }
+    }
+
+    /// Removes the piece at `sq` and every piece other than a pawn next to it, as after a capture
+    /// on `sq` in Atomic, updating the Zobrist key and castling rights.
+    fn explode(&mut self, sq: Square) {
+        let pawns = self.occupied_by_piece(White, Pawn) | self.occupied_by_piece(Black, Pawn);
+        let blast = (king_attacks(sq) & self.occ_squares & !pawns) | sq.into();
+
+        for sq in blast {
+            let (c, p) = self.piece_at(sq).expect("INFALLIBLE");
+            self.toggle_piece(c, p, sq);
+            if p == King {
+                self.clear_castling_rights(c, CASTLE_BOTH_SIDES);
+            } else {
+                self.clear_castling_rights_for_rook(c, sq);
+            }
+        }
+    }
+
+    /// Adds a piece of type `p` to the pocket of `c`, updating the Zobrist key.
+    fn add_to_pocket(&mut self, c: Color, p: Piece) {
+        let count = self.pockets[c as usize][p as usize];
+        if (count as usize) < MAX_POCKET {
+            self.zobrist.toggle_pocket(c, p, count);
+            self.zobrist.toggle_pocket(c, p, count + 1);
+            self.pockets[c as usize][p as usize] = count + 1;
+        }
+    }
+
+    /// Removes a piece of type `p` from the pocket of `c`, updating the Zobrist key.
+    fn remove_from_pocket(&mut self, c: Color, p: Piece) {
+        let count = self.pockets[c as usize][p as usize];
+        self.zobrist.toggle_pocket(c, p, count);
+        self.zobrist.toggle_pocket(c, p, count - 1);
+        self.pockets[c as usize][p as usize] = count - 1;
+    }
+
+    /// Removes the castling rights for `c` given by `flags`, updating the Zobrist key.
+    fn clear_castling_rights(&mut self, c: Color, flags: u8) {
+        let cleared = self.castling_rights[c as usize] & flags;
+        if cleared != 0 {
+            self.castling_rights[c as usize] &= !cleared;
+            self.zobrist.toggle_castling_rights(c, cleared);
+        }
+    }
+
+    /// Removes the castling rights for `c` on the side of the rook at `sq` (if any), because the
+    /// rook has moved or been captured.
+    fn clear_castling_rights_for_rook(&mut self, c: Color, sq: Square) {
+        for side in &Castle::BOTH {
+            if self.castling_rights[c as usize] & side.flag() != 0
+                && self.castling_rook(c, *side) == sq {
+                self.clear_castling_rights(c, side.flag());
+            }
+        }
+    }
+
     /// Make a null move. This is not a legal move, but can be useful to the chess engine.
     pub fn make_null_move(&self) -> Result<Position> {
         let mut pos = self.clone();
@@ -654,6 +1131,14 @@ impl Position {
 
         self.zobrist.toggle_castling_rights(White, self.castling_rights[White as usize]);
         self.zobrist.toggle_castling_rights(Black, self.castling_rights[Black as usize]);
+        self.zobrist.toggle_checks(White, self.checks[White as usize]);
+        self.zobrist.toggle_checks(Black, self.checks[Black as usize]);
+
+        for c in &[ White, Black ] {
+            for p in &[ Pawn, Knight, Bishop, Rook, Queen ] {
+                self.zobrist.toggle_pocket(*c, *p, self.pocket(*c, *p));
+            }
+        }
 
         for c in &[ White, Black ] {
             for p in &[ Pawn, Knight, Bishop, Rook, Queen, King ] {
@@ -661,6 +1146,10 @@ impl Position {
                     self.zobrist.toggle_piece_placement(*c, *p, sq);
                 }
             }
+
+            for sq in self.promoted & self.occupied_by(*c) {
+                self.zobrist.toggle_promoted(*c, sq);
+            }
         }
     }
 
@@ -1041,5 +1530,248 @@ mod tests {
             );
         }
 
+        // 38. In Three-check, the checks given field sets the counts, and is written back by
+        //      to_fen_str()
+        #[test]
+        fn checks_given_set_correctly() {
+            let fen = "4k3/8/8/8/8/8/8/4K3 w - - 0 1 +2+1";
+            let pos = Position::from_fen_str_variant(fen, Variant::ThreeCheck).expect("valid fen");
+            assert_eq!(pos.checks_given(White), 2);
+            assert_eq!(pos.checks_given(Black), 1);
+            assert_eq!(pos.to_fen_str(), fen);
+
+            let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+                Variant::ThreeCheck).expect("valid fen");
+            assert_eq!(pos.to_fen_str(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1 +0+0");
+        }
+
+        // 39. Invalid checks given field returns Err(ParseError), and too many checks, or checks
+        //      outside of Three-check, return Err(InvalidCheckCount)
+        #[test]
+        fn bad_checks_given_returns_error() {
+            for fen in &["+2", "+2+x", "2+1", "+2+1+0"] {
+                assert_eq!(Position::from_fen_str_variant(
+                    &format!("4k3/8/8/8/8/8/8/4K3 w - - 0 1 {}", fen), Variant::ThreeCheck),
+                    Err(ParseError));
+            }
+            assert_eq!(Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +4+0",
+                Variant::ThreeCheck), Err(InvalidCheckCount));
+            assert_eq!(Position::from_fen_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +1+0"),
+                Err(InvalidCheckCount));
+        }
+
+        // 40. In Crazyhouse, the holdings set the pockets, either in brackets or as a ninth rank,
+        //      and promoted pieces are marked by '~'. Both are written back by to_fen_str()
+        #[test]
+        fn holdings_set_correctly() {
+            let fen = "4k3/1Q~6/8/8/8/8/8/4K3[NPPbq] w - - 0 1";
+            let pos = Position::from_fen_str_variant(fen, Variant::Crazyhouse).expect("valid fen");
+            assert_eq!(pos.pocket(White, Piece::Pawn), 2);
+            assert_eq!(pos.pocket(White, Piece::Knight), 1);
+            assert_eq!(pos.pocket(Black, Piece::Queen), 1);
+            assert_eq!(pos.pocket(Black, Piece::Pawn), 0);
+            assert_eq!(pos.promoted(), Bitboard::from(Square::B7));
+            assert_eq!(pos.to_fen_str(), "4k3/1Q~6/8/8/8/8/8/4K3[NPPqb] w - - 0 1");
+
+            let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3/Nb w - - 0 1",
+                Variant::Crazyhouse).expect("valid fen");
+            assert_eq!(pos.to_fen_str(), "4k3/8/8/8/8/8/8/4K3[Nb] w - - 0 1");
+        }
+
+        // 41. Pieces in hand outside of Crazyhouse, or held kings, return Err(InvalidPocket)
+        #[test]
+        fn bad_holdings_returns_error() {
+            assert_eq!(Position::from_fen_str("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1"),
+                Err(InvalidPocket));
+            assert_eq!(Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3[K] w - - 0 1",
+                Variant::Crazyhouse), Err(InvalidPocket));
+        }
+    }
+
+    #[test]
+    fn checks_are_counted_in_three_check() {
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0",
+            Variant::ThreeCheck).expect("valid fen");
+        assert_eq!(pos.variant_winner(), None);
+
+        let mv = MoveBuilder::new().origin(Square::A1).destination(Square::A8)
+            .validate(&pos).expect("valid move");
+        let pos = mv.make().expect("legal move");
+        assert_eq!(pos.checks_given(White), 3);
+        assert_eq!(pos.variant_winner(), Some(White));
+        assert_eq!(pos.moves().count(), 0);
+        assert_eq!(pos.zobrist_key(),
+            Position::from_fen_str_variant(&pos.to_fen_str(), Variant::ThreeCheck)
+                .expect("valid fen").zobrist_key());
+    }
+
+    #[test]
+    fn lone_minor_piece_can_win_three_check() {
+        for fen in &["4k3/8/8/8/8/8/8/1N2K3 w - - 0 1 +0+0",
+                     "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1 +0+0"] {
+            let pos = Position::from_fen_str_variant(fen, Variant::ThreeCheck).expect(fen);
+            assert!(!pos.insufficient_material(), "{}", fen);
+        }
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +0+0",
+            Variant::ThreeCheck).expect("valid fen");
+        assert!(pos.insufficient_material());
+    }
+
+    #[test]
+    fn king_of_the_hill_is_won_in_the_center() {
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/2K5/8/8 w - - 0 1",
+            Variant::KingOfTheHill).expect("valid fen");
+        assert!(!pos.insufficient_material());
+
+        let mv = MoveBuilder::new().origin(Square::C3).destination(Square::D4)
+            .validate(&pos).expect("valid move");
+        let pos = mv.make().expect("legal move");
+        assert_eq!(pos.variant_winner(), Some(White));
+        assert_eq!(pos.moves().count(), 0);
+        assert_eq!(pos.promotions_and_captures().count(), 0);
+    }
+
+    #[test]
+    fn captures_go_to_the_pocket_in_crazyhouse() {
+        let capture = |fen: &str| {
+            let pos = Position::from_fen_str_variant(fen, Variant::Crazyhouse).expect("valid fen");
+            let mv = MoveBuilder::new().origin(Square::E4).destination(Square::D5)
+                .validate(&pos).expect("valid move");
+            let pos = mv.make().expect("legal move");
+            assert_eq!(pos.zobrist_key(),
+                Position::from_fen_str_variant(&pos.to_fen_str(), Variant::Crazyhouse)
+                    .expect("valid fen").zobrist_key());
+            pos
+        };
+
+        let pos = capture("4k3/8/8/3n4/4P3/8/8/4K3[] w - - 0 1");
+        assert_eq!(pos.pocket(White, Piece::Knight), 1);
+        assert_eq!(pos.to_fen_str(), "4k3/8/8/3P4/8/8/8/4K3[N] b - - 0 1");
+
+        // a promoted piece returns to the pocket as a pawn
+        let pos = capture("4k3/8/8/3q~4/4P3/8/8/4K3[] w - - 0 1");
+        assert_eq!(pos.pocket(White, Piece::Queen), 0);
+        assert_eq!(pos.pocket(White, Piece::Pawn), 1);
+        assert!(pos.promoted().is_empty());
+
+        // promoted pieces are part of the key
+        let key = |fen: &str| Position::from_fen_str_variant(fen, Variant::Crazyhouse)
+            .expect("valid fen").zobrist_key();
+        assert_ne!(key("4k3/8/8/3q4/8/8/8/4K3[] w - - 0 1"),
+            key("4k3/8/8/3q~4/8/8/8/4K3[] w - - 0 1"));
+        let pos = capture("4k3/q~7/8/3q~4/4P3/8/8/4K3[] w - - 0 1");
+        let mv = MoveBuilder::new().origin(Square::A7).destination(Square::A6)
+            .validate(&pos).expect("valid move");
+        let pos = mv.make().expect("legal move");
+        assert_eq!(pos.zobrist_key(),
+            Position::from_fen_str_variant(&pos.to_fen_str(), Variant::Crazyhouse)
+                .expect("valid fen").zobrist_key());
+
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1",
+            Variant::Crazyhouse).expect("valid fen");
+        let mv = MoveBuilder::new().drop_piece(Piece::Knight).destination(Square::F3)
+            .validate(&pos).expect("valid move");
+        assert_eq!(mv.to_string(), "N@f3");
+        assert_eq!(format!("{:#}", mv), "N@f3");
+        let pos = mv.make().expect("legal move");
+        assert_eq!(pos.pocket(White, Piece::Knight), 0);
+        assert_eq!(pos.to_fen_str(), "4k3/8/8/8/8/5N2/8/4K3[] b - - 1 1");
+    }
+
+    #[test]
+    fn captures_explode_in_atomic() {
+        let pos = Position::from_fen_str_variant("4k3/8/8/2nqp3/8/1B6/8/4K3 w - - 0 1",
+            Variant::Atomic).expect("valid fen");
+        let mv = MoveBuilder::new().origin(Square::B3).destination(Square::D5)
+            .validate(&pos).expect("valid move");
+        let pos = mv.make().expect("legal move");
+        assert_eq!(pos.to_fen_str(), "4k3/8/8/4p3/8/8/8/4K3 b - - 0 1");
+
+        // kings can't capture, and kings next to each other aren't in check
+        let pos = Position::from_fen_str_variant("8/8/8/8/8/8/3kq3/3K4 w - - 0 1",
+            Variant::Atomic).expect("valid fen");
+        assert!(!pos.in_check());
+        let mv = MoveBuilder::new().origin(Square::D1).destination(Square::E2)
+            .validate(&pos).expect("valid move");
+        assert_eq!(mv.make(), Err(Error::KingExploded));
+
+        // exploding the opponent's king wins, even if the mover is in check
+        let capture = |fen: &str| {
+            let pos = Position::from_fen_str_variant(fen, Variant::Atomic).expect("valid fen");
+            assert!(pos.in_check());
+            let mv = MoveBuilder::new().origin(Square::A1).destination(Square::A8)
+                .validate(&pos).expect("valid move");
+            mv.make()
+        };
+        assert_eq!(capture("r3k3/8/8/8/8/8/4r3/R3K3 w - - 0 1"), Err(Error::KingCapturable));
+        let pos = capture("rk6/8/8/8/8/8/4r3/R3K3 w - - 0 1").expect("legal move");
+        assert_eq!(pos.variant_winner(), Some(White));
+        assert_eq!(pos.moves().count(), 0);
+    }
+
+    #[test]
+    fn captures_are_forced_in_losing_variants() {
+        let pos = Position::from_fen_str_variant("8/8/8/3p4/4P3/8/8/K7 w - - 0 1",
+            Variant::Suicide).expect("valid fen");
+        assert_eq!(pos.moves().count(), 1);
+        let mv = MoveBuilder::new().origin(Square::A1).destination(Square::A2)
+            .validate(&pos);
+        assert_eq!(mv, Err(Error::CaptureRequired));
+
+        // the king is an ordinary piece, which may be captured or promoted to
+        let pos = Position::from_fen_str_variant("8/P7/8/8/8/8/8/kK6 b - - 0 1",
+            Variant::Giveaway).expect("valid fen");
+        assert!(!pos.insufficient_material());
+        assert_eq!(pos.moves().map(|mv| mv.captured_piece()).collect::<Vec<_>>(), [Some(King)]);
+        let pos = Position::from_fen_str_variant("8/P7/8/8/8/8/8/k7 w - - 0 1",
+            Variant::Giveaway).expect("valid fen");
+        assert_eq!(pos.moves().filter(|mv| mv.promotion() == Some(Promotion::ToKing)).count(), 1);
+        let pos = Position::from_fen_str_variant("8/P7/8/8/8/8/8/k7 b - - 0 1", Variant::Atomic);
+        assert_eq!(pos, Err(Error::InvalidKingCount));
+    }
+
+    #[test]
+    fn winner_without_moves() {
+        let winner = |fen: &str, variant: Variant| {
+            Position::from_fen_str_variant(fen, variant).expect("valid fen").winner_without_moves()
+        };
+        assert_eq!(winner("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", Variant::Normal), None);
+        assert_eq!(winner("R6k/6pp/8/8/8/8/8/K7 b - - 0 1", Variant::Normal), Some(White));
+        assert_eq!(winner("8/8/8/7p/7P/p7/P7/8 w - - 0 1", Variant::Suicide), None);
+        assert_eq!(winner("8/8/2p5/7p/7P/p7/P7/8 w - - 0 1", Variant::Suicide), Some(White));
+        assert_eq!(winner("8/8/2p5/7p/7P/p7/P7/8 w - - 0 1", Variant::Giveaway), Some(White));
+        assert_eq!(winner("8/8/8/7p/7P/p1P5/P1P5/8 b - - 0 1", Variant::Suicide), Some(Black));
+        assert_eq!(winner("8/8/8/7p/7P/p1P5/P1P5/8 b - - 0 1", Variant::Giveaway), Some(Black));
+        assert_eq!(winner("7k/8/8/8/8/8/8/8 w - - 0 1", Variant::Giveaway), Some(White));
+        assert_eq!(winner("7k/8/8/8/8/8/8/8 w - - 0 1", Variant::Horde), Some(Black));
+        assert_eq!(winner("7k/8/8/8/8/p7/P7/8 w - - 0 1", Variant::Horde), None);
+    }
+
+    #[test]
+    fn horde_has_pawns_without_a_king() {
+        let pos = Position::new_variant(Variant::Horde);
+        assert_eq!(pos.to_fen_str(), HORDE_FEN);
+        assert_eq!(pos.moves().count(), 8);
+
+        // only Black has a king, and only White may have pawns on its first rank
+        let horde = |fen: &str| Position::from_fen_str_variant(fen, Variant::Horde);
+        assert_eq!(horde("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"), Err(Error::InvalidKingCount));
+        assert_eq!(horde("p3k3/8/8/8/8/8/8/P7 w - - 0 1"), Err(Error::InvalidPawnRank));
+        assert_eq!(horde("4k3/3P4/8/8/8/8/8/8 w - - 0 1"), Err(Error::KingCapturable));
+        assert!(horde("4k3/3P4/8/8/8/8/8/8 b - - 0 1").expect("valid fen").in_check());
+        assert_eq!(Position::from_fen_str("4k3/8/8/8/8/8/8/P7 w - - 0 1"),
+            Err(Error::InvalidKingCount));
+
+        // pawns on the first rank may advance two squares, without an en passant square
+        let pos = horde("4k3/8/8/8/8/8/8/P7 w - - 0 1").expect("valid fen");
+        let mv = MoveBuilder::new().origin(Square::A1).destination(Square::A3)
+            .validate(&pos).expect("valid move");
+        let pos = mv.make().expect("legal move");
+        assert_eq!(pos.to_fen_str(), "4k3/8/8/8/8/P7/8/8 b - - 0 1");
+
+        // but not over another piece
+        let pos = horde("4k3/8/8/8/8/8/p7/P7 w - - 0 1").expect("valid fen");
+        let mv = MoveBuilder::new().origin(Square::A1).destination(Square::A3).validate(&pos);
+        assert_eq!(mv, Err(Error::IllegalMove));
     }
 }
@@ -25,6 +25,7 @@ pub struct Moves<'a> {
     pos: &'a Position,
     state: MovesState,
     prom_and_capt: PromotionsAndCaptures<'a>,
+    captures_only: bool,
     piece: Piece,
     orig: Square,
     board1: Bitboard,
@@ -37,6 +38,7 @@ enum MovesState {
     Castling,
     PawnAdvancement(i8),
     Remaining,
+    Drops,
     Finished,
 }
 
@@ -44,8 +46,13 @@ impl<'a> Moves<'a> {
     pub (super) fn new(pos: &'a Position) -> Moves {
         Moves {
             pos,
-            state: MovesState::PromAndCapt,
+            state: if pos.variant_winner().is_some() {
+                MovesState::Finished
+            } else {
+                MovesState::PromAndCapt
+            },
             prom_and_capt: PromotionsAndCaptures::new(pos),
+            captures_only: pos.variant().has_forced_captures() && pos.capture_available(),
             piece: Default::default(),
             orig: Default::default(),
             board1: Default::default(),
@@ -63,36 +70,30 @@ impl<'a> Iterator for Moves<'a> {
 
         while let PromAndCapt = self.state {
             if let Some(m) = self.prom_and_capt.next() {
+                if self.captures_only && !m.is_capture() {
+                    continue;
+                }
                 return Some(m);
+            } else if self.captures_only {
+                // only captures are allowed when one is available in the losing variants
+                self.state = Finished;
             } else {
                 self.state = MovesState::Castling;
                 self.piece = King;
-                let rights = if !pos.in_check() {
-                    pos.castling_rights[pos.turn() as usize]
-                } else {
-                    0
-                };
-                let mask = match rights {
-                    CASTLE_KING_SIDE => Bitboard::from(File::H),
-                    CASTLE_QUEEN_SIDE => Bitboard::from(File::A),
-                    CASTLE_BOTH_SIDES => Bitboard::from(File::A) | File::H.into(),
-                    _ => Bitboard::new(),
-                };
-                self.orig = pos.king_location(pos.turn());
-                self.board2 = rank_attacks(self.orig, pos.occupied()) &
-                    pos.occupied_by_piece(pos.turn(), Rook) & mask;
+                self.board2 = Bitboard::new();
+                if !pos.in_check() {
+                    for side in &Castle::BOTH {
+                        if pos.can_castle(*side) {
+                            self.orig = pos.castling_king(pos.turn());
+                            self.board2.insert(pos.castling_destination(*side));
+                        }
+                    }
+                }
             }
         }
 
         while let Castling = self.state {
-            if let Some(rook) = self.board2.pop() {
-                let dest_file = match rook.file() {
-                    File::H => File::G,
-                    File::A => File::C,
-                    _ => unreachable!(),
-                };
-                let dest = Square::from_coord(dest_file, rook.rank());
-
+            if let Some(dest) = self.board2.pop() {
                 return Some(Move {
                     pos,
                     piece: self.piece,
@@ -108,14 +109,23 @@ impl<'a> Iterator for Moves<'a> {
                 let pieces = pos.occupied_by_piece(pos.turn(), self.piece);
                 let mask = Bitboard::from(Rank::R1) | Rank::R8.into();
                 self.board1 = pieces.shift_y(forward) & !pos.occupied() & !mask;
-                let adv2_rank = if pos.turn() == White { Rank::R4 } else { Rank::R5 };
-                self.board2 = self.board1.shift_y(forward) & !pos.occupied() & adv2_rank.into();
+                let (adv2_rank, first_rank_adv2) = if pos.turn() == White {
+                    (Rank::R4, Rank::R3)
+                } else {
+                    (Rank::R5, Rank::R6)
+                };
+                let mut adv2_mask = Bitboard::from(adv2_rank);
+                if pos.variant().has_first_rank_pawns(pos.turn()) {
+                    adv2_mask |= first_rank_adv2.into();
+                }
+                self.board2 = self.board1.shift_y(forward) & !pos.occupied() & adv2_mask;
             }
         }
 
         while let PawnAdvancement(forward) = self.state {
             if let Some(dest) = self.board2.pop() {
-                let orig_rank = if pos.turn() == White { Rank::R2 } else { Rank::R7 };
+                let orig_rank = Rank::try_from((dest.rank() as i8 - 2 * forward) as usize)
+                    .expect("INFALLIBLE");
                 let orig = Square::from_coord(dest.file(), orig_rank);
 
                 return Some(Move {
@@ -175,6 +185,32 @@ impl<'a> Iterator for Moves<'a> {
                     _ => unreachable!(),
                 };
                 self.board1 = pos.occupied_by_piece(pos.turn(), self.piece);
+            } else {
+                self.state = Drops;
+                self.piece = Pawn;
+                self.board2 = pos.drop_squares(self.piece);
+            }
+        }
+
+        while let Drops = self.state {
+            if let Some(dest) = self.board2.pop() {
+                return Some(Move {
+                    pos,
+                    piece: self.piece,
+                    orig: dest,
+                    capt_pc: None,
+                    dest,
+                    move_type: MoveType::Drop,
+                });
+            } else if self.piece < Queen {
+                self.piece = match self.piece {
+                    Pawn => Knight,
+                    Knight => Bishop,
+                    Bishop => Rook,
+                    Rook => Queen,
+                    _ => unreachable!(),
+                };
+                self.board2 = pos.drop_squares(self.piece);
             } else {
                 self.state = Finished;
             }
@@ -224,7 +260,8 @@ impl<'a> PromotionsAndCaptures<'a> {
     pub (super) fn new(pos: &'a Position) -> PromotionsAndCaptures {
         let forward = if pos.turn() == White { 1 } else { -1 };
         let mask = Bitboard::from(Rank::R1) | Rank::R8.into();
-        let targets = mask & pos.occupied_by_piece(!pos.turn(), Queen);
+        let victim = most_valuable_victim(pos);
+        let targets = mask & pos.occupied_by_piece(!pos.turn(), victim);
         let pieces = pos.occupied_by_piece(pos.turn(), Pawn);
         let destinations = pieces.shift_xy(-1, forward) & targets;
 
@@ -238,8 +275,12 @@ impl<'a> PromotionsAndCaptures<'a> {
             },
             under_promotions: VecDeque::new(),
 
-            state: PromAndCaptState::CapturePromotions,
-            victim: Queen,
+            state: if pos.variant_winner().is_some() {
+                PromAndCaptState::UnderPromotions
+            } else {
+                PromAndCaptState::CapturePromotions
+            },
+            victim,
             targets,
             attacker: Pawn,
             pieces,
@@ -276,7 +317,7 @@ impl<'a> Iterator for PromotionsAndCaptures<'a> {
                     move_type: MoveType::Promotion(ToQueen),
                 };
 
-                for prom_pc in &[ ToKnight, ToRook, ToBishop ] {
+                for prom_pc in under_promotions(pos) {
                     let mut m = m.clone();
                     m.move_type = MoveType::Promotion(*prom_pc);
                     self.under_promotions.push_back(m);
@@ -288,6 +329,7 @@ impl<'a> Iterator for PromotionsAndCaptures<'a> {
                 self.destinations = self.pieces.shift_xy(self.side, self.forward) & self.targets;
             } else if self.victim > Knight {
                 self.victim = match self.victim {
+                    King => Queen,
                     Queen => Rook,
                     Rook => Bishop,
                     Bishop => Knight,
@@ -321,7 +363,7 @@ impl<'a> Iterator for PromotionsAndCaptures<'a> {
                     move_type: MoveType::Promotion(ToQueen),
                 };
 
-                for prom_pc in &[ ToKnight, ToRook, ToBishop ] {
+                for prom_pc in under_promotions(pos) {
                     let mut m = m.clone();
                     m.move_type = MoveType::Promotion(*prom_pc);
                     self.under_promotions.push_back(m);
@@ -330,7 +372,7 @@ impl<'a> Iterator for PromotionsAndCaptures<'a> {
                 return Some(m);
             } else {
                 self.state = Captures;
-                self.victim = Queen;
+                self.victim = most_valuable_victim(pos);
                 self.targets = pos.occupied_by_piece(!pos.turn(), self.victim);
                 self.attacker = Pawn;
                 self.pieces = pos.occupied_by_piece(pos.turn(), self.attacker);
@@ -407,6 +449,7 @@ impl<'a> Iterator for PromotionsAndCaptures<'a> {
             } else if self.victim > Pawn {
                 // switch to new victim piece type
                 self.victim = match self.victim {
+                    King => Queen,
                     Queen => Rook,
                     Rook => Bishop,
                     Bishop => Knight,
@@ -428,3 +471,21 @@ impl<'a> Iterator for PromotionsAndCaptures<'a> {
 }
 
 impl<'a> FusedIterator for PromotionsAndCaptures<'a> { }
+
+/// Returns the most valuable piece which can be captured, which is the king where it isn't royal.
+fn most_valuable_victim(pos: &Position) -> Piece {
+    if pos.variant().has_royal_king() {
+        Queen
+    } else {
+        King
+    }
+}
+
+/// Returns the promotions other than to a queen, which include a king where it isn't royal.
+fn under_promotions(pos: &Position) -> &'static [Promotion] {
+    if pos.variant().has_royal_king() {
+        &[ ToKnight, ToRook, ToBishop ]
+    } else {
+        &[ ToKnight, ToRook, ToBishop, ToKing ]
+    }
+}
@@ -36,6 +36,21 @@ impl Zobrist {
         self.0 ^= CASTLE_FLAGS[c as usize][flags as usize];
     }
 
+    /// Toggles the number of checks given by `c`
+    pub fn toggle_checks(&mut self, c: Color, checks: u8) {
+        self.0 ^= CHECKS_GIVEN[c as usize][checks as usize];
+    }
+
+    /// Toggles the number of pieces of type `p` in the pocket of `c`
+    pub fn toggle_pocket(&mut self, c: Color, p: Piece, count: u8) {
+        self.0 ^= POCKET[c as usize][p as usize][count as usize];
+    }
+
+    /// Toggles whether the piece of `c` on `sq` was promoted from a pawn
+    pub fn toggle_promoted(&mut self, c: Color, sq: Square) {
+        self.0 ^= PROMOTED[c as usize][sq as usize];
+    }
+
     /// Toggles whose turn it is
     pub fn toggle_turn(&mut self) {
         self.0 ^= BLACK_MOVE;
@@ -88,6 +103,13 @@ impl From<Zobrist> for u64 {
     }
 }
 
+impl From<u64> for Zobrist {
+    /// Allows restoring a key which was previously converted to a `u64`
+    fn from(key: u64) -> Self {
+        Zobrist(key)
+    }
+}
+
 const PIECE_PLACEMENT: [[[u64; Square::COUNT]; Piece::COUNT]; Color::COUNT] = [
     // white
     [
@@ -539,4 +561,211 @@ const CASTLE_FLAGS: [[u64; 4]; Color::COUNT] = [
     ],
 ];
 
+const CHECKS_GIVEN: [[u64; 4]; Color::COUNT] = [
+    [0, 0x4279_5307_35b8_cfae, 0x06a3_f5be_62a9_701b, 0x3875_5cee_31ef_7910],
+    [0, 0xba94_68ff_6546_15c9, 0x015d_02db_900b_d913, 0xcc86_4476_a553_dacf],
+];
+
+const POCKET: [[[u64; MAX_POCKET + 1]; Piece::COUNT - 1]; Color::COUNT] = [
+    // white
+    [
+        // white pawn
+        [
+            0,
+            0x9459_4d8b_7567_3fca, 0x8623_121d_e0bb_f37a,
+            0x3ecb_55e9_0827_174a, 0xaa20_78e5_484d_1466,
+            0xa318_d8b3_f637_f221, 0x34d2_4c28_aa10_cae2,
+            0xc313_063d_20dd_02f4, 0x46f1_4170_5930_0965,
+            0xbe44_db9b_e137_4045, 0xd1a1_c009_70d5_caf6,
+            0x8709_fec0_0754_6dbb, 0xbc94_4c3f_e56c_297e,
+            0x0f2e_ded7_213f_6d69, 0x33b5_2c97_a42a_0397,
+            0xb505_d6e1_9e9c_c914, 0xd992_f7f5_75ee_364f,
+        ],
+        // white knight
+        [
+            0,
+            0x0d9c_f6da_f644_a8fd, 0xcadd_3e7f_2d32_8ad5,
+            0x502e_b5d1_9ad0_8fa3, 0x401e_4da8_1d15_2300,
+            0x5336_f85e_f731_6abf, 0xc804_5872_2512_0c15,
+            0xae48_1f91_8357_5f0d, 0x85fc_d2f2_d526_523e,
+            0xf9d0_0763_e21c_a726, 0x684a_78f7_18fa_1906,
+            0x0c90_939d_e095_d391, 0xd2b1_983d_d7b7_f93b,
+            0xbb1e_f327_50d5_e290, 0x55ff_a25e_c1a8_8bd3,
+            0xbcd7_f30f_3a21_72d2, 0x4f69_a816_2760_dfe5,
+        ],
+        // white bishop
+        [
+            0,
+            0x8538_107d_29a0_645f, 0xb20b_bfd0_4f09_f3d0,
+            0x4a98_971e_d384_fbd5, 0xc8d5_3380_7639_e89f,
+            0x2871_4ede_fd31_ad7d, 0x960b_079a_e4d2_d889,
+            0x7df3_143a_35e0_c306, 0x842e_25da_6ef4_74fd,
+            0x2bb9_9c2f_107c_687b, 0x3479_ea1a_956e_b6dc,
+            0xcb3a_6798_9bf9_9106, 0x024a_88bd_95d7_a4da,
+            0x7791_3d3d_16ae_b1a3, 0x4da0_002f_9c82_5e0c,
+            0x30ed_0772_1cbd_6274, 0x3dab_60b0_d74d_0b34,
+        ],
+        // white rook
+        [
+            0,
+            0x7425_579e_761a_e20a, 0x78ad_36b8_196a_fb70,
+            0xa375_2a18_fa82_7e1b, 0xdf89_df38_f1ab_b748,
+            0x9f2c_978b_54ef_3f20, 0x7403_c2c6_edf9_fa24,
+            0xd8ab_d704_48f7_4b1a, 0xe9a1_0eaf_8888_2cb2,
+            0xfd04_01ce_92a7_e3c4, 0x3f3d_a685_ca5d_5568,
+            0xf531_0f9c_11e0_46cf, 0xce49_3d9a_0981_a299,
+            0x9b72_ccad_260f_8530, 0x2fd5_5742_6287_26ae,
+            0xf13d_4e41_cbde_7902, 0x7248_99e0_b929_1956,
+        ],
+        // white queen
+        [
+            0,
+            0xd75a_7dd5_1b9e_5de6, 0x6a7f_b62d_bbff_5e48,
+            0x9654_3d41_308d_0d8c, 0x6b09_10c6_04f1_a8e7,
+            0x84f8_a28a_7aae_04a3, 0x3d8f_2212_e8eb_a2a5,
+            0x38fb_5a6f_e7f0_ef27, 0xc82a_00df_70b7_fa18,
+            0xb971_c6e0_cc61_6e92, 0x2bda_20c4_3196_1d9c,
+            0x4e26_57f7_28db_ad5c, 0x40b3_b5db_f557_98cc,
+            0x177f_3763_abcf_7d1f, 0xcf37_19b5_2c3c_086e,
+            0x29ca_c3d5_565d_0d18, 0x3fc9_e24c_c5d6_48f6,
+        ],
+    ],
+    // black
+    [
+        // black pawn
+        [
+            0,
+            0x3dee_0eb8_0eb9_1a50, 0x0e3e_c47e_2fc8_bc4c,
+            0x8194_641c_cdbe_1b37, 0x3686_ee1e_3c79_0949,
+            0xac06_67ca_6b4f_2a11, 0xfef5_c1d6_6e29_aa22,
+            0x4f97_e1a2_79e8_f514, 0x8313_d412_4f4b_cf33,
+            0x0803_9cb5_3f9d_15d3, 0xa3d9_9202_cd0c_141a,
+            0x663c_8ba0_c24d_6628, 0xc176_2f90_f256_ab88,
+            0xd34c_bc3a_0ab6_05a6, 0xa4cc_548d_286b_fc49,
+            0x2998_515d_da45_6c42, 0xc1cc_04b4_4ffc_a198,
+        ],
+        // black knight
+        [
+            0,
+            0x0773_2f9d_caf3_6466, 0x5ce5_0912_4da0_f820,
+            0xa6e3_e823_64c3_e1e6, 0xc5fa_fe2f_ab7e_18b2,
+            0x5202_4791_6d21_603d, 0xb8c8_c556_866b_31d8,
+            0x8853_c445_093b_3b4c, 0xe854_edbf_dad1_3dac,
+            0x850a_a3b4_9e70_8c5a, 0x9651_fd58_4192_b1e4,
+            0x4164_54f8_7990_249a, 0xb482_4d6f_a38e_3f68,
+            0x8a58_d392_eed5_a5bb, 0x8718_028f_059d_6a65,
+            0x36e1_ec44_ed12_b6d0, 0xf89c_b314_969d_7478,
+        ],
+        // black bishop
+        [
+            0,
+            0x9388_9bb3_0a85_9e69, 0x4833_60b5_5d8f_20b5,
+            0xc12b_e3f4_b982_8494, 0x0966_3cac_1ccb_2e7f,
+            0x83e4_3373_96aa_fe09, 0xed68_2791_8f8c_0c8f,
+            0xfea7_36ae_931b_89a4, 0x4e2b_a9a3_06d1_7253,
+            0x42d0_8c6d_04e6_f08f, 0x1f25_3db3_f703_5e70,
+            0xc533_b31f_2a3e_7fbb, 0x9642_e1f0_2fa4_9b1b,
+            0x0633_42b8_91b3_d48d, 0xd756_662a_04a4_e79f,
+            0x5b00_4bf9_bb72_4fd8, 0x3324_96ae_c399_1bf5,
+        ],
+        // black rook
+        [
+            0,
+            0xe8b0_861b_561e_b59e, 0x6994_c946_9a19_8ec4,
+            0x08f5_ee21_7bc8_22a0, 0xbf74_695a_afb0_7926,
+            0x4b53_29a0_2cf6_10e1, 0x7bd7_9ce8_0773_a1cc,
+            0x3f8b_9650_1834_7f75, 0x7226_2844_eee6_ceb8,
+            0x7ac2_f6f5_fc49_de2a, 0xea52_2ffa_43b6_021f,
+            0x7a6c_e51a_bae7_ecd8, 0x73c0_2483_3b29_5bcd,
+            0xc986_6a2e_9940_0d76, 0x5443_db0a_63d4_4629,
+            0xf899_c3a0_8f6e_61f2, 0xeca6_c3d9_e8f1_d07a,
+        ],
+        // black queen
+        [
+            0,
+            0x730e_aff2_dfb6_90c0, 0xb93c_98e7_ee9d_ab1f,
+            0xd2bf_d68d_d4ac_dcb2, 0x060f_3a6d_7a31_2fac,
+            0x6e92_ed50_333c_551a, 0x5c87_f633_d423_1737,
+            0xb2cf_1a63_a554_6029, 0xedde_f96e_24d5_57ad,
+            0x6f2d_cc2d_649d_3c69, 0x8a7c_ef06_0d71_03dd,
+            0x350f_6ae8_c6c1_f0d4, 0x9771_26e7_f022_ea80,
+            0x0105_f2de_0271_85d8, 0x28c9_5dcc_a15e_49ce,
+            0xbdda_5142_e578_feaf, 0x8bb4_eb76_e4a5_6f08,
+        ],
+    ],
+];
+
+const PROMOTED: [[u64; Square::COUNT]; Color::COUNT] = [
+    // white
+    [
+        0xc70b_741c_94c7_7250, 0x3c47_9c62_5d25_337c,
+        0x25ed_212c_cebf_ad35, 0xc04c_0484_8520_78a8,
+        0xf486_0ea9_f342_ed0e, 0xc7fc_7563_64ae_cda9,
+        0xb4fb_0674_9dac_31fb, 0x4e3a_fb86_8d6d_c1e6,
+        0xfa15_55ca_d89e_e1aa, 0xa69a_92bb_0c9e_2e61,
+        0xfdc5_894e_5aff_5c05, 0x0f46_433c_74f4_e736,
+        0x6a5a_3435_fcab_7783, 0x67a2_902c_49d6_0501,
+        0xb1a4_577f_651d_d292, 0x2f21_1733_44d3_ddf3,
+        0xa491_45dc_552f_1c1b, 0x854f_207b_9291_5f78,
+        0xcf0d_02a1_7d4f_acdf, 0xbb01_6bad_2c30_f5f0,
+        0x57fc_d832_2d60_8088, 0x0c59_1c31_22c6_e075,
+        0x8779_53c2_b0a6_c5b4, 0x9eef_5690_ebf2_0282,
+        0xdf2a_3610_d90b_75f5, 0xe78d_d078_803a_2db7,
+        0xa367_4ba5_93ea_12b3, 0x1ee6_8c13_4a5c_e6ae,
+        0xf8f4_6413_f2f3_6243, 0x2a44_4335_858b_ecee,
+        0xd8f3_b5a6_3e8a_cb13, 0x7d14_f1b3_d46c_a0c5,
+        0x5f3d_2a3e_213a_886b, 0x5a3f_bddb_2903_0064,
+        0x8046_ad41_71b7_6832, 0xadb9_e805_6fa2_f0e9,
+        0x6afc_5a42_c65e_1c9a, 0xff8d_9fc7_bc89_88f4,
+        0x3061_065b_2295_fa91, 0xa396_0971_a74a_29c3,
+        0x708e_f84c_c9d9_77ca, 0xf361_bb5c_b836_c402,
+        0x3f34_df19_4fcb_5476, 0x158c_72ba_e7b6_8df3,
+        0x0ddd_04d0_dca0_6832, 0x2824_7091_4f85_dbe4,
+        0xe4f9_3bc3_c943_3ae0, 0xb79d_b45b_bf78_0e54,
+        0x0ccc_6359_d05a_353e, 0xc524_480c_bd3b_b179,
+        0xe4c9_ebb8_6f67_d817, 0x8c63_a795_a7cd_9fae,
+        0xe3e9_7525_edc4_2173, 0xa764_6c09_ec7b_f836,
+        0x3986_d592_b287_4ee3, 0x96b7_e9f5_e183_bffe,
+        0x4762_e914_6dd8_15c2, 0x40ac_7b3a_1102_58b8,
+        0x81c3_7d88_b3d5_49f1, 0xdda6_fd0c_249a_e1b5,
+        0x2712_f872_9f54_9edd, 0xa062_f33c_e849_5927,
+        0x4200_6f56_7eea_ada0, 0xd84c_7b00_7e7f_5026,
+    ],
+    // black
+    [
+        0x71f6_ef43_b94f_3ab6, 0x2394_cab8_05b7_7df1,
+        0x092a_305c_fc1b_9be9, 0x4414_d15e_89cd_7e40,
+        0xb30c_dabe_0ee8_e1fc, 0xefb4_e3b3_3474_8afd,
+        0x3a40_9668_8e75_9174, 0xf998_6ef3_5ff9_a863,
+        0x1fbb_25c6_7cbc_3cba, 0x3c5b_acd0_48c8_4391,
+        0x05ba_f7c0_9cb1_6764, 0xa749_bc88_fcd6_0c59,
+        0xa265_f204_d48c_9b58, 0xef7c_a863_8ebe_0e9e,
+        0x16c8_617e_fbfc_9191, 0xdd5a_db0c_284f_7556,
+        0xe334_4c9a_9662_bfa0, 0x9a98_7574_4292_7ed3,
+        0x4f26_922a_1b48_7657, 0x9c01_9f58_b3da_872a,
+        0x05d6_8677_717f_ebc2, 0x11bf_259b_cd23_cb4f,
+        0x7ec7_3e1d_5311_e907, 0x239e_8e57_a850_4a01,
+        0xdd9d_08cd_e95b_7e4f, 0x24c5_acae_0fae_fc9f,
+        0x8c08_7ef4_2cd3_6a1f, 0x228d_fd2f_d3c5_0a12,
+        0x46da_7d2d_eb19_0962, 0x5cc7_3cab_fa56_b7bc,
+        0x62e4_147e_ce49_c000, 0xbedc_9017_9456_4219,
+        0x2d6a_6ae5_761f_2cd3, 0x24c6_e388_99b1_b079,
+        0xa313_fb09_3e10_445d, 0x9c7f_a3f2_e4fc_a8e3,
+        0x8aeb_4995_8191_4c3a, 0xc36c_1725_bcbd_bbdb,
+        0xf08f_0cd5_7149_a882, 0xb49c_dc33_e38e_a92c,
+        0x99bc_eda5_50f7_29fa, 0x785d_19d7_f797_2de7,
+        0x176d_538a_4b3e_a6cf, 0xc85a_9e78_a05e_1b4e,
+        0xe832_ecfe_aac7_9e98, 0x0706_05b8_ba90_fa03,
+        0xe448_ddf6_af61_4e02, 0xf23f_73df_d3f9_5e40,
+        0x3dc5_f126_bcd7_ee65, 0x82d9_b7cf_aea9_c5ae,
+        0xe50a_3fb4_f327_9c2d, 0x6d14_8dab_7c37_f8c3,
+        0x6477_48ad_d613_95d3, 0xb6f3_b241_c093_3c7f,
+        0xa4c2_c820_a787_edb0, 0x4177_eb59_92fe_d68f,
+        0x2506_eb9f_c71a_6524, 0xc778_d08a_52ba_4234,
+        0x446e_748d_f447_c815, 0xf12b_9d8d_2343_7ff9,
+        0xb04b_63d9_3225_250c, 0x93d3_4ff5_bc1a_3ce2,
+        0x3616_5873_13c3_85c3, 0x019a_56c2_3dda_bb54,
+    ],
+];
+
 const BLACK_MOVE: u64 = 0xa585_29f9_b891_ace8;
@@ -0,0 +1,157 @@
+//! Contains the `Variant` type, which selects the rules used by a `Position`.
+//
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+use std::fmt;
+use std::str::FromStr;
+use crate::{Color, Error, Result};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// The rules used for a game. Each variant is named as in the XBoard protocol.
+///
+/// ```rust
+/// use chess::Variant;
+///
+/// assert_eq!("fischerandom".parse(), Ok(Variant::FischerRandom));
+/// assert_eq!(Variant::ThreeCheck.to_string(), "3check");
+/// assert!("xiangqi".parse::<Variant>().is_err());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Variant {
+    /// Standard chess, under the FIDE Laws of Chess.
+    #[default]
+    Normal,
+    /// Fischer Random Chess (Chess960). The pieces of the back rank are shuffled, and castling
+    /// moves the king and rook to the same squares as in standard chess.
+    FischerRandom,
+    /// Standard chess without castling.
+    NoCastle,
+    /// Standard chess, except that a player also wins by giving check for the third time.
+    ThreeCheck,
+    /// Standard chess, except that a player also wins by moving their king to one of the four
+    /// central squares (d4, d5, e4 or e5).
+    KingOfTheHill,
+    /// Standard chess, except that captured pieces are kept in hand by the capturing player, who
+    /// may later drop them on any empty square instead of making a regular move.
+    Crazyhouse,
+    /// Standard chess, except that a capture explodes both the capturing and captured pieces, along
+    /// with every piece other than a pawn next to the capture square. Kings can't capture, and a
+    /// player wins by exploding the opponent's king.
+    Atomic,
+    /// Losing chess, as played on FICS. Captures are compulsory, the king is an ordinary piece
+    /// which pawns may also promote to, and there is no castling. A player wins by losing all of
+    /// their pieces, or by having fewer pieces than the opponent when they can't move.
+    Suicide,
+    /// Losing chess, as played on ICC. As in Suicide, except that castling is allowed, and a
+    /// player who can't move wins regardless of the pieces left.
+    Giveaway,
+    /// Standard chess, except that White has 36 pawns and no king. Pawns on the first rank may
+    /// also advance two squares, but can't be captured en passant after doing so. Black wins by
+    /// capturing every white piece.
+    Horde,
+}
+
+impl Variant {
+    /// All supported variants.
+    pub const ALL: [Variant; 10] = [
+        Variant::Normal,
+        Variant::FischerRandom,
+        Variant::NoCastle,
+        Variant::ThreeCheck,
+        Variant::KingOfTheHill,
+        Variant::Crazyhouse,
+        Variant::Atomic,
+        Variant::Suicide,
+        Variant::Giveaway,
+        Variant::Horde,
+    ];
+
+    /// Returns `true` if castling is allowed in this variant.
+    pub fn has_castling(self) -> bool {
+        self != Variant::NoCastle && self != Variant::Suicide
+    }
+
+    /// Returns `true` if the king is royal in this variant, so that it may not be left in check.
+    /// Otherwise the king is an ordinary piece, which may be captured and promoted to.
+    pub fn has_royal_king(self) -> bool {
+        !self.has_forced_captures()
+    }
+
+    /// Returns `true` if player `c` has a king in this variant, which is only not the case for
+    /// White in Horde.
+    pub fn has_king(self, c: Color) -> bool {
+        self != Variant::Horde || c == Color::Black
+    }
+
+    /// Returns `true` if player `c` may have pawns on their first rank in this variant.
+    pub fn has_first_rank_pawns(self, c: Color) -> bool {
+        self == Variant::Horde && c == Color::White
+    }
+
+    /// Returns `true` if captures are compulsory in this variant.
+    pub fn has_forced_captures(self) -> bool {
+        self == Variant::Suicide || self == Variant::Giveaway
+    }
+
+    /// Returns `true` if captures explode the pieces around the capture square in this variant.
+    pub fn has_explosions(self) -> bool {
+        self == Variant::Atomic
+    }
+
+    /// Returns `true` if captured pieces can be dropped back on the board in this variant.
+    pub fn has_drops(self) -> bool {
+        self == Variant::Crazyhouse
+    }
+
+    /// Returns the number of checks which win the game, if giving check can win the game in this
+    /// variant.
+    pub fn winning_checks(self) -> Option<u8> {
+        if self == Variant::ThreeCheck {
+            Some(3)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variant::Normal => "normal",
+            Variant::FischerRandom => "fischerandom",
+            Variant::NoCastle => "nocastle",
+            Variant::ThreeCheck => "3check",
+            Variant::KingOfTheHill => "kingofthehill",
+            Variant::Crazyhouse => "crazyhouse",
+            Variant::Atomic => "atomic",
+            Variant::Suicide => "suicide",
+            Variant::Giveaway => "giveaway",
+            Variant::Horde => "horde",
+        }.fmt(f)
+    }
+}
+
+impl FromStr for Variant {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "normal" => Ok(Variant::Normal),
+            "fischerandom" | "chess960" => Ok(Variant::FischerRandom),
+            "nocastle" => Ok(Variant::NoCastle),
+            "3check" => Ok(Variant::ThreeCheck),
+            "kingofthehill" => Ok(Variant::KingOfTheHill),
+            "crazyhouse" => Ok(Variant::Crazyhouse),
+            "atomic" => Ok(Variant::Atomic),
+            "suicide" => Ok(Variant::Suicide),
+            "giveaway" => Ok(Variant::Giveaway),
+            "horde" => Ok(Variant::Horde),
+            _ => Err(Error::ParseError),
+        }
+    }
+}
@@ -40,6 +40,10 @@ pub enum EngineError{
     RejectedLegalMove,
     /// The engine claims the game is over when it's not
     FalseResultClaim,
+    /// The engine does not support the game's variant
+    UnsupportedVariant,
+    /// The game's time control can't be given to the engine
+    UnsupportedTimeControl,
     /// Other error
     Other,
 }
@@ -54,6 +58,8 @@ impl fmt::Display for EngineError {
             ProtocolError => { "engine did not conform with the protocol" },
             RejectedLegalMove => { "engine did not accept a legal move" },
             FalseResultClaim => { "the engine claimed the game is over when it wasn't" },
+            UnsupportedVariant => { "engine does not support the game's variant" },
+            UnsupportedTimeControl => { "the game's time control can't be given to the engine" },
             Other => { "engine encountered an unknown error" },
         }.fmt(f)
     }
@@ -175,7 +181,7 @@ impl GameSetup {
         mut black: Box<dyn EngineInterface>)
     -> (Game, Result<(), EngineError>) {
         let mut game = Game::starting_at(self.opening.initial_position().as_ref().to_owned());
-        game.set_time_control(self.tc);
+        game.set_time_control(self.tc.clone());
         for mv in self.opening.iter() {
             game.make_move(mv.to_owned()).expect("INFALLIBLE");
         }
@@ -217,7 +223,8 @@ impl GameSetup {
             },
         }
 
-        while game.result().is_none() {
+        // as is usual between engines, any draw which can be claimed is claimed for them
+        while game.result().is_none() && game.claim_draw().is_none() {
             let start = Instant::now();
             let response = if game.position().turn() == chess::Color::White {
                 white.send_move_and_go(&game)
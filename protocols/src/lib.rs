@@ -12,9 +12,11 @@
 #![warn(clippy::option_unwrap_used, clippy::result_unwrap_used)]
 
 use std::time::Duration;
+use std::path::PathBuf;
 use chess::game::Game;
 use chess::MoveRc;
 use chess::game::MoveSequence;
+use options::{EngineOption, OptionValue};
 
 pub mod client;
 
@@ -47,7 +49,9 @@ pub struct Thinking {
     depth: u8,
     time: Duration,
     nodes: u64,
+    hashfull: u16,
     pv: Option<MoveSequence>,
+    request: Option<Request>,
 }
 
 impl Thinking {
@@ -59,7 +63,9 @@ impl Thinking {
             depth: 0,
             time: Duration::from_secs(0),
             nodes: 0,
+            hashfull: 0,
             pv: None,
+            request: None,
         }
     }
 
@@ -84,6 +90,16 @@ impl Thinking {
         self.nodes = nodes;
     }
 
+    /// Set how full the hash table is, in entries per thousand.
+    pub fn set_hashfull(&mut self, permille: u16) {
+        self.hashfull = permille;
+    }
+
+    /// Set or clear the request to make to the client along with the move.
+    pub fn set_request(&mut self, request: Option<Request>) {
+        self.request = request;
+    }
+
     /// Returns the estimated score for the principle variation.
     pub fn score(&self) -> Score {
         self.score
@@ -109,6 +125,11 @@ impl Thinking {
         self.nodes/self.time.as_secs()
     }
 
+    /// Returns how full the hash table is, in entries per thousand.
+    pub fn hashfull(&self) -> u16 {
+        self.hashfull
+    }
+
     /// Returns the principle variation.
     pub fn pv(&self) -> Option<&MoveSequence> {
         self.pv.as_ref()
@@ -123,6 +144,99 @@ impl Thinking {
     pub fn ponder_move(&self) -> Option<&chess::MoveRc> {
         self.pv.as_ref()?.get(1)
     }
+
+    /// Returns the request to make to the client along with the move, if any.
+    pub fn request(&self) -> Option<Request> {
+        self.request
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A request the engine makes to the client when it sends its move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// The engine offers a draw, or accepts the draw its opponent offered.
+    OfferDraw,
+    /// The engine claims a draw, either in the current position, or if none can be claimed there,
+    /// after making its move.
+    ClaimDraw,
+    /// The engine resigns instead of making its move.
+    Resign,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Limits placed on a search, in addition to those imposed by the game's time control.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    depth: Option<usize>,
+    nodes: Option<u64>,
+    move_time: Option<Duration>,
+    mate: Option<usize>,
+    nps: Option<u64>,
+}
+
+impl SearchLimits {
+    /// Returns a `SearchLimits` struct with no limits set.
+    pub fn new() -> SearchLimits {
+        SearchLimits::default()
+    }
+
+    /// Sets or clears the maximum search depth.
+    pub fn set_depth(&mut self, depth: Option<usize>) -> &mut Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Sets or clears the maximum number of nodes to search.
+    pub fn set_nodes(&mut self, nodes: Option<u64>) -> &mut Self {
+        self.nodes = nodes;
+        self
+    }
+
+    /// Sets or clears a fixed amount of time to search, overriding the time control.
+    pub fn set_move_time(&mut self, time: Option<Duration>) -> &mut Self {
+        self.move_time = time;
+        self
+    }
+
+    /// Sets or clears the number of moves in which to search for a mate. The search stops as soon
+    /// as a mate in that many moves (or fewer) is found.
+    pub fn set_mate(&mut self, moves: Option<usize>) -> &mut Self {
+        self.mate = moves;
+        self
+    }
+
+    /// Sets or clears the node rate. If set, time allocated by the time control is converted to a
+    /// number of nodes at this rate (in nodes per second) instead of being measured by the clock.
+    pub fn set_nps(&mut self, nps: Option<u64>) -> &mut Self {
+        self.nps = nps;
+        self
+    }
+
+    /// Returns the maximum search depth (if any).
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Returns the maximum number of nodes to search (if any).
+    pub fn nodes(&self) -> Option<u64> {
+        self.nodes
+    }
+
+    /// Returns the fixed amount of time to search (if any).
+    pub fn move_time(&self) -> Option<Duration> {
+        self.move_time
+    }
+
+    /// Returns the number of moves in which to search for a mate (if any).
+    pub fn mate(&self) -> Option<usize> {
+        self.mate
+    }
+
+    /// Returns the node rate used in place of the clock (if any).
+    pub fn nps(&self) -> Option<u64> {
+        self.nps
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -137,6 +251,18 @@ pub enum Action {
     HashSize(usize),
     /// The engine should clear the transposition table.
     ClearHash,
+    /// The engine should save the transposition table to the given file.
+    SaveHash(PathBuf),
+    /// The engine should replace the transposition table with one loaded from the given file.
+    LoadHash(PathBuf),
+    /// The engine should set one of the options it declared with
+    /// [declare_options](trait.Protocol.html#tymethod.declare_options).
+    SetOption {
+        /// The name of the option.
+        name: String,
+        /// The new value, which has been checked to be valid for the option.
+        value: OptionValue,
+    },
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -164,8 +290,9 @@ pub trait Protocol {
     /// Returns the next action that the engine should take.
     fn wait_for_direction(&mut self) -> Action;
 
-    /// Sends the engine's move to the client. If supported by the protocol, the engine's request to
-    /// resign, claim a draw, or offer a draw should be carried out. 
+    /// Sends the engine's move to the client. If supported by the protocol, the engine's
+    /// [request](struct.Thinking.html#method.request) to resign, claim a draw, or offer a draw
+    /// should be carried out.
     ///
     /// # TODO
     /// Determine what to do if thinking doesn't contain a move.
@@ -189,9 +316,28 @@ pub trait Protocol {
     /// Returns the current ponder move, if any.
     fn ponder_move(&self) -> Option<&MoveRc>;
 
-    /// Returns the maximum search depth (if any)
-    fn max_depth(&self) -> Option<usize>;
+    /// Returns the limits which should be placed on the next search.
+    fn search_limits(&self) -> SearchLimits;
+
+    /// Returns the list of moves the search should be restricted to at the root of the current
+    /// position, or `None` if all legal moves should be searched.
+    ///
+    /// The moves are valid from `self.game().position()`, and do not apply while pondering.
+    fn search_moves(&self) -> Option<&[MoveRc]>;
+
+    /// Returns the engine's rating and its opponent's rating, if both are known.
+    fn ratings(&self) -> Option<(u32, u32)>;
+
+    /// Returns `true` if the opponent has offered a draw which is still open. The engine accepts it
+    /// by sending its move with `Request::OfferDraw`.
+    fn draw_offered(&self) -> bool;
+
+    /// Declares the options which the engine allows the client to set. Called once, before the
+    /// first call to `wait_for_direction`. Valid values received for these options are passed to
+    /// the engine as `Action::SetOption`.
+    fn declare_options(&mut self, options: &[EngineOption]);
 }
 
 pub mod io;
+pub mod options;
 pub mod xboard;
@@ -0,0 +1,276 @@
+//! Engine-defined options, which are declared by the engine and exposed to the client by each
+//! protocol.
+//
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+use std::fmt;
+use std::error;
+use std::path::PathBuf;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// An option which the engine allows the client to set.
+///
+/// # Example
+/// ```rust
+/// use protocols::options::{EngineOption, OptionValue};
+///
+/// let option = EngineOption::spin("Hash", 256, 1, 65536);
+/// assert_eq!(option.to_uci(), "name Hash type spin default 256 min 1 max 65536");
+/// assert_eq!(option.parse_value(Some("512")), Ok(OptionValue::Spin(512)));
+/// assert!(option.parse_value(Some("0")).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineOption {
+    name: String,
+    kind: OptionKind,
+}
+
+/// The type of an `EngineOption`, along with its default value and any restrictions on its value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionKind {
+    /// An integer within a range.
+    Spin {
+        /// The default value.
+        default: i64,
+        /// The minimum value.
+        min: i64,
+        /// The maximum value.
+        max: i64,
+    },
+    /// A boolean value.
+    Check {
+        /// The default value.
+        default: bool,
+    },
+    /// One of a list of strings.
+    Combo {
+        /// The default choice.
+        default: String,
+        /// All the choices, including the default.
+        choices: Vec<String>,
+    },
+    /// An arbitrary string.
+    String {
+        /// The default value.
+        default: String,
+    },
+    /// An option without a value, which causes the engine to perform an action when set.
+    Button,
+    /// The name of a file. An empty name means that no file is used.
+    File {
+        /// The default file name.
+        default: PathBuf,
+    },
+    /// The name of a directory. An empty name means that no directory is used.
+    Path {
+        /// The default directory name.
+        default: PathBuf,
+    },
+}
+
+/// A new value for an `EngineOption`, whose variant matches the option's `OptionKind`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    /// The value of a spin option.
+    Spin(i64),
+    /// The value of a check option.
+    Check(bool),
+    /// The choice for a combo option.
+    Combo(String),
+    /// The value of a string option.
+    String(String),
+    /// Indicates that a button was pressed.
+    Button,
+    /// The file name for a file option.
+    File(PathBuf),
+    /// The directory name for a path option.
+    Path(PathBuf),
+}
+
+impl EngineOption {
+    /// Creates an integer option with a value from `min` to `max`.
+    pub fn spin(name: &str, default: i64, min: i64, max: i64) -> Self {
+        Self::new(name, OptionKind::Spin{ default, min, max })
+    }
+
+    /// Creates a boolean option.
+    pub fn check(name: &str, default: bool) -> Self {
+        Self::new(name, OptionKind::Check{ default })
+    }
+
+    /// Creates an option whose value is one of `choices`.
+    pub fn combo(name: &str, default: &str, choices: &[&str]) -> Self {
+        Self::new(name, OptionKind::Combo{
+            default: default.to_owned(),
+            choices: choices.iter().map(|&choice| choice.to_owned()).collect(),
+        })
+    }
+
+    /// Creates a string option.
+    pub fn string(name: &str, default: &str) -> Self {
+        Self::new(name, OptionKind::String{ default: default.to_owned() })
+    }
+
+    /// Creates a button option.
+    pub fn button(name: &str) -> Self {
+        Self::new(name, OptionKind::Button)
+    }
+
+    /// Creates an option whose value is the name of a file.
+    pub fn file(name: &str, default: PathBuf) -> Self {
+        Self::new(name, OptionKind::File{ default })
+    }
+
+    /// Creates an option whose value is the name of a directory.
+    pub fn path(name: &str, default: PathBuf) -> Self {
+        Self::new(name, OptionKind::Path{ default })
+    }
+
+    fn new(name: &str, kind: OptionKind) -> Self {
+        EngineOption {
+            name: name.to_owned(),
+            kind,
+        }
+    }
+
+    /// Returns the name of the option.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type of the option.
+    pub fn kind(&self) -> &OptionKind {
+        &self.kind
+    }
+
+    /// Parses a value sent by the client for this option, returning an error if it is not a valid
+    /// value. `value` should be `None` only for button options.
+    pub fn parse_value(&self, value: Option<&str>) -> Result<OptionValue, OptionError> {
+        let value = match (&self.kind, value) {
+            (OptionKind::Button, _) => return Ok(OptionValue::Button),
+            (_, Some(value)) => value.trim(),
+            (_, None) => return Err(self.error("requires a value")),
+        };
+
+        match &self.kind {
+            OptionKind::Spin{ min, max, .. } => match value.parse() {
+                Ok(value) if value >= *min && value <= *max => Ok(OptionValue::Spin(value)),
+                _ => Err(self.error(&format!("must be an integer from {} to {}", min, max))),
+            },
+            OptionKind::Check{ .. } => match value {
+                "1" | "true" => Ok(OptionValue::Check(true)),
+                "0" | "false" => Ok(OptionValue::Check(false)),
+                _ => Err(self.error("must be true or false")),
+            },
+            OptionKind::Combo{ choices, .. } => {
+                if choices.iter().any(|choice| choice == value) {
+                    Ok(OptionValue::Combo(value.to_owned()))
+                } else {
+                    Err(self.error(&format!("must be one of: {}", choices.join(", "))))
+                }
+            },
+            OptionKind::String{ .. } => Ok(OptionValue::String(value.to_owned())),
+            OptionKind::Button => Ok(OptionValue::Button),
+            OptionKind::File{ .. } => Ok(OptionValue::File(value.into())),
+            OptionKind::Path{ .. } => Ok(OptionValue::Path(value.into())),
+        }
+    }
+
+    fn error(&self, msg: &str) -> OptionError {
+        OptionError(format!("option \"{}\" {}", self.name, msg))
+    }
+
+    /// Formats the option as the arguments of a UCI `option` command, which begin with
+    /// `name <name> type <type>`.
+    ///
+    /// UCI has no file or directory types, so these are declared as strings. An empty string is
+    /// given as `<empty>`.
+    pub fn to_uci(&self) -> String {
+        let string = |s: &str| if s.is_empty() { "<empty>".to_owned() } else { s.to_owned() };
+
+        match &self.kind {
+            OptionKind::Spin{ default, min, max } => {
+                format!("name {} type spin default {} min {} max {}", self.name, default, min, max)
+            },
+            OptionKind::Check{ default } => {
+                format!("name {} type check default {}", self.name, default)
+            },
+            OptionKind::Combo{ default, choices } => {
+                let vars: Vec<_> = choices.iter()
+                    .map(|choice| format!(" var {}", choice))
+                    .collect();
+                format!("name {} type combo default {}{}", self.name, default, vars.concat())
+            },
+            OptionKind::String{ default } => {
+                format!("name {} type string default {}", self.name, string(default))
+            },
+            OptionKind::Button => format!("name {} type button", self.name),
+            OptionKind::File{ default } | OptionKind::Path{ default } => {
+                format!("name {} type string default {}",
+                    self.name, string(&default.to_string_lossy()))
+            },
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Error returned when a client sends an invalid value for an option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionError(String);
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for OptionError { }
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_value() {
+        let check = EngineOption::check("Ponder", true);
+        assert_eq!(check.parse_value(Some("1")), Ok(OptionValue::Check(true)));
+        assert_eq!(check.parse_value(Some("false")), Ok(OptionValue::Check(false)));
+        assert!(check.parse_value(Some("yes")).is_err());
+        assert!(check.parse_value(None).is_err());
+
+        let combo = EngineOption::combo("Style", "Normal", &["Solid", "Normal", "Risky"]);
+        assert_eq!(combo.parse_value(Some("Risky")), Ok(OptionValue::Combo("Risky".to_owned())));
+        assert!(combo.parse_value(Some("risky")).is_err());
+
+        let button = EngineOption::button("Clear Hash");
+        assert_eq!(button.parse_value(None), Ok(OptionValue::Button));
+
+        let file = EngineOption::file("Book File", PathBuf::new());
+        assert_eq!(file.parse_value(Some("")), Ok(OptionValue::File(PathBuf::new())));
+        assert_eq!(file.parse_value(Some("a b.bin")), Ok(OptionValue::File("a b.bin".into())));
+
+        let spin = EngineOption::spin("Contempt", 0, -100, 100);
+        assert_eq!(spin.parse_value(Some("-100")), Ok(OptionValue::Spin(-100)));
+        assert_eq!(spin.parse_value(Some("101")).map_err(|err| err.to_string()),
+            Err("option \"Contempt\" must be an integer from -100 to 100".to_owned()));
+    }
+
+    #[test]
+    fn uci_format() {
+        assert_eq!(EngineOption::check("Ponder", false).to_uci(),
+            "name Ponder type check default false");
+        assert_eq!(EngineOption::combo("Style", "Normal", &["Solid", "Normal"]).to_uci(),
+            "name Style type combo default Normal var Solid var Normal");
+        assert_eq!(EngineOption::string("Player", "").to_uci(),
+            "name Player type string default <empty>");
+        assert_eq!(EngineOption::button("Clear Hash").to_uci(), "name Clear Hash type button");
+        assert_eq!(EngineOption::path("Tablebase Path", "tb".into()).to_uci(),
+            "name Tablebase Path type string default tb");
+    }
+}
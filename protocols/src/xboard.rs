@@ -17,8 +17,10 @@ use std::ffi::OsStr;
 use log::{debug, info, error};
 use lazy_static::lazy_static;
 use regex::{RegexSet, Regex};
-use super::{Protocol, Action, SearchAction, Thinking, io};
-use chess::game::{Game, TimeControl, GameResult};
+use super::{Protocol, Action, SearchAction, SearchLimits, Thinking, Request, io};
+use super::options::{EngineOption, OptionKind};
+use chess::{ValidMove, MoveType, Variant};
+use chess::game::{Game, TimeControl, TimeStage, GameResult};
 use crate::client::{EngineInterface, EngineResponse, EngineError};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -38,12 +40,18 @@ pub struct Xboard {
     client: io::Client,
 
     game: Game,
+    variant: Variant,
 
     state: State,
     color: Option<chess::Color>,
     post_thinking: bool,
     can_ponder: bool,
     max_depth: Option<usize>,
+    nps: Option<u64>,
+    ratings: Option<(u32, u32)>,
+    options: Vec<EngineOption>,
+    search_moves: Option<Vec<chess::MoveRc>>,
+    draw_offered: bool,
 
     ponder_hits: usize,
     ponder_total: usize,
@@ -55,15 +63,147 @@ impl Xboard {
         Xboard {
             client: io::Client::connect(),
             game: Game::new(),
+            variant: Variant::Normal,
             state: State::Idle,
             color: Some(chess::Color::Black),
             post_thinking: true,
             can_ponder: true,
             max_depth: None,
+            nps: None,
+            ratings: None,
+            options: Vec::new(),
+            search_moves: None,
+            draw_offered: false,
             ponder_hits: 0,
             ponder_total: 0,
         }
     }
+
+    /// Switches to the rules of the variant named by the `variant` command, and sets up its
+    /// starting position. Returns `false` if the variant isn't supported.
+    fn set_variant(&mut self, line: &str, name: &str) -> bool {
+        match name.parse() {
+            Ok(variant) => {
+                let tc = self.game.clock().time_control();
+                self.variant = variant;
+                self.game = Game::starting_at(chess::Position::new_variant(variant));
+                self.game.set_time_control(tc);
+                self.search_moves = None;
+                true
+            },
+            Err(_) => {
+                Response::ErrorMessage(line.to_owned(), "unsupported variant".to_owned()).send();
+                false
+            },
+        }
+    }
+
+    /// Sets up the position sent by the `setboard` command, using the rules of the current variant.
+    fn set_board(&mut self, line: &str, fen: &str) {
+        match chess::Position::from_fen_str_variant(fen, self.variant) {
+            Ok(pos) => {
+                let tc = self.game.clock().time_control();
+                self.game = Game::starting_at(pos);
+                self.game.set_time_control(tc);
+                self.search_moves = None;
+                self.draw_offered = false;
+            },
+            Err(err) => Response::ErrorMessage(line.to_owned(), err.to_string()).send(),
+        }
+    }
+
+    /// Replaces the pieces in hand with those sent by the `holding` command. Returns `true` if the
+    /// position changed, in which case the game restarts from it.
+    fn set_holdings(&mut self, line: &str, white: &str, black: &str) -> bool {
+        let fen = self.game.position().to_fen_str();
+        let (board, rest) = fen.split_at(fen.find(' ').expect("INFALLIBLE"));
+        let board = board.split('[').next().expect("INFALLIBLE");
+        let fen = format!("{}[{}{}]{}", board, white.to_uppercase(), black.to_lowercase(), rest);
+        match chess::Position::from_fen_str_variant(&fen, self.variant) {
+            Ok(pos) if pos.zobrist_key() == self.game.position().zobrist_key() => false,
+            Ok(_) => {
+                self.set_board(line, &fen);
+                true
+            },
+            Err(err) => {
+                Response::ErrorMessage(line.to_owned(), err.to_string()).send();
+                false
+            },
+        }
+    }
+
+    /// Records the ratings sent by the `rating` command. A rating of zero means it is unknown.
+    fn set_ratings(&mut self, engine: u32, opponent: u32) {
+        self.ratings = if engine > 0 && opponent > 0 { Some((engine, opponent)) } else { None };
+    }
+
+    /// Handles an engine-defined option. Returns an action if the value is valid for the option.
+    fn set_option(&mut self, line: &str, name: &str, value: Option<&str>) -> Option<Action> {
+        let option = match self.options.iter().find(|option| option.name() == name) {
+            Some(option) => option,
+            None => {
+                Response::ErrorMessage(line.to_owned(), "unknown option".to_owned()).send();
+                return None;
+            },
+        };
+
+        match option.parse_value(value) {
+            Ok(value) => Some(Action::SetOption{ name: name.to_owned(), value }),
+            Err(error) => {
+                Response::ErrorMessage(line.to_owned(), error.to_string()).send();
+                None
+            },
+        }
+    }
+
+    /// Removes the move given in coordinate notation from the list of moves to be searched. If
+    /// `move_str` is `None`, all moves are excluded.
+    fn exclude(&mut self, line: &str, move_str: Option<&str>) {
+        if let Some(move_str) = move_str {
+            match self.parse_move(move_str) {
+                Ok(mv) => {
+                    let pos = self.game.position();
+                    let search_moves = self.search_moves.get_or_insert_with(|| {
+                        pos.moves()
+                            .filter(|mv| mv.make().is_ok())
+                            .map(chess::MoveRc::from)
+                            .collect()
+                    });
+                    search_moves.retain(|m| *m != mv);
+                },
+                Err(error) => Response::ErrorMessage(line.to_owned(), error.to_string()).send(),
+            }
+        } else {
+            self.search_moves = Some(Vec::new());
+        }
+    }
+
+    /// Adds the move given in coordinate notation back into the list of moves to be searched. If
+    /// `move_str` is `None`, all moves are included.
+    fn include(&mut self, line: &str, move_str: Option<&str>) {
+        if let Some(move_str) = move_str {
+            match self.parse_move(move_str) {
+                Ok(mv) => {
+                    if let Some(search_moves) = &mut self.search_moves {
+                        if !search_moves.contains(&mv) {
+                            search_moves.push(mv);
+                        }
+                    }
+                },
+                Err(error) => Response::ErrorMessage(line.to_owned(), error.to_string()).send(),
+            }
+        } else {
+            self.search_moves = None;
+        }
+    }
+
+    /// Parses a move from the current position, and verifies that it is legal.
+    fn parse_move(&self, move_str: &str) -> chess::Result<chess::MoveRc> {
+        let mv = move_str.parse::<chess::MoveBuilder>()?.validate(self.game.position())?;
+        mv.make()?;
+
+        Ok(mv.into())
+    }
 }
 
 impl Protocol for Xboard {
@@ -89,10 +229,16 @@ impl Protocol for Xboard {
                                 Feature{ name: "myname".to_owned(),
                                     value: String("tinman".to_owned()) },
                                 Feature{ name: "debug".to_owned(), value: Int(1) },
-                                Feature{ name: "nps".to_owned(), value: Int(0) },
+                                Feature{ name: "nps".to_owned(), value: Int(1) },
                                 Feature{ name: "analyze".to_owned(), value: Int(0) },
                                 Feature{ name: "memory".to_owned(), value: Int(1) },
-                            ]).send();
+                                Feature{ name: "exclude".to_owned(), value: Int(1) },
+                                Feature{ name: "variants".to_owned(),
+                                    value: String(variant_names()) },
+                            ].into_iter().chain(self.options.iter().map(|option| Feature{
+                                name: "option".to_owned(),
+                                value: String(xboard_option(option)),
+                            })).collect()).send();
                             Response::Feature(vec![
                                 Feature{name: "done".to_owned(), value: Int(1)}
                             ]).send();
@@ -110,8 +256,11 @@ impl Protocol for Xboard {
                         },
                         New => {
                             self.game = Game::new();
+                            self.variant = chess::Variant::Normal;
                             self.color = Some(chess::Color::Black);
                             self.max_depth = None;
+                            self.search_moves = None;
+                            self.draw_offered = false;
                         },
                         Force => {
                             self.color = None;
@@ -123,9 +272,9 @@ impl Protocol for Xboard {
                         UserMove(move_str) => {
                             match self.game.make_move_from_str(&move_str) {
                                 Ok(_) => {
+                                    self.search_moves = None;
                                     if let Some(result) = self.game.result() {
-                                        // TODO: use Response
-                                        io::Client::send(&result.to_string());
+                                        Response::from(result).send();
                                     } else if self.color == Some(self.game.position().turn()) {
                                         self.state = State::Thinking;
                                     }
@@ -137,27 +286,29 @@ impl Protocol for Xboard {
                             }
                         },
                         SetBoard(fen) => {
-                            match fen.parse() {
-                                Ok(pos) => {
-                                    let tc = self.game.clock().time_control();
-                                    self.game = Game::starting_at(pos);
-                                    self.game.set_time_control(tc);
-                                },
-                                Err(err) => Response::ErrorMessage(line, err.to_string()).send(),
-                            }
+                            self.set_board(&line, &fen);
+                        },
+                        Variant(name) => {
+                            self.set_variant(&line, &name);
+                        },
+                        Holding{ white, black } => {
+                            self.set_holdings(&line, &white, &black);
                         },
                         Draw => {
-                            // TODO: consider if draw should be accepted
+                            // answered along with the engine's next move
+                            self.draw_offered = true;
                         },
                         GameResult{ .. } => {
                             self.color = None;
                         },
                         Undo => {
                             self.game.undo();
+                            self.search_moves = None;
                         },
                         Remove => {
                             self.game.undo();
                             self.game.undo();
+                            self.search_moves = None;
                         },
                         MoveNow => {
                             // TODO: not valid here
@@ -183,11 +334,9 @@ impl Protocol for Xboard {
                                 (0, 0) => TimeControl::SuddenDeath(base),
                                 (0, _) => TimeControl::Incremental{ base, inc },
                                 (_, 0) => TimeControl::Session{ base, mps },
-                                _ => {
-                                    Response::ErrorMessage(line.clone(),
-                                        "invalid time control".to_string()).send();
-                                    continue
-                                },
+                                _ => TimeControl::Stages(vec![
+                                    TimeStage::new(Some(mps), base, inc)
+                                ]),
                             });
                         },
                         SetTime(time) => {
@@ -196,9 +345,20 @@ impl Protocol for Xboard {
                         SetDepth(depth) => {
                             self.max_depth = Some(depth);
                         },
+                        Nps(nps) => {
+                            self.nps = if nps > 0 { Some(nps) } else { None };
+                        },
+                        Rating{ engine, opponent } => {
+                            self.set_ratings(engine, opponent);
+                        },
                         Memory(size) => {
                             return Action::HashSize(size * 0x10_0000);
                         },
+                        SetOption{ name, value } => {
+                            if let Some(action) = self.set_option(&line, &name, value.as_deref()) {
+                                return action;
+                            }
+                        },
                         Post => {
                             self.post_thinking = true;
                         },
@@ -215,6 +375,12 @@ impl Protocol for Xboard {
                             Response::ErrorMessage(line.clone(),
                                 "You're on your own on this one".to_string()).send();
                         },
+                        Exclude(move_str) => {
+                            self.exclude(&line, move_str.as_deref());
+                        },
+                        Include(move_str) => {
+                            self.include(&line, move_str.as_deref());
+                        },
                     }
                 } else {
                     Response::ErrorMessage(line.clone(),
@@ -235,30 +401,46 @@ impl Protocol for Xboard {
 
     fn send_move(&mut self, thinking: &Thinking) {
         self.send_thinking(thinking);
+        self.draw_offered = false;
+        self.state = State::Idle; // default to idle
+
+        match thinking.request() {
+            Some(Request::Resign) => {
+                Response::Resign.send();
+                return;
+            },
+            Some(Request::ClaimDraw) => {
+                if let Some(result) = self.game.claim_draw() {
+                    Response::from(result).send();
+                    return;
+                }
+            },
+            Some(Request::OfferDraw) => {
+                // offered before moving, so that the opponent can accept on their turn
+                Response::OfferDraw.send();
+            },
+            None => { },
+        }
 
         // TODO: make_move_timed
         if let Some(mv) = thinking.best_move() {
             self.game.make_move(mv.clone()).expect("INFALLIBLE");
-            Response::Move(format!("{:#}", mv)).send();
-
-            self.state = State::Idle; // default to idle
+            self.search_moves = None;
+            Response::Move(coordinate_move(mv)).send();
 
             if let Some(result) = self.game.result() {
-                // TODO: use Response
-                io::Client::send(&result.to_string());
+                Response::from(result).send();
+            } else if thinking.request() == Some(Request::ClaimDraw) {
+                if let Some(result) = self.game.claim_draw() {
+                    Response::from(result).send();
+                }
             } else if self.can_ponder {
                 if let Some(mv) = thinking.ponder_move() {
                     self.state = State::Pondering(mv.clone());
                 }
             }
-        } else {
-            if let Some(result) = self.game.result() {
-                // TODO: use Response
-                io::Client::send(&result.to_string());
-            } else if self.color == Some(self.game.position().turn()) {
-                self.state = State::Thinking;
-            }
-            self.state = State::Idle;
+        } else if let Some(result) = self.game.result() {
+            Response::from(result).send();
         }
     }
 
@@ -306,8 +488,11 @@ impl Protocol for Xboard {
                         },
                         New => {
                             self.game = Game::new();
+                            self.variant = chess::Variant::Normal;
                             self.state = State::Idle;
                             self.max_depth = None;
+                            self.search_moves = None;
+                            self.draw_offered = false;
                             return Some(SearchAction::Abort);
                         },
                         Force => {
@@ -325,9 +510,9 @@ impl Protocol for Xboard {
                         UserMove(move_str) => {
                             match self.game.make_move_from_str(&move_str) {
                                 Ok(_) => {
+                                    self.search_moves = None;
                                     if let Some(result) = self.game.result() {
-                                        // TODO: use Response
-                                        io::Client::send(&result.to_string());
+                                        Response::from(result).send();
                                         self.state = State::Idle;
                                         return Some(SearchAction::Abort);
                                     } else if let State::Pondering(mv) = &self.state {
@@ -360,19 +545,21 @@ impl Protocol for Xboard {
                             }
                         },
                         SetBoard(fen) => {
-                            match fen.parse() {
-                                Ok(pos) => {
-                                    let tc = self.game.clock().time_control();
-                                    self.game = Game::starting_at(pos);
-                                    self.game.set_time_control(tc);
-                                },
-                                Err(err) => Response::ErrorMessage(line, err.to_string()).send(),
-                            }
+                            self.set_board(&line, &fen);
+                            self.state = State::Idle;
+                            return Some(SearchAction::Abort);
+                        },
+                        Variant(name) if self.set_variant(&line, &name) => {
+                            self.state = State::Idle;
+                            return Some(SearchAction::Abort);
+                        },
+                        Holding{ white, black } if self.set_holdings(&line, &white, &black) => {
                             self.state = State::Idle;
                             return Some(SearchAction::Abort);
                         },
                         Draw => {
-                            // TODO: consider if draw should be accepted
+                            // answered along with the engine's next move
+                            self.draw_offered = true;
                         },
                         GameResult{ .. } => {
                             self.color = None;
@@ -381,12 +568,14 @@ impl Protocol for Xboard {
                         },
                         Undo => {
                             self.game.undo();
+                            self.search_moves = None;
                             self.state = State::Idle;
                             return Some(SearchAction::Abort);
                         },
                         Remove => {
                             self.game.undo();
                             self.game.undo();
+                            self.search_moves = None;
                             self.state = State::Idle;
                             return Some(SearchAction::Abort);
                         },
@@ -418,11 +607,24 @@ impl Protocol for Xboard {
                                 "cannot change search depth while thinking".to_string())
                                 .send();
                         },
+                        Nps(_) => {
+                            Response::ErrorMessage(line.clone(),
+                                "cannot change node rate while thinking".to_string())
+                                .send();
+                        },
+                        Rating{ engine, opponent } => {
+                            self.set_ratings(engine, opponent);
+                        },
                         Memory(_) => {
                             Response::ErrorMessage(line.clone(),
                                 "cannot change hash size in while thinking".to_string())
                                 .send();
                         },
+                        SetOption{ .. } => {
+                            Response::ErrorMessage(line.clone(),
+                                "cannot change options while thinking".to_string())
+                                .send();
+                        },
                         Post => {
                             self.post_thinking = true;
                         },
@@ -441,12 +643,26 @@ impl Protocol for Xboard {
                         },
                         Hint => {
                             if let State::Pondering(mv) = &self.state {
-                                Response::Hint(format!("{:#}", mv)).send();
+                                Response::Hint(coordinate_move(mv)).send();
                             } else {
                                 Response::ErrorMessage(line.clone(),
                                     "it's not your turn".to_string()).send();
                             }
                         },
+                        Exclude(move_str) => {
+                            self.exclude(&line, move_str.as_deref());
+                            if self.state == State::Thinking {
+                                // restart the search with the new list of moves
+                                return Some(SearchAction::Abort);
+                            }
+                        },
+                        Include(move_str) => {
+                            self.include(&line, move_str.as_deref());
+                            if self.state == State::Thinking {
+                                // restart the search with the new list of moves
+                                return Some(SearchAction::Abort);
+                            }
+                        },
                         _ => { },
                     }
                 } else {
@@ -477,8 +693,27 @@ impl Protocol for Xboard {
         }
     }
 
-    fn max_depth(&self) -> Option<usize> {
-        self.max_depth
+    fn search_limits(&self) -> SearchLimits {
+        let mut limits = SearchLimits::new();
+        limits.set_depth(self.max_depth).set_nps(self.nps);
+
+        limits
+    }
+
+    fn search_moves(&self) -> Option<&[chess::MoveRc]> {
+        self.search_moves.as_deref()
+    }
+
+    fn ratings(&self) -> Option<(u32, u32)> {
+        self.ratings
+    }
+
+    fn draw_offered(&self) -> bool {
+        self.draw_offered
+    }
+
+    fn declare_options(&mut self, options: &[EngineOption]) {
+        self.options = options.to_vec();
     }
 }
 
@@ -499,6 +734,7 @@ pub struct XboardClient {
     san: bool,
     usermove: bool,
     ping: usize,
+    variants: Vec<String>,
 }
 
 impl XboardClient {
@@ -513,6 +749,7 @@ impl XboardClient {
             san: false,
             usermove: false,
             ping: 0,
+            variants: vec!["normal".to_owned()],
         }.init()
     }
 
@@ -646,8 +883,19 @@ impl XboardClient {
                                     }
                                 },
 
+                                "variants" => {
+                                    if let FeatureValue::String(list) = &feature.value {
+                                        self.variants = list.split(',')
+                                            .map(|name| name.trim().to_owned())
+                                            .collect();
+                                        self.send(&Command::Accepted(feature.name))?;
+                                    } else {
+                                        self.send(&Command::Rejected(feature.name))?;
+                                    }
+                                },
+
                                 // these features are accepted, but (currently) ignored
-                                "myname" | "egt" | "option" | "variants" => {
+                                "myname" | "egt" | "option" => {
                                     if let FeatureValue::String(_) = feature.value {
                                         self.send(&Command::Accepted(feature.name))?;
                                     } else {
@@ -690,7 +938,7 @@ impl XboardClient {
         let move_string = if self.san {
             mv.to_string()
         } else {
-            format!("{:#}", mv)
+            coordinate_move(mv)
         };
 
         let cmd = Command::UserMove(move_string);
@@ -702,8 +950,9 @@ impl XboardClient {
         }
     }
 
-    /// Pings the engine and waits for a response.
-    fn ping(&mut self) -> Result<(), EngineError> {
+    /// Pings the engine and waits for a response. Any result the engine sends before responding is
+    /// a false claim, unless the game is already over.
+    fn ping(&mut self, game_over: bool) -> Result<(), EngineError> {
         if self.ping > 0 {
             self.send(&Command::Ping(self.ping))?;
             loop {
@@ -718,7 +967,7 @@ impl XboardClient {
                     Ok(Response::IllegalMove(_, _)) => {
                         return Err(EngineError::RejectedLegalMove);
                     },
-                    Ok(Response::GameResult(_, _)) => {
+                    Ok(Response::GameResult(_, _)) if !game_over => {
                         return Err(EngineError::FalseResultClaim);
                     },
                     Ok(_) => { /*todo!()*/ }
@@ -775,6 +1024,13 @@ impl EngineInterface for XboardClient {
     fn new_game(&mut self, game: &Game) -> Result<(), EngineError> {
         // TODO: include all this in init() and only allow one game per engine invocation
         self.send(&Command::New)?;
+        let variant = game.history().initial_position().variant();
+        if variant != Variant::Normal {
+            if !self.variants.contains(&variant.to_string()) {
+                return Err(EngineError::UnsupportedVariant);
+            }
+            self.send(&Command::Variant(variant.to_string()))?;
+        }
         self.send(&Command::Post)?;
         if self.ponder {
             self.send(&Command::Ponder)?;
@@ -782,8 +1038,12 @@ impl EngineInterface for XboardClient {
             self.send(&Command::NoPonder)?;
         }
         match game.clock().time_control() {
+            // xboard has no level without a clock
             TimeControl::Infinite => {
-                panic!("infinite time control");
+                return Err(EngineError::UnsupportedTimeControl);
+            },
+            TimeControl::Stages(stages) if stages.is_empty() => {
+                return Err(EngineError::UnsupportedTimeControl);
             },
             TimeControl::SuddenDeath(base) => {
                 self.send(&Command::Level{ mps: 0, base, inc: Duration::from_secs(0) })?;
@@ -797,6 +1057,20 @@ impl EngineInterface for XboardClient {
             TimeControl::Exact(time) => {
                 self.send(&Command::SetTime(time))?;
             },
+            // xboard has no delays, but time given back after a move acts like an increment
+            TimeControl::Delay{ base, delay } | TimeControl::Bronstein{ base, delay } => {
+                self.send(&Command::Level{ mps: 0, base, inc: delay })?;
+            },
+            // only one stage can be described, but the `time` and `otim` commands keep the
+            // engine's clock right when later stages begin
+            TimeControl::Stages(stages) => {
+                let stage = stages.first().expect("INFALLIBLE");
+                self.send(&Command::Level{
+                    mps: stage.moves.unwrap_or(0),
+                    base: stage.base,
+                    inc: stage.inc,
+                })?;
+            },
         }
         self.force_mode = false;
 
@@ -809,7 +1083,7 @@ impl EngineInterface for XboardClient {
         if !game.history().is_empty() {
             self.send_moves(game)?;
         } else {
-            self.ping()?;
+            self.ping(false)?;
         }
 
         Ok(())
@@ -828,7 +1102,7 @@ impl EngineInterface for XboardClient {
         }
         self.move_count = game.history().len();
 
-        self.ping()?;
+        self.ping(false)?;
         Ok(())
     }
 
@@ -862,7 +1136,7 @@ impl EngineInterface for XboardClient {
         self.send(&game.result().expect("game result").into())?;
         self.send(&Command::Force)?;
         self.force_mode = true;
-        self.ping()?;
+        self.ping(true)?;
 
         Ok(())
     }
@@ -1067,6 +1341,68 @@ pub enum Command {
     /// `depth` is the maximum depth that the engine should search.
     SetDepth(usize), // idle
 
+    /// Tells the engine to measure its time in nodes rather than by the clock.
+    ///
+    /// ```text
+    /// nps <rate>
+    /// ```
+    ///
+    /// `rate` is the number of nodes which count as one second of thinking time. A rate of zero
+    /// returns to measuring by the clock.
+    Nps(u64), // idle
+
+    /// Tells the engine its own rating and its opponent's rating.
+    ///
+    /// ```text
+    /// rating <engine> <opponent>
+    /// ```
+    ///
+    /// A rating of zero means that it is unknown.
+    Rating{
+        /// The engine's rating.
+        engine: u32,
+        /// The opponent's rating.
+        opponent: u32,
+    },
+
+    /// Selects the rules for the game, which begins from the variant's starting position (or from
+    /// a position sent by `setboard`).
+    ///
+    /// ```text
+    /// variant <name>
+    /// ```
+    ///
+    /// `name` is the name of a variant listed by the `variants` feature.
+    Variant(String), // idle, abort search
+
+    /// Sets the pieces in hand of both players, in variants with drops.
+    ///
+    /// ```text
+    /// holding [<white>] [<black>]
+    /// ```
+    ///
+    /// `white` and `black` list the pieces held by each player, such as `PPN`.
+    Holding{ // any, abort search if changed
+        /// The pieces held by white.
+        white: String,
+        /// The pieces held by black.
+        black: String,
+    },
+
+    /// Sets an option which the engine defined using the `option` feature.
+    ///
+    /// ```text
+    /// option <name>[=<value>]
+    /// ```
+    ///
+    /// `value` is omitted for button options.
+    SetOption{ // idle
+        /// The name of the option.
+        name: String,
+        /// The new value of the option, if any.
+        value: Option<String>,
+    },
+
     /// Tells the engine how much memory it is allowed to use.
     ///
     /// ```text
@@ -1110,6 +1446,26 @@ pub enum Command {
     /// hint
     /// ```
     Hint, // pondering
+
+    /// Excludes a move from the search at the root of the current position.
+    ///
+    /// ```text
+    /// exclude <move>
+    /// exclude all
+    /// ```
+    ///
+    /// `move` is the move to be excluded. If `None`, all moves are excluded.
+    Exclude(Option<String>), // any
+
+    /// Includes a previously excluded move in the search at the root of the current position.
+    ///
+    /// ```text
+    /// include <move>
+    /// include all
+    /// ```
+    ///
+    /// `move` is the move to be included. If `None`, all moves are included.
+    Include(Option<String>), // any
 }
 
 impl From<GameResult> for Command {
@@ -1182,12 +1538,22 @@ impl fmt::Display for Command {
                 }
             }
             SetDepth(depth) => format!("sd {}", depth).fmt(f),
+            Nps(nps) => format!("nps {}", nps).fmt(f),
+            Rating{ engine, opponent } => format!("rating {} {}", engine, opponent).fmt(f),
+            Variant(name) => format!("variant {}", name).fmt(f),
+            Holding{ white, black } => format!("holding [{}] [{}]", white, black).fmt(f),
+            SetOption{ name, value: Some(value) } => format!("option {}={}", name, value).fmt(f),
+            SetOption{ name, value: None } => format!("option {}", name).fmt(f),
             Memory(mem) => format!("memory {}", mem).fmt(f),
             Post => "post".fmt(f),
             NoPost => "nopost".fmt(f),
             Ponder => "hard".fmt(f),
             NoPonder => "easy".fmt(f),
             Hint => "hint".fmt(f),
+            Exclude(Some(mv)) => format!("exclude {}", mv).fmt(f),
+            Exclude(None) => "exclude all".fmt(f),
+            Include(Some(mv)) => format!("include {}", mv).fmt(f),
+            Include(None) => "include all".fmt(f),
         }
     }
 }
@@ -1278,6 +1644,38 @@ impl FromStr for Command {
                 24 => Ok(Ponder),
                 25 => Ok(NoPonder),
                 26 => Ok(Hint),
+                27 => Ok(Exclude(None)),
+                28 => {
+                    Ok(Exclude(Some(args.get(1).expect("INFALLIBLE").as_str().to_string())))
+                },
+                29 => Ok(Include(None)),
+                30 => {
+                    Ok(Include(Some(args.get(1).expect("INFALLIBLE").as_str().to_string())))
+                },
+                31 => {
+                    Ok(Nps(args.get(1).expect("INFALLIBLE").as_str().parse()?))
+                },
+                32 => {
+                    Ok(SetOption{
+                        name: args.get(1).expect("INFALLIBLE").as_str().to_string(),
+                        value: args.get(2).map(|value| value.as_str().to_string()),
+                    })
+                },
+                33 => {
+                    Ok(Rating{
+                        engine: args.get(1).expect("INFALLIBLE").as_str().parse()?,
+                        opponent: args.get(2).expect("INFALLIBLE").as_str().parse()?,
+                    })
+                },
+                34 => {
+                    Ok(Variant(args.get(1).expect("INFALLIBLE").as_str().to_string()))
+                },
+                35 => {
+                    Ok(Holding{
+                        white: args.get(1).expect("INFALLIBLE").as_str().to_string(),
+                        black: args.get(2).expect("INFALLIBLE").as_str().to_string(),
+                    })
+                },
                 _ => unreachable!(),
             }
         } else {
@@ -1286,7 +1684,13 @@ impl FromStr for Command {
     }
 }
 
-const COMMANDS: [&str; 27] = [
+/// The pattern matching a move in a command: coordinate notation, a drop such as `N@f3`, or
+/// castling given as `O-O` or `O-O-O`.
+macro_rules! move_pattern {
+    () => { r"([a-h][1-8][a-h][1-8][qrbnk]?|[PNBRQ]@[a-h][1-8]|O-O(?:-O)?)" };
+}
+
+const COMMANDS: [&str; 36] = [
     r"^xboard\b",
     r"^protover\s+(\d+)\b",
     r"^accepted\s+(\w+)\b",
@@ -1296,7 +1700,7 @@ const COMMANDS: [&str; 27] = [
     r"^new\b",
     r"^force\b",
     r"^go\b",
-    r"^(?:usermove\s+)?([a-h][1-8][a-h][1-8][qrbn]?)\b",
+    concat!(r"^(?:usermove\s+)?", move_pattern!(), r"\b"),
     r"^setboard\s+(.+)\b",
     r"^draw\b",
     r"^result\s+([-/012]+)\b\s*(?:\{([^}]+)\})?",
@@ -1314,6 +1718,15 @@ const COMMANDS: [&str; 27] = [
     r"^hard\b",
     r"^easy\b",
     r"^hint\b",
+    r"^exclude\s+all\b",
+    concat!(r"^exclude\s+", move_pattern!(), r"\b"),
+    r"^include\s+all\b",
+    concat!(r"^include\s+", move_pattern!(), r"\b"),
+    r"^nps\s+(\d+)\b",
+    r"^option\s+([^=]*[^=\s])\s*(?:=(.*))?$",
+    r"^rating\s+(\d+)\s+(\d+)\b",
+    r"^variant\s+(\S+)",
+    r"^holding\s+\[([PNBRQpnbrq]*)\]\s*\[([PNBRQpnbrq]*)\]",
 ];
 
 lazy_static! {
@@ -1460,6 +1873,15 @@ impl Response {
     }
 }
 
+impl From<GameResult> for Response {
+    fn from(result: GameResult) -> Self {
+        match result.into() {
+            Command::GameResult{ result, reason } => Response::GameResult(result, reason),
+            _ => unreachable!(),
+        }
+    }
+}
+
 impl fmt::Display for Response {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Response::*;
@@ -1621,6 +2043,46 @@ lazy_static! {
     static ref FEATURE: Regex = Regex::new(r#"(\w+)=(?:(\d+)|"([^"]*)"|(\w+))"#).expect("INFALLIBLE");
 }
 
+/// Formats an engine-defined option as the value of an `option` feature.
+fn xboard_option(option: &EngineOption) -> String {
+    let name = option.name();
+
+    match option.kind() {
+        OptionKind::Spin{ default, min, max } => {
+            format!("{} -spin {} {} {}", name, default, min, max)
+        },
+        OptionKind::Check{ default } => format!("{} -check {}", name, *default as u8),
+        OptionKind::Combo{ default, choices } => {
+            let choices: Vec<_> = choices.iter()
+                .map(|choice| {
+                    if choice == default { format!("*{}", choice) } else { choice.to_owned() }
+                })
+                .collect();
+            format!("{} -combo {}", name, choices.join(" /// "))
+        },
+        OptionKind::String{ default } => format!("{} -string {}", name, default),
+        OptionKind::Button => format!("{} -button", name),
+        OptionKind::File{ default } => format!("{} -file {}", name, default.display()),
+        OptionKind::Path{ default } => format!("{} -path {}", name, default.display()),
+    }
+}
+
+/// Returns the names of the supported variants, as the value of the `variants` feature.
+fn variant_names() -> String {
+    let names: Vec<_> = Variant::ALL.iter().map(|variant| variant.to_string()).collect();
+    names.join(",")
+}
+
+/// Formats a move in coordinate notation, except that castling in Fischer Random Chess is given
+/// as `O-O` or `O-O-O`, as the protocol requires.
+fn coordinate_move<T: ValidMove + fmt::Display>(mv: &T) -> String {
+    if mv.move_type() == MoveType::Castling && mv.position().variant() == Variant::FischerRandom {
+        mv.to_string()
+    } else {
+        format!("{:#}", mv)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// Error type for xboard
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1691,6 +2153,32 @@ mod test {
         assert_eq!(Memory(512).to_string(), "memory 512");
     }
 
+    #[test]
+    fn format_setup_commands() {
+        use Command::*;
+
+        assert_eq!(Rating{ engine: 2100, opponent: 1850 }.to_string(), "rating 2100 1850");
+        assert_eq!(Variant("fischerandom".to_string()).to_string(), "variant fischerandom");
+        assert_eq!(Holding{ white: "PPN".to_string(), black: "".to_string() }.to_string(),
+            "holding [PPN] []");
+    }
+
+    #[test]
+    fn format_option() {
+        assert_eq!(xboard_option(&EngineOption::spin("Hash", 256, 1, 65536)),
+            "Hash -spin 256 1 65536");
+        assert_eq!(xboard_option(&EngineOption::check("Rating Contempt", true)),
+            "Rating Contempt -check 1");
+        assert_eq!(xboard_option(&EngineOption::combo("Style", "Normal", &["Solid", "Normal"])),
+            "Style -combo Solid /// *Normal");
+        assert_eq!(xboard_option(&EngineOption::string("Player", "")), "Player -string ");
+        assert_eq!(xboard_option(&EngineOption::button("Save Hash")), "Save Hash -button");
+        assert_eq!(xboard_option(&EngineOption::file("Hash File", "tinman.hash".into())),
+            "Hash File -file tinman.hash");
+        assert_eq!(xboard_option(&EngineOption::path("Tablebase Path", "".into())),
+            "Tablebase Path -path ");
+    }
+
     #[test]
     fn parse_command() {
         use Command::*;
@@ -1737,6 +2225,28 @@ mod test {
         assert_eq!(Ok(SetTime(Duration::from_millis(10))), "st 0.01".parse());
         assert_eq!(Ok(SetDepth(12)), "sd 12".parse());
         assert_eq!(Ok(Memory(512)), "memory 512".parse());
+        assert_eq!(Ok(Exclude(Some("e2e4".to_string()))), "exclude e2e4".parse());
+        assert_eq!(Ok(Exclude(None)), "exclude all".parse());
+        assert_eq!(Ok(Include(Some("a7a8q".to_string()))), "include a7a8q".parse());
+        assert_eq!(Ok(Include(None)), "include all".parse());
+        assert_eq!(Ok(Exclude(Some("N@f3".to_string()))), "exclude N@f3".parse());
+        assert_eq!(Ok(Exclude(Some("O-O".to_string()))), "exclude O-O".parse());
+        assert_eq!(Ok(Include(Some("P@e4".to_string()))), "include P@e4".parse());
+        assert_eq!(Ok(Include(Some("O-O-O".to_string()))), "include O-O-O".parse());
+        assert_eq!(Ok(Nps(50000)), "nps 50000".parse());
+        assert_eq!(Ok(Rating{ engine: 2100, opponent: 0 }), "rating 2100 0".parse());
+        assert_eq!(Ok(Variant("3check".to_string())), "variant 3check".parse());
+        assert_eq!(Ok(UserMove("O-O-O".to_string())), "O-O-O".parse());
+        assert_eq!(Ok(UserMove("O-O".to_string())), "usermove O-O".parse());
+        assert_eq!(Ok(UserMove("N@f3".to_string())), "usermove N@f3".parse());
+        assert_eq!(Ok(UserMove("P@e4".to_string())), "P@e4".parse());
+        assert_eq!(Ok(Holding{ white: "".to_string(), black: "QRp".to_string() }),
+            "holding [] [QRp]".parse());
+        assert_eq!(
+            Ok(SetOption{ name: "Hash File".to_owned(), value: Some("a b.hash".to_owned()) }),
+            "option Hash File=a b.hash".parse());
+        assert_eq!(Ok(SetOption{ name: "Save Hash".to_owned(), value: None }),
+            "option Save Hash".parse());
     }
 
     #[test]
@@ -1748,6 +2258,10 @@ mod test {
         assert_eq!(GameResult("1/2-1/2".to_string(), Some("stalemate".to_string())).to_string(),
             "1/2-1/2 {stalemate}");
         assert_eq!(GameResult("0-1".to_string(), None).to_string(), "0-1");
+        assert_eq!(Response::from(chess::game::GameResult::Draw(
+            Some(chess::game::DrawReason::Repetition))).to_string(), "1/2-1/2 {repetition}");
+        assert_eq!(OfferDraw.to_string(), "offer draw");
+        assert_eq!(Resign.to_string(), "resign");
         assert_eq!(Hint("g1f3".to_string()).to_string(), "Hint: g1f3");
         assert_eq!(IllegalMove("e1g1".to_string(),
             Some("castling through check".to_string())).to_string(),
@@ -0,0 +1,163 @@
+//! Implements analysis of a single position from the command line.
+//!
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::path::PathBuf;
+use chess::{Position, MoveRc};
+use chess::game::Game;
+use protocols::options::EngineOption;
+use protocols::{Protocol, Action, SearchAction, SearchLimits, Thinking, Score};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A `Protocol` which directs the engine to search a single position, printing its thinking
+/// output as it goes. Each line of output gives the depth, score, time, nodes, how full the hash
+/// table is and the principal variation.
+///
+/// The transposition table can be loaded from a file before the search and saved to a file
+/// afterwards, so that a long analysis can be resumed later.
+///
+/// # Example
+/// ```no_run
+/// use tinman::Engine;
+/// use tinman::analysis::Analysis;
+/// use protocols::SearchLimits;
+///
+/// let mut limits = SearchLimits::new();
+/// limits.set_depth(Some(10));
+///
+/// let mut analysis = Analysis::new(chess::Position::new(), limits);
+/// analysis.save_hash("analysis.hash".into());
+///
+/// Engine::new(analysis).run();
+/// ```
+#[derive(Debug)]
+pub struct Analysis {
+    game: Game,
+    limits: SearchLimits,
+    load_hash: Option<PathBuf>,
+    save_hash: Option<PathBuf>,
+    stage: Stage,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stage {
+    LoadHash,
+    Search,
+    SaveHash,
+    Done,
+}
+
+impl Analysis {
+    /// Creates an `Analysis` which searches `pos` using `limits`.
+    pub fn new(pos: Position, limits: SearchLimits) -> Self {
+        Analysis {
+            game: Game::starting_at(pos),
+            limits,
+            load_hash: None,
+            save_hash: None,
+            stage: Stage::LoadHash,
+        }
+    }
+
+    /// Sets a file from which to load the transposition table before searching.
+    pub fn load_hash(&mut self, path: PathBuf) -> &mut Self {
+        self.load_hash = Some(path);
+        self
+    }
+
+    /// Sets a file to which the transposition table is saved after searching.
+    pub fn save_hash(&mut self, path: PathBuf) -> &mut Self {
+        self.save_hash = Some(path);
+        self
+    }
+
+    fn print(thinking: &Thinking) {
+        let score = match thinking.score() {
+            Score::Val(val) => format!("{:+.2}", f64::from(val) / 100.0),
+            Score::MateIn(plies) if plies > 0 => format!("#{}", (plies + 1) / 2),
+            Score::MateIn(plies) => format!("#-{}", -plies / 2),
+        };
+        let pv: Vec<_> = thinking.pv()
+            .map_or_else(Vec::new, |pv| pv.iter().map(|mv| mv.to_string()).collect());
+
+        println!("{:3} {:>7} {:9.3} {:12} {:5.1}%  {}",
+            thinking.depth(), score, thinking.time().as_secs_f64(), thinking.nodes(),
+            f64::from(thinking.hashfull()) / 10.0, pv.join(" "));
+    }
+}
+
+impl Protocol for Analysis {
+    fn wait_for_direction(&mut self) -> Action {
+        loop {
+            match self.stage {
+                Stage::LoadHash => {
+                    self.stage = Stage::Search;
+                    if let Some(path) = &self.load_hash {
+                        return Action::LoadHash(path.clone());
+                    }
+                },
+                Stage::Search => {
+                    self.stage = Stage::SaveHash;
+                    return Action::Search;
+                },
+                Stage::SaveHash => {
+                    self.stage = Stage::Done;
+                    if let Some(path) = &self.save_hash {
+                        return Action::SaveHash(path.clone());
+                    }
+                },
+                Stage::Done => return Action::Quit,
+            }
+        }
+    }
+
+    fn send_move(&mut self, thinking: &Thinking) {
+        let best_move = thinking.best_move().map_or("(none)".to_owned(), |mv| mv.to_string());
+        println!();
+        println!("Best move: {}", best_move);
+    }
+
+    fn send_thinking(&mut self, thinking: &Thinking) {
+        Self::print(thinking);
+    }
+
+    fn send_debug_msg(&mut self, msg: &str) {
+        eprintln!("{}", msg);
+    }
+
+    fn check_input(&mut self) -> Option<SearchAction> {
+        None
+    }
+
+    fn game(&self) -> &Game {
+        &self.game
+    }
+
+    fn ponder_move(&self) -> Option<&MoveRc> {
+        None
+    }
+
+    fn search_limits(&self) -> SearchLimits {
+        self.limits
+    }
+
+    fn search_moves(&self) -> Option<&[MoveRc]> {
+        None
+    }
+
+    fn ratings(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn draw_offered(&self) -> bool {
+        false
+    }
+
+    fn declare_options(&mut self, _options: &[EngineOption]) { }
+}
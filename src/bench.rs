@@ -0,0 +1,174 @@
+//! Implements a benchmark which searches a fixed set of positions to a fixed depth.
+//!
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::time::Duration;
+use chess::{Position, MoveRc};
+use chess::game::Game;
+use protocols::options::EngineOption;
+use protocols::{Protocol, Action, SearchAction, SearchLimits, Thinking};
+
+/// The positions searched by the benchmark, in Forsyth-Edwards Notation (FEN).
+pub const POSITIONS: [&str; 12] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 11",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r1bq1rk1/pp2ppbp/2np1np1/8/3NP3/2N1BP2/PPPQ2PP/R3KB1R w KQ - 3 9",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+    "8/8/8/4k3/8/8/3QK3/8 w - - 0 1",
+    "8/k7/3p4/p2P1p2/P2P1P2/8/8/K7 w - - 0 1",
+];
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A `Protocol` which directs the engine to search each of the benchmark [`POSITIONS`] to a fixed
+/// depth, with the hash table cleared before each search.
+///
+/// The result of each search is printed as it completes, followed by the total nodes, time and
+/// nodes per second once all positions have been searched. Since the search is deterministic, the
+/// total number of nodes serves as a signature for the engine's behavior.
+///
+/// # Example
+/// ```no_run
+/// use tinman::Engine;
+/// use tinman::bench::Bench;
+///
+/// Engine::new(Bench::new(6)).run();
+/// ```
+#[derive(Debug)]
+pub struct Bench {
+    positions: Vec<Position>,
+    game: Game,
+    next: usize,
+    hash_cleared: bool,
+    depth: usize,
+    nodes: u64,
+    time: Duration,
+}
+
+impl Bench {
+    /// Creates a `Bench` which searches each position to `depth`.
+    pub fn new(depth: usize) -> Self {
+        Bench {
+            positions: POSITIONS.iter()
+                .map(|fen| fen.parse().expect("INFALLIBLE"))
+                .collect(),
+            game: Game::new(),
+            next: 0,
+            hash_cleared: false,
+            depth,
+            nodes: 0,
+            time: Duration::default(),
+        }
+    }
+
+    /// Returns the total number of nodes searched so far.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Returns the total time spent searching so far.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns the average number of nodes searched per second so far.
+    pub fn nps(&self) -> u64 {
+        (u128::from(self.nodes) * 1000).checked_div(self.time.as_millis()).unwrap_or(0) as u64
+    }
+}
+
+impl Protocol for Bench {
+    fn wait_for_direction(&mut self) -> Action {
+        if let Some(pos) = self.positions.get(self.next) {
+            if !self.hash_cleared {
+                self.hash_cleared = true;
+                return Action::ClearHash;
+            }
+
+            self.game = Game::starting_at(pos.clone());
+            self.hash_cleared = false;
+            self.next += 1;
+
+            Action::Search
+        } else {
+            println!();
+            println!("Total time (ms) : {}", self.time.as_millis());
+            println!("Nodes searched  : {}", self.nodes);
+            println!("Nodes/second    : {}", self.nps());
+
+            Action::Quit
+        }
+    }
+
+    fn send_move(&mut self, thinking: &Thinking) {
+        self.nodes += thinking.nodes();
+        self.time += thinking.time();
+
+        let best_move = thinking.best_move().map_or("(none)".to_owned(), |mv| mv.to_string());
+        println!("Position {:2}/{}: {:8} {:12} nodes  {}",
+            self.next, self.positions.len(), best_move, thinking.nodes(),
+            self.game.position());
+    }
+
+    fn send_thinking(&mut self, _thinking: &Thinking) { }
+
+    fn send_debug_msg(&mut self, _msg: &str) { }
+
+    fn check_input(&mut self) -> Option<SearchAction> {
+        None
+    }
+
+    fn game(&self) -> &Game {
+        &self.game
+    }
+
+    fn ponder_move(&self) -> Option<&MoveRc> {
+        None
+    }
+
+    fn search_limits(&self) -> SearchLimits {
+        let mut limits = SearchLimits::new();
+        limits.set_depth(Some(self.depth));
+
+        limits
+    }
+
+    fn search_moves(&self) -> Option<&[MoveRc]> {
+        None
+    }
+
+    fn ratings(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn draw_offered(&self) -> bool {
+        false
+    }
+
+    fn declare_options(&mut self, _options: &[EngineOption]) { }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn positions_are_valid() {
+        for fen in POSITIONS.iter() {
+            let pos: Position = fen.parse().expect(fen);
+            assert!(pos.moves().next().is_some(), "{}", fen);
+        }
+    }
+}
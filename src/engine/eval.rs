@@ -8,7 +8,8 @@
 //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 use std::ops;
-use chess::{Color, Piece, Square, Position};
+use std::io::{self, BufRead, ErrorKind};
+use chess::{Color, Piece, Square, Position, Variant};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// Score
@@ -168,17 +169,96 @@ const END_KING_TABLE: [i16; Square::COUNT] =  [
     -50, -40, -30, -20, -20, -30, -40, -50, // h
 ];  
 
+/// Bonus for the king's closeness to the center in King of the Hill, added to the king tables.
+const HILL_KING_TABLE: [i16; Square::COUNT] =  [
+    //  1    2    3    4    5    6    7    8
+      0,   0,   0,   0,   0,   0,   0,   0, // a
+      0,  30,  30,  30,  30,  30,  30,   0, // b
+      0,  30, 100, 100, 100, 100,  30,   0, // c
+      0,  30, 100, 300, 300, 100,  30,   0, // d
+      0,  30, 100, 300, 300, 100,  30,   0, // e
+      0,  30, 100, 100, 100, 100,  30,   0, // f
+      0,  30,  30,  30,  30,  30,  30,   0, // g
+      0,   0,   0,   0,   0,   0,   0,   0, // h
+];
+
+/// Bonus for the number of checks given in Three-check.
+const CHECKS_GIVEN_VAL: [i16; 4] = [ 0, 80, 250, 0 ];
+
+/// Bonus for each piece fewer than the opponent in the losing variants.
+const FEWER_PIECES_VAL: i16 = 100;
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Adjustable weights used by the evaluation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EvalWeights {
+    piece_val: [i16; Piece::COUNT],
+}
+
+impl EvalWeights {
+    /// Returns the value of a piece.
+    pub fn piece_val(&self, piece: Piece) -> i16 {
+        self.piece_val[piece as usize]
+    }
 
-/// Returns the value of a piece.
-pub fn piece_val(piece: Piece) -> i16 {
-    PIECE_VAL[piece as usize]
+    /// Reads weights from a text file. Each line gives the name of a weight followed by its value,
+    /// such as `knight 320`. Blank lines and lines beginning with `#` are ignored, and any weight
+    /// not given keeps its default value.
+    ///
+    /// The weights are the values of the pawn, knight, bishop, rook and queen.
+    pub fn read(r: impl BufRead) -> io::Result<Self> {
+        let mut weights = EvalWeights::default();
+
+        for line in r.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || io::Error::new(ErrorKind::InvalidData,
+                format!("invalid weight: {}", line));
+            let mut words = line.split_whitespace();
+            let piece = match words.next() {
+                Some("pawn") => Piece::Pawn,
+                Some("knight") => Piece::Knight,
+                Some("bishop") => Piece::Bishop,
+                Some("rook") => Piece::Rook,
+                Some("queen") => Piece::Queen,
+                _ => return Err(invalid()),
+            };
+            let value = words.next().and_then(|value| value.parse().ok()).ok_or_else(invalid)?;
+            if words.next().is_some() {
+                return Err(invalid());
+            }
+
+            weights.piece_val[piece as usize] = value;
+        }
+
+        Ok(weights)
+    }
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        EvalWeights {
+            piece_val: PIECE_VAL,
+        }
+    }
 }
 
 /// Returns the estimated static score for the current search position.
-pub fn evaluate(pos: &Position) -> Score {
+pub fn evaluate(pos: &Position, weights: &EvalWeights) -> Score {
     use Color::*;
     use Piece::*;
 
+    if pos.variant().has_forced_captures() {
+        // the goal is to lose every piece, whatever its value
+        let pieces = |c: Color| pos.occupied_by(c).len() as i16;
+        return (FEWER_PIECES_VAL * (pieces(!pos.turn()) - pieces(pos.turn()))).into();
+    }
+
     let mut val = [0; Color::COUNT];
     let mut total_piece_val = 0;
 
@@ -192,10 +272,15 @@ pub fn evaluate(pos: &Position) -> Score {
             for sq in pos.occupied_by_piece(color, piece) {
                 count += 1;
                 let sq = if color == White { sq as usize } else { sq as usize ^ 0o07 };
-                val[color as usize] += PIECE_VAL[piece as usize]
+                val[color as usize] += weights.piece_val(piece)
                     + PIECE_SQUARE_VAL[piece as usize][sq as usize];
             }
-            total_piece_val += count * PIECE_VAL[piece as usize];
+
+            // pieces in hand can be dropped on any free square, and count as material
+            let held = pos.pocket(color, piece) as i16;
+            val[color as usize] += held * weights.piece_val(piece);
+            count += held;
+            total_piece_val += count * weights.piece_val(piece);
 
             if count > 0 {
                 match piece {
@@ -208,19 +293,34 @@ pub fn evaluate(pos: &Position) -> Score {
     }
 
     for color in [White, Black].iter().copied() {
-        let sq = pos.occupied_by_piece(color, King).peek().expect("INFALLIBLE");
+        let sq = match pos.occupied_by_piece(color, King).peek() {
+            Some(sq) => sq,
+            None => continue, // exploded in Atomic
+        };
         let sq = if color == White { sq as usize } else { sq as usize ^ 0o07 };
 
-        if total_piece_val > 3*PIECE_VAL[Queen as usize] {
+        if total_piece_val > 3*weights.piece_val(Queen) {
             val[color as usize] += MID_KING_TABLE[sq as usize];
-        } else if total_piece_val > 2*PIECE_VAL[Queen as usize] {
+        } else if total_piece_val > 2*weights.piece_val(Queen) {
             val[color as usize] += (MID_KING_TABLE[sq as usize] + END_KING_TABLE[sq as usize])/2;
         } else {
             val[color as usize] += END_KING_TABLE[sq as usize];
         }
+
+        match pos.variant() {
+            Variant::KingOfTheHill => val[color as usize] += HILL_KING_TABLE[sq],
+            Variant::ThreeCheck => {
+                val[color as usize] += CHECKS_GIVEN_VAL[pos.checks_given(color) as usize];
+            },
+            _ => { },
+        }
     }
 
     let val = val[pos.turn() as usize] - val[!pos.turn() as usize];
+    if pos.variant() == Variant::KingOfTheHill {
+        // either king can still win by reaching the center, even without material
+        return val.into();
+    }
 
     let strong_side = if val > 0 { pos.turn() } else { !pos.turn() };
     let weak_side = !strong_side as usize;
@@ -240,34 +340,81 @@ pub fn evaluate(pos: &Position) -> Score {
 #[cfg(test)]
 mod eval_test {
     use std::str::FromStr;
-    use chess::Position;
-    use super::{Score, evaluate};
+    use chess::{Position, Piece, Variant};
+    use super::{Score, EvalWeights, evaluate};
 
     #[test]
     fn eval() {
+        let weights = EvalWeights::default();
         assert_eq!(
-            evaluate(&Position::from_str("k7/8/8/8/8/8/8/K7 w - - 0 1").unwrap()),
+            evaluate(&Position::from_str("k7/8/8/8/8/8/8/K7 w - - 0 1").unwrap(), &weights),
             Score::from(0)
         );
         assert_eq!(
-            evaluate(&Position::from_str("kq6/8/8/8/8/8/8/K7 w - - 0 1").unwrap()),
+            evaluate(&Position::from_str("kq6/8/8/8/8/8/8/K7 w - - 0 1").unwrap(), &weights),
             Score::from(-1000)
         );
         assert_eq!(
-            evaluate(&Position::from_str("k7/8/8/8/8/8/8/KQ6 w - - 0 1").unwrap()),
+            evaluate(&Position::from_str("k7/8/8/8/8/8/8/KQ6 w - - 0 1").unwrap(), &weights),
             Score::from(1000)
         );
         assert_eq!(
-            evaluate(&Position::from_str("k7/8/8/8/8/8/8/KQ6 b - - 0 1").unwrap()),
+            evaluate(&Position::from_str("k7/8/8/8/8/8/8/KQ6 b - - 0 1").unwrap(), &weights),
             Score::from(-1000)
         );
         assert_eq!(
-            evaluate(&Position::from_str("k7/3p4/8/8/8/8/8/K7 b - - 0 1").unwrap()),
-            evaluate(&Position::from_str("k7/8/8/8/8/8/3P4/K7 w - - 0 1").unwrap()),
+            evaluate(&Position::from_str("k7/3p4/8/8/8/8/8/K7 b - - 0 1").unwrap(), &weights),
+            evaluate(&Position::from_str("k7/8/8/8/8/8/3P4/K7 w - - 0 1").unwrap(), &weights),
         );
         assert_eq!(
-            evaluate(&Position::from_str("k7/8/8/8/8/8/3P4/K7 w - - 0 1").unwrap()),
-            -evaluate(&Position::from_str("k7/8/8/8/8/8/3P4/K7 b - - 0 1").unwrap()),
+            evaluate(&Position::from_str("k7/8/8/8/8/8/3P4/K7 w - - 0 1").unwrap(), &weights),
+            -evaluate(&Position::from_str("k7/8/8/8/8/8/3P4/K7 b - - 0 1").unwrap(), &weights),
         );
     }
+
+    #[test]
+    fn variant_eval() {
+        let weights = EvalWeights::default();
+        let fen = "k7/8/8/8/8/2K5/8/8 w - - 0 1";
+        let pos = Position::from_fen_str_variant(fen, Variant::KingOfTheHill).expect("valid fen");
+        assert!(evaluate(&pos, &weights) > Score::from(0));
+        let pos = Position::from_str(fen).expect("valid fen");
+        assert_eq!(evaluate(&pos, &weights), Score::from(0));
+
+        let fen = "k7/8/8/8/8/8/8/KQ6 w - - 0 1";
+        let eval_checks = |checks: &str| {
+            let pos = Position::from_fen_str_variant(&format!("{} {}", fen, checks),
+                Variant::ThreeCheck).expect("valid fen");
+            evaluate(&pos, &weights)
+        };
+        assert!(eval_checks("+2+0") > eval_checks("+0+0"));
+        assert!(eval_checks("+0+2") < eval_checks("+0+0"));
+
+        let eval_pockets = |fen: &str| {
+            let pos = Position::from_fen_str_variant(fen, Variant::Crazyhouse).expect("valid fen");
+            evaluate(&pos, &weights)
+        };
+        assert_eq!(eval_pockets("k7/8/8/8/8/8/8/K7[Q] w - - 0 1"), Score::from(1000));
+        assert_eq!(eval_pockets("k7/8/8/8/8/8/8/K7[Q] b - - 0 1"), Score::from(-1000));
+        assert_eq!(eval_pockets("k7/8/8/8/8/8/8/K7[Nn] w - - 0 1"), Score::from(0));
+
+        let pos = Position::from_fen_str_variant("k7/8/8/8/8/8/8/KQ6 w - - 0 1", Variant::Suicide)
+            .expect("valid fen");
+        assert!(evaluate(&pos, &weights) < Score::from(0));
+    }
+
+    #[test]
+    fn read_weights() {
+        let weights = EvalWeights::read("# weights\n\nknight 300\n  queen 950 \n".as_bytes())
+            .expect("valid weights");
+        assert_eq!(weights.piece_val(Piece::Knight), 300);
+        assert_eq!(weights.piece_val(Piece::Queen), 950);
+        assert_eq!(weights.piece_val(Piece::Pawn), EvalWeights::default().piece_val(Piece::Pawn));
+
+        assert!(EvalWeights::read("king 100\n".as_bytes()).is_err());
+        assert!(EvalWeights::read("rook\n".as_bytes()).is_err());
+        assert!(EvalWeights::read("rook 500 600\n".as_bytes()).is_err());
+        let pos = Position::from_str("k7/8/8/8/8/8/8/KQ6 w - - 0 1").expect("valid position");
+        assert_eq!(evaluate(&pos, &weights), Score::from(950));
+    }
 }
@@ -8,9 +8,12 @@
 //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 use std::num::NonZeroU16;
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 use std::mem::size_of;
-use chess::{Square, Promotion, Move, ValidMove, MoveBuilder, Position, Zobrist};
+use std::io::{self, Read, Write, ErrorKind};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use chess::{Square, Piece, Promotion, Move, MoveType, ValidMove, MoveBuilder, Position, Zobrist};
 use chess::Result;
 use crate::engine::Score;
 
@@ -18,7 +21,8 @@ use crate::engine::Score;
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// A representation of a move that fits in 16 bits.
 ///
-/// `Option<HashMove>` is also guaranteed to be only 16 bits.
+/// `Option<HashMove>` is also guaranteed to be only 16 bits. Drops are stored with an origin equal
+/// to their destination, and the dropped piece in place of the promotion.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct HashMove(NonZeroU16);
 
@@ -30,17 +34,55 @@ impl HashMove {
         Square::try_from(((self.0.get() >> 3) & 0o77) as usize).expect("INFALLIBLE")
     }
     pub fn promotion(self) -> Option<Promotion> {
+        if self.is_drop() {
+            return None;
+        }
         match self.0.get() & 0o7 {
             0 => None,
             1 => Some(Promotion::ToKnight),
             2 => Some(Promotion::ToBishop),
             3 => Some(Promotion::ToRook),
             4 => Some(Promotion::ToQueen),
+            5 => Some(Promotion::ToKing),
+            _ => unreachable!(),
+        }
+    }
+    pub fn is_drop(self) -> bool {
+        self.origin() == self.destination()
+    }
+    /// Returns the dropped piece, if the move is a drop.
+    pub fn drop_piece(self) -> Option<Piece> {
+        if !self.is_drop() {
+            return None;
+        }
+        match self.0.get() & 0o7 {
+            0 => Some(Piece::Pawn),
+            1 => Some(Piece::Knight),
+            2 => Some(Piece::Bishop),
+            3 => Some(Piece::Rook),
+            4 => Some(Piece::Queen),
+            5 => Some(Piece::King),
             _ => unreachable!(),
         }
     }
 
+    /// Restores a move from its 16-bit representation, returning `None` if the bits are invalid.
+    fn from_bits(bits: u16) -> Option<HashMove> {
+        if bits & 0o7 > 5 {
+            return None;
+        }
+
+        NonZeroU16::new(bits).map(HashMove)
+    }
+
     pub fn validate<'a>(self, pos: &'a Position) -> Result<Move<'a>> {
+        if let Some(piece) = self.drop_piece() {
+            return MoveBuilder::new()
+                .drop_piece(piece)
+                .destination(self.destination())
+                .validate(pos);
+        }
+
         MoveBuilder::new()
             .origin(self.origin())
             .destination(self.destination())
@@ -54,9 +96,11 @@ impl<T: ValidMove> From<T> for HashMove {
         HashMove(NonZeroU16::new(
             ((mv.origin() as u16) << 9)
             + ((mv.destination() as u16) << 3)
-            + match mv.promotion() {
-                None => 0,
-                Some(prom) => prom as u16,
+            + match (mv.move_type(), mv.promotion()) {
+                // A pawn can't be dropped on a1, so the bits are never all zero
+                (MoveType::Drop, _) => mv.piece() as u16,
+                (_, None) => 0,
+                (_, Some(prom)) => prom as u16,
             }
         ).expect("INFALLIBLE"))
     }
@@ -138,30 +182,247 @@ impl HashEntry {
     pub fn best_move(&self) -> Option<HashMove> {
         self.best_move
     }
+
+    /// Packs an entry into its key and 64 bits of data. The data is also the last 8 bytes of the
+    /// on-disk format, in little-endian order.
+    fn pack(self) -> (u64, u64) {
+        let bound: u64 = match self.bound {
+            Bound::Lower => 1,
+            Bound::Exact => 2,
+            Bound::Upper => 3,
+        };
+        let data = u64::from(self.when)
+            | u64::from(self.depth) << 16
+            | bound << 24
+            | u64::from(i16::from(self.score) as u16) << 32
+            | u64::from(self.best_move.map_or(0, |mv| mv.0.get())) << 48;
+
+        (u64::from(self.zobrist), data)
+    }
+
+    /// Unpacks an entry packed by `pack`, returning `None` if the slot is empty or the data is
+    /// invalid.
+    fn unpack(key: u64, data: u64) -> Option<HashEntry> {
+        let bound = match (data >> 24) as u8 {
+            1 => Bound::Lower,
+            2 => Bound::Exact,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        let best_move = match (data >> 48) as u16 {
+            0 => None,
+            bits => Some(HashMove::from_bits(bits)?),
+        };
+
+        Some(HashEntry {
+            zobrist: key.into(),
+            when: data as u16,
+            depth: (data >> 16) as u8,
+            bound,
+            score: ((data >> 32) as u16 as i16).into(),
+            best_move,
+        })
+    }
+
+    /// Writes an entry (or an empty slot) using the on-disk format.
+    fn write(entry: Option<HashEntry>, w: &mut impl Write) -> io::Result<()> {
+        let (key, data) = entry.map_or((0, 0), HashEntry::pack);
+        w.write_all(&key.to_le_bytes())?;
+        w.write_all(&data.to_le_bytes())
+    }
+
+    /// Reads an entry (or an empty slot) using the on-disk format.
+    fn read(r: &mut impl Read) -> io::Result<Option<HashEntry>> {
+        let mut bytes = [0; HashTable::ENTRY_BYTES];
+        r.read_exact(&mut bytes)?;
+
+        let key = u64::from_le_bytes(bytes[0..8].try_into().expect("INFALLIBLE"));
+        let data = u64::from_le_bytes(bytes[8..16].try_into().expect("INFALLIBLE"));
+        if bytes[11] == 0 {
+            return Ok(None);
+        }
+
+        HashEntry::unpack(key, data).map(Some).ok_or_else(|| invalid_data("invalid entry"))
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Counters describing how the transposition table has been used since they were last reset.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct HashStats {
+    /// The number of lookups.
+    pub probes: u64,
+    /// The number of lookups which found an entry for the position.
+    pub hits: u64,
+    /// The number of hits whose score was used to end the search of a node.
+    pub cutoffs: u64,
+    /// The number of entries stored.
+    pub stores: u64,
+    /// The number of stores which overwrote an entry for a different position.
+    pub replacements: u64,
+    /// The number of hits whose move was illegal in the position, indicating that the entry
+    /// belongs to a different position with the same key.
+    pub collisions: u64,
+}
+
+impl HashStats {
+    /// Returns the percentage of probes which were hits.
+    pub fn hit_rate(&self) -> f64 {
+        if self.probes == 0 {
+            0.0
+        } else {
+            self.hits as f64 * 100.0 / self.probes as f64
+        }
+    }
+}
+
+impl fmt::Display for HashStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "probes {} hits {} ({:.1}%) cutoffs {} stores {} replacements {} collisions {}",
+            self.probes, self.hits, self.hit_rate(), self.cutoffs, self.stores,
+            self.replacements, self.collisions)
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
-/// A transposition table
+/// The counters behind `HashStats`, which may be updated by several threads at once.
+#[derive(Debug, Default)]
+struct StatCounters {
+    probes: AtomicU64,
+    hits: AtomicU64,
+    cutoffs: AtomicU64,
+    stores: AtomicU64,
+    replacements: AtomicU64,
+    collisions: AtomicU64,
+}
+
+impl StatCounters {
+    fn snapshot(&self) -> HashStats {
+        HashStats {
+            probes: self.probes.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+            cutoffs: self.cutoffs.load(Ordering::Relaxed),
+            stores: self.stores.load(Ordering::Relaxed),
+            replacements: self.replacements.load(Ordering::Relaxed),
+            collisions: self.collisions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn increment(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A slot in the transposition table, which may be read and written by several threads at once
+/// without locking.
+///
+/// The key is stored XORed with the entry's data, so that a slot torn by simultaneous writes from
+/// two threads fails verification rather than returning the data of another position.
+#[derive(Debug, Default)]
+struct HashSlot {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+impl HashSlot {
+    fn load(&self) -> Option<HashEntry> {
+        let check = self.check.load(Ordering::Relaxed);
+        let data = self.data.load(Ordering::Relaxed);
+
+        HashEntry::unpack(check ^ data, data)
+    }
+
+    fn store(&self, entry: Option<HashEntry>) {
+        let (key, data) = entry.map_or((0, 0), HashEntry::pack);
+        self.check.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// The slots for positions sharing the same index, aligned so that they fill exactly one cache
+/// line.
+#[derive(Debug, Default)]
+#[repr(align(64))]
+struct Bucket([HashSlot; HashTable::ENTRIES]);
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A transposition table, which may be shared between search threads.
+///
+/// When every slot in a bucket is full, the entry replaced is the one with the least depth, where
+/// entries from earlier searches are treated as being `AGE_WEIGHT` plies shallower for each search
+/// since they were stored.
 #[derive(Debug)]
-pub struct HashTable(Vec<BucketList>);
-type BucketList = [Option<HashEntry>; HashTable::BUCKETS];
+pub struct HashTable {
+    table: Vec<Bucket>,
+    stats: StatCounters,
+}
 
 impl HashTable {
-    const BUCKETS: usize = 4;
+    /// The number of entries in each bucket.
+    const ENTRIES: usize = 4;
+    /// The depth an entry loses for each search since it was stored, when choosing which entry to
+    /// replace.
+    const AGE_WEIGHT: i32 = 8;
+
+    /// Identifies a file as a saved hash table.
+    const MAGIC: [u8; 8] = *b"TINMANTT";
+    /// Version of the on-disk format.
+    const VERSION: u32 = 1;
+    /// Size of an entry in the on-disk format.
+    const ENTRY_BYTES: usize = 16;
+    /// The number of buckets sampled when calculating `hashfull`.
+    const HASHFULL_SAMPLE: usize = 250;
+    /// The largest table allowed, in bytes.
+    pub (super) const MAX_SIZE: usize = 0x0100_0000_0000; // 1 TB
 
     pub fn new(size: usize) -> HashTable {
+        HashTable {
+            table: Self::buckets(size),
+            stats: StatCounters::default(),
+        }
+    }
+
+    fn buckets(size: usize) -> Vec<Bucket> {
         let size = (size/2).next_power_of_two();
-        let len = size/size_of::<BucketList>();
+        let len = size/size_of::<Bucket>();
+
+        (0..len).map(|_| Bucket::default()).collect()
+    }
+
+    fn bucket(&self, zobrist: Zobrist) -> &Bucket {
+        &self.table[u64::from(zobrist) as usize & (self.table.len() - 1)]
+    }
+
+    /// Hints to the processor that the bucket for `zobrist` will be needed soon, so that it can be
+    /// loaded into the cache ahead of a call to `get` or `insert`.
+    #[cfg(target_arch = "x86_64")]
+    pub fn prefetch(&self, zobrist: Zobrist) {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
 
-        HashTable(vec![[None; Self::BUCKETS]; len])
+        let bucket: *const Bucket = self.bucket(zobrist);
+        // prefetching has no observable effect other than timing, and SSE is always available on
+        // x86_64
+        unsafe { _mm_prefetch::<_MM_HINT_T0>(bucket as *const i8) };
     }
 
+    /// Hints to the processor that the bucket for `zobrist` will be needed soon. This does nothing
+    /// on architectures without a supported prefetch instruction.
+    #[cfg(not(target_arch = "x86_64"))]
+    pub fn prefetch(&self, _zobrist: Zobrist) { }
+
     pub fn get(&self, zobrist: Zobrist, cur_ply: usize) -> Option<HashEntry> {
-        let index = u64::from(zobrist) as usize & (self.0.len() - 1);
+        increment(&self.stats.probes);
 
-        for bucket in 0..Self::BUCKETS {
-            match self.0[index][bucket] {
+        for slot in self.bucket(zobrist).0.iter() {
+            match slot.load() {
                 Some(mut entry) if zobrist == entry.zobrist => {
+                    increment(&self.stats.hits);
                     if entry.score >= Score::mates_in(1_000) {
                         entry.score = entry.score - cur_ply as i16;
                     } else if entry.score <= Score::mated_in(1_000) {
@@ -177,53 +438,153 @@ impl HashTable {
         None
     }
 
-    pub fn insert(&mut self, mut new_entry: HashEntry, cur_ply: usize) {
-        let index = u64::from(new_entry.zobrist) as usize & (self.0.len() - 1);
-
+    pub fn insert(&self, mut new_entry: HashEntry, cur_ply: usize) {
         if new_entry.score >= Score::mates_in(1_000) {
             new_entry.score = new_entry.score + cur_ply as i16;
         } else if new_entry.score <= Score::mated_in(1_000) {
             new_entry.score = new_entry.score - cur_ply as i16;
         }
 
-        let mut draft = 0;
-        let mut bucket = 0;
-        for b in 0..Self::BUCKETS {
-            match self.0[index][b] {
+        let bucket = self.bucket(new_entry.zobrist);
+        let mut replace = 0;
+        let mut replace_value = i32::MAX;
+        let mut replaced = false;
+        for (i, slot) in bucket.0.iter().enumerate() {
+            match slot.load() {
                 Some(entry) if new_entry.zobrist == entry.zobrist => {
-                    bucket = b;
+                    replace = i;
+                    replaced = false;
                     break;
                 },
                 Some(entry) => {
-                    let cur_draft = entry.depth as u16 + entry.when;
-                    if cur_draft > draft {
-                        draft = cur_draft;
-                        bucket = b;
+                    let age = i32::from(new_entry.when.wrapping_sub(entry.when));
+                    let value = i32::from(entry.depth) - age * Self::AGE_WEIGHT;
+                    if value < replace_value {
+                        replace = i;
+                        replace_value = value;
+                        replaced = true;
                     }
                 },
                 None => {
-                    draft = u16::max_value();
-                    bucket = b;
+                    if replace_value > i32::MIN {
+                        replace = i;
+                        replace_value = i32::MIN;
+                        replaced = false;
+                    }
                 },
             }
         }
 
-        self.0[index][bucket] = Some(new_entry);
+        increment(&self.stats.stores);
+        if replaced {
+            increment(&self.stats.replacements);
+        }
+
+        bucket.0[replace].store(Some(new_entry));
     }
 
     pub fn clear(&mut self) {
-        let len = self.0.len();
-        self.0.clear();
-        self.0.resize(len, [None; Self::BUCKETS]);
+        for slot in self.table.iter().flat_map(|bucket| bucket.0.iter()) {
+            slot.store(None);
+        }
     }
 
-    pub fn resize(&mut self, size: usize) {
-        let size = (size/2).next_power_of_two();
-        let len = size/size_of::<BucketList>();
+    /// Records that a hit was used to end the search of a node.
+    pub fn record_cutoff(&self) {
+        increment(&self.stats.cutoffs);
+    }
+
+    /// Records that a hit's move was illegal in the position searched.
+    pub fn record_collision(&self) {
+        increment(&self.stats.collisions);
+    }
+
+    /// Returns the counters accumulated since the last call to `reset_stats`.
+    pub fn stats(&self) -> HashStats {
+        self.stats.snapshot()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = StatCounters::default();
+    }
+
+    /// Returns the approximate number of entries per thousand which have been written during the
+    /// search numbered `now`, estimated from a sample at the start of the table.
+    pub fn hashfull(&self, now: u16) -> u16 {
+        let sample = &self.table[..Self::HASHFULL_SAMPLE.min(self.table.len())];
+        let used = sample.iter()
+            .flat_map(|bucket| bucket.0.iter())
+            .filter(|slot| slot.load().is_some_and(|entry| entry.when == now))
+            .count();
+        let total = sample.len() * Self::ENTRIES;
+
+        (used * 1000 / total) as u16
+    }
+
+    /// Writes the table to `w`. The data begins with a header containing a magic number, the
+    /// format version, the number of entries per bucket and the number of buckets.
+    pub fn save(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(&Self::MAGIC)?;
+        w.write_all(&Self::VERSION.to_le_bytes())?;
+        w.write_all(&(Self::ENTRIES as u32).to_le_bytes())?;
+        w.write_all(&(self.table.len() as u64).to_le_bytes())?;
+
+        for slot in self.table.iter().flat_map(|bucket| bucket.0.iter()) {
+            HashEntry::write(slot.load(), w)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a table previously written by `save`. The table takes the size it had when saved.
+    pub fn load(r: &mut impl Read) -> io::Result<HashTable> {
+        let mut magic = [0; 8];
+        let mut version = [0; 4];
+        let mut entries = [0; 4];
+        let mut len = [0; 8];
+        r.read_exact(&mut magic)?;
+        r.read_exact(&mut version)?;
+        r.read_exact(&mut entries)?;
+        r.read_exact(&mut len)?;
+
+        if magic != Self::MAGIC {
+            return Err(invalid_data("not a hash table file"));
+        }
+        if u32::from_le_bytes(version) != Self::VERSION {
+            return Err(invalid_data("unsupported hash table version"));
+        }
+        if u32::from_le_bytes(entries) as usize != Self::ENTRIES {
+            return Err(invalid_data("unsupported number of entries per bucket"));
+        }
+        let len = usize::try_from(u64::from_le_bytes(len))
+            .map_err(|_| invalid_data("hash table too large"))?;
+        if !len.is_power_of_two() {
+            return Err(invalid_data("invalid hash table size"));
+        }
+        if len > Self::MAX_SIZE / size_of::<Bucket>() {
+            return Err(invalid_data("hash table too large"));
+        }
+
+        let mut table = Vec::new();
+        table.try_reserve_exact(len)
+            .map_err(|_| io::Error::new(ErrorKind::OutOfMemory, "hash table too large"))?;
+        for _ in 0..len {
+            let bucket = Bucket::default();
+            for slot in bucket.0.iter() {
+                slot.store(HashEntry::read(r)?);
+            }
+            table.push(bucket);
+        }
 
-        self.0.clear();
-        self.0.resize(len, [None; Self::BUCKETS]);
-        self.0.shrink_to_fit();
+        Ok(HashTable {
+            table,
+            stats: StatCounters::default(),
+        })
+    }
+
+    pub fn resize(&mut self, size: usize) {
+        self.table = Vec::new();
+        self.table = Self::buckets(size);
     }
 }
 
@@ -238,4 +599,190 @@ mod test {
         use std::mem::size_of;
         assert_eq!(size_of::<Option<HashEntry>>(), 16);
     }
+
+    #[test]
+    fn bucket_size() {
+        use std::mem::{size_of, align_of};
+        assert_eq!(size_of::<HashSlot>(), HashTable::ENTRY_BYTES);
+        assert_eq!(size_of::<Bucket>(), 64);
+        assert_eq!(align_of::<Bucket>(), 64);
+    }
+
+    fn entries(table: &HashTable) -> Vec<Option<HashEntry>> {
+        table.table.iter().flat_map(|bucket| bucket.0.iter()).map(HashSlot::load).collect()
+    }
+
+    #[test]
+    fn pack_and_unpack() {
+        let pos = Position::new();
+        let mv = pos.moves().next().expect("INFALLIBLE");
+        let entry = HashEntry::new(pos.zobrist_key(), 0xfedc, 0xab, Bound::Upper,
+            Score::mated_in(7), mv.into());
+        let (key, data) = entry.pack();
+        assert_eq!(HashEntry::unpack(key, data), Some(entry));
+
+        let entry = HashEntry::new_without_move(pos.zobrist_key(), 0, 0, Bound::Lower,
+            Score::from(-1));
+        let (key, data) = entry.pack();
+        assert_eq!(HashEntry::unpack(key, data), Some(entry));
+        assert_eq!(HashEntry::unpack(0, 0), None);
+    }
+
+    #[test]
+    fn drops() {
+        use chess::Variant;
+        let pos = Position::from_fen_str_variant("4k3/8/8/8/8/8/8/4K3[Pn] w - - 0 1",
+            Variant::Crazyhouse).expect("valid fen");
+        for mv in pos.moves() {
+            let hash_mv = HashMove::from(mv.clone());
+            assert_eq!(hash_mv.is_drop(), mv.move_type() == MoveType::Drop);
+            assert_eq!(hash_mv.validate(&pos).expect("valid move"), mv);
+        }
+        assert_eq!(pos.moves().filter(|mv| mv.move_type() == MoveType::Drop).count(), 48);
+    }
+
+    #[test]
+    fn torn_slot() {
+        let a = HashEntry::new_without_move(0x1111.into(), 1, 1, Bound::Lower, Score::from(1));
+        let b = HashEntry::new_without_move(0x2222.into(), 2, 2, Bound::Upper, Score::from(2));
+        let slot = HashSlot::default();
+        slot.store(Some(a));
+        assert_eq!(slot.load(), Some(a));
+
+        // the check from one write and the data from another
+        let (_, data) = b.pack();
+        slot.data.store(data, Ordering::Relaxed);
+        let torn = slot.load().expect("valid data");
+        assert_ne!(torn.zobrist, a.zobrist);
+        assert_ne!(torn.zobrist, b.zobrist);
+    }
+
+    #[test]
+    fn aging() {
+        let table = HashTable::new(0x1_0000);
+        let len = table.table.len() as u64;
+        let key = |i: u64| Zobrist::from(i * len);
+        let entry = |i, when, depth| HashEntry::new_without_move(key(i), when, depth,
+            Bound::Exact, Score::from(0));
+
+        // a deep entry from an earlier search is replaced before a shallow one from this search
+        table.insert(entry(1, 1, 10), 0);
+        for i in 2 ..= 4 {
+            table.insert(entry(i, 2, 3), 0);
+        }
+        table.insert(entry(5, 2, 1), 0);
+        assert!(table.get(key(1), 0).is_none());
+
+        // within a search, the shallowest entry is replaced
+        table.insert(entry(6, 2, 2), 0);
+        assert!(table.get(key(5), 0).is_none());
+        for i in 2 ..= 4 {
+            assert!(table.get(key(i), 0).is_some());
+        }
+
+        // an entry for the same position is always replaced
+        table.insert(entry(6, 2, 1), 0);
+        assert_eq!(table.get(key(6), 0).map(|entry| entry.depth()), Some(1));
+        assert_eq!(table.stats().replacements, 2);
+    }
+
+    #[test]
+    fn concurrent_access() {
+        let table = HashTable::new(0x1000);
+        let entry = |key: u64| HashEntry::new_without_move(key.into(), key as u16,
+            (key >> 16) as u8, Bound::Exact, Score::from((key >> 24) as i16 & 0x3ff));
+
+        std::thread::scope(|scope| {
+            for thread in 0 .. 4u64 {
+                let table = &table;
+                scope.spawn(move || {
+                    for i in 0 .. 10_000u64 {
+                        let key = (i * 4 + thread).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+                        table.insert(entry(key), 0);
+                        if let Some(found) = table.get(key.into(), 0) {
+                            assert_eq!(found, entry(key));
+                        }
+                    }
+                });
+            }
+        });
+
+        for found in entries(&table).into_iter().flatten() {
+            assert_eq!(found, entry(u64::from(found.zobrist)));
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let pos = Position::new();
+        let mv = pos.moves().next().expect("INFALLIBLE");
+        let table = HashTable::new(0x1_0000);
+        table.insert(HashEntry::new(pos.zobrist_key(), 3, 5, Bound::Lower,
+            Score::from(-25), mv.into()), 0);
+        table.insert(HashEntry::new_without_move(0x1234_5678_9abc_def0.into(), 1, 2,
+            Bound::Upper, Score::mates_in(3)), 0);
+
+        let mut data = Vec::new();
+        table.save(&mut data).expect("INFALLIBLE");
+        assert_eq!(data.len(), 24 + table.table.len() * HashTable::ENTRIES * 16);
+
+        let loaded = HashTable::load(&mut data.as_slice()).expect("valid hash table");
+        assert_eq!(entries(&loaded), entries(&table));
+
+        // corrupted header
+        data[0] = b'X';
+        assert!(HashTable::load(&mut data.as_slice()).is_err());
+
+        // truncated data
+        data[0] = b'T';
+        assert!(HashTable::load(&mut &data[..data.len() - 1]).is_err());
+
+        // forged size
+        data[16..24].copy_from_slice(&(1u64 << 60).to_le_bytes());
+        let error = HashTable::load(&mut data.as_slice()).expect_err("table too large");
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn stats_and_hashfull() {
+        let pos = Position::new();
+        let mv = pos.moves().next().expect("INFALLIBLE");
+        let table_len = HashTable::HASHFULL_SAMPLE * HashTable::ENTRIES;
+        let mut table = HashTable::new(table_len * HashTable::ENTRY_BYTES * 2);
+        assert_eq!(table.table.len(), HashTable::HASHFULL_SAMPLE.next_power_of_two());
+        assert_eq!(table.hashfull(1), 0);
+
+        assert!(table.get(pos.zobrist_key(), 0).is_none());
+        table.insert(HashEntry::new(pos.zobrist_key(), 1, 5, Bound::Exact,
+            Score::from(10), mv.into()), 0);
+        assert!(table.get(pos.zobrist_key(), 0).is_some());
+        table.record_cutoff();
+
+        // fill the bucket, then overwrite the oldest entry with a different position
+        let index = u64::from(pos.zobrist_key()) & (table.table.len() as u64 - 1);
+        let len = table.table.len() as u64;
+        for i in 1 ..= HashTable::ENTRIES as u64 {
+            table.insert(HashEntry::new_without_move((index + i * len).into(), 2, 1,
+                Bound::Lower, Score::from(0)), 0);
+        }
+
+        let stats = table.stats();
+        assert_eq!(stats.probes, 2);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.cutoffs, 1);
+        assert_eq!(stats.stores, 1 + HashTable::ENTRIES as u64);
+        assert_eq!(stats.replacements, 1);
+        assert_eq!(stats.collisions, 0);
+
+        table.reset_stats();
+        assert_eq!(table.stats(), HashStats::default());
+
+        // fill every sampled entry in search 3
+        for i in 0 .. table_len as u64 {
+            table.insert(HashEntry::new_without_move(
+                (i % len + (i / len + 1) * len).into(), 3, 1, Bound::Lower, Score::from(0)), 0);
+        }
+        assert!(table.hashfull(3) > 900);
+        assert_eq!(table.hashfull(4), 0);
+    }
 }
@@ -10,21 +10,28 @@
 
 use std::cmp::max;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Instant, Duration};
 use std::collections::VecDeque;
 use std::convert::TryInto;
-use log::debug;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use log::{debug, info, warn};
 use chess::{Position, ValidMove, Move, MoveRc, Piece};
-use chess::game::{MoveSequence, TimeControl};
-use protocols::{Protocol, Action, SearchAction, Thinking};
+use chess::game::MoveSequence;
+use protocols::{Protocol, Action, SearchAction, SearchLimits, Thinking, Request};
+use protocols::options::{EngineOption, OptionValue};
 
 mod eval;
-use eval::{evaluate, piece_val};
+use eval::{evaluate, EvalWeights};
 use eval::Score;
 
 mod hash;
 use hash::{HashTable, HashEntry, Bound};
 
+mod time;
+use time::TimeManager;
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// The core of the Tinman chess engine.
 ///
@@ -45,9 +52,22 @@ use hash::{HashTable, HashEntry, Bound};
 pub struct Engine<T> where T: Protocol {
     protocol: T,
     hash: HashTable,
+    hash_file: PathBuf,
+    weights: EvalWeights,
 
     start_time: Instant,
-    stop_times: Option<(Instant, Instant)>,
+    time_manager: Option<TimeManager>,
+    move_overhead: Duration,
+    contempt: i16,
+    rating_contempt: bool,
+    draw_score: Score,
+    resign_score: i16,
+    resign_moves: u16,
+    losing_moves: u16,
+    drawn_moves: u16,
+    last_ply: usize,
+    limits: SearchLimits,
+    max_nodes: Option<u64>,
     pondering: bool,
     abort: bool,
     nodes: u64,
@@ -59,14 +79,42 @@ pub struct Engine<T> where T: Protocol {
 
 impl<T> Engine<T> where T: Protocol {
     const DEFAULT_HASH_SIZE: usize = 0x0000_1000_0000; // default to 256 MB hash
+    const MAX_HASH_SIZE: usize = HashTable::MAX_SIZE;
+    const DEFAULT_HASH_FILE: &'static str = "tinman.hash";
+    const MAX_CONTEMPT: i16 = 1000;
+    const DEFAULT_MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+    /// The rating difference worth one centipawn of contempt.
+    const RATING_PER_CONTEMPT: i64 = 10;
+    /// The most contempt that can be added or removed because of the ratings.
+    const MAX_RATING_CONTEMPT: i64 = 50;
+    const DEFAULT_RESIGN_SCORE: i16 = 1000;
+    const DEFAULT_RESIGN_MOVES: u16 = 5;
+    const MAX_RESIGN_MOVES: u16 = 100;
+    /// The number of consecutive moves scored close to a draw before the engine offers one.
+    const DRAW_OFFER_MOVES: u16 = 10;
+    /// The most a score may differ from a draw, in centipawns, for the position to be drawish.
+    const DRAW_OFFER_MARGIN: i16 = 10;
 
     /// Creates a new `Engine` which will use `protocol` to communicate with the client.
     pub fn new(protocol: T) -> Self {
         Engine {
             protocol,
             hash: HashTable::new(Self::DEFAULT_HASH_SIZE),
+            hash_file: Self::DEFAULT_HASH_FILE.into(),
+            weights: EvalWeights::default(),
             start_time: Instant::now(),
-            stop_times: None,
+            time_manager: None,
+            move_overhead: Self::DEFAULT_MOVE_OVERHEAD,
+            contempt: 0,
+            rating_contempt: false,
+            draw_score: Score::draw(),
+            resign_score: Self::DEFAULT_RESIGN_SCORE,
+            resign_moves: Self::DEFAULT_RESIGN_MOVES,
+            losing_moves: 0,
+            drawn_moves: 0,
+            last_ply: 0,
+            limits: SearchLimits::new(),
+            max_nodes: None,
             pondering: false,
             abort: false,
             nodes: 1,
@@ -76,6 +124,41 @@ impl<T> Engine<T> where T: Protocol {
         }
     }
 
+    /// Sets the amount of time to allow for communication delays when calculating search times.
+    pub fn set_move_overhead(&mut self, overhead: Duration) -> &mut Self {
+        self.move_overhead = overhead;
+        self
+    }
+
+    /// Sets the contempt in centipawns. A positive contempt makes the engine treat a draw as a
+    /// loss of that many centipawns, so that it avoids draws.
+    pub fn set_contempt(&mut self, contempt: i16) -> &mut Self {
+        self.contempt = contempt;
+        self
+    }
+
+    /// Sets whether the contempt is adjusted by the difference between the engine's rating and its
+    /// opponent's, when the protocol provides them. The engine is given more contempt against
+    /// weaker opponents and less against stronger ones.
+    pub fn set_rating_contempt(&mut self, enabled: bool) -> &mut Self {
+        self.rating_contempt = enabled;
+        self
+    }
+
+    /// Sets how many centipawns behind the engine must be before it resigns, or zero if it should
+    /// never resign.
+    pub fn set_resign_score(&mut self, centipawns: i16) -> &mut Self {
+        self.resign_score = centipawns;
+        self
+    }
+
+    /// Sets the number of consecutive moves for which the engine must be behind by the resign score
+    /// before it resigns.
+    pub fn set_resign_moves(&mut self, moves: u16) -> &mut Self {
+        self.resign_moves = moves;
+        self
+    }
+
     /// Tinman's main loop.
     ///
     /// Plays games of chess, using the `Protocol` passed to `new` to communicate moves with its
@@ -89,11 +172,18 @@ impl<T> Engine<T> where T: Protocol {
     /// Change `Protocol::ponder_move` to guarantee that the move is legal, so this panic is no
     /// longer necessary.
     pub fn run(mut self) {
+        let options = self.options();
+        self.protocol.declare_options(&options);
+
         loop {
             match self.protocol.wait_for_direction() {
                 Action::Search => {
                     self.start_time = Instant::now();
                     self.abort = false;
+                    self.nodes = 1;
+                    self.limits = self.protocol.search_limits();
+                    self.max_nodes = self.limits.nodes();
+                    self.hash.reset_stats();
 
                     self.history = self.protocol.game().history().clone();
 
@@ -103,22 +193,32 @@ impl<T> Engine<T> where T: Protocol {
                         debug!("pondering");
                     } else {
                         self.pondering = false;
-                        self.calc_search_time();
                     }
                     self.color = self.history.final_position().turn();
+                    self.draw_score = -Score::from(self.calc_contempt());
+                    if !self.pondering {
+                        self.calc_search_time();
+                    }
+
+                    let result = self.search_root();
 
-                    if let Some(thinking) = self.search_root() {
+                    let stats = format!("hash: {} hashfull {}",
+                        self.hash.stats(), self.hash.hashfull(self.search_count));
+                    debug!("{}", stats);
+                    self.protocol.send_debug_msg(&stats);
+
+                    if let Some(thinking) = result {
                         if self.pondering {
                             loop {
                                 match self.protocol.check_input() {
                                     Some(SearchAction::Abort) => { },
-                                    Some(_) => self.protocol.send_move(&thinking),
+                                    Some(_) => self.send_move(thinking),
                                     None => { continue },
                                 }
                                 break;
                             }
                         } else {
-                            self.protocol.send_move(&thinking);
+                            self.send_move(thinking);
                         }
                     }
                 },
@@ -128,6 +228,15 @@ impl<T> Engine<T> where T: Protocol {
                 Action::ClearHash => {
                     self.hash.clear();
                 },
+                Action::SaveHash(path) => {
+                    self.save_hash(&path);
+                },
+                Action::LoadHash(path) => {
+                    self.load_hash(&path);
+                },
+                Action::SetOption{ name, value } => {
+                    self.set_option(&name, value);
+                },
                 Action::Quit => {
                     return;
                 }
@@ -135,49 +244,231 @@ impl<T> Engine<T> where T: Protocol {
         }
     }
 
-    /// Calculate the amount of time that the engine should search.
-    fn calc_search_time(&mut self) {
-        use TimeControl::*;
-        let clock = self.protocol.game().clock();
+    /// Returns the options which the client can set, with their current values as defaults.
+    fn options(&self) -> Vec<EngineOption> {
+        const MB: usize = 0x10_0000;
+        let contempt = self.contempt.clamp(-Self::MAX_CONTEMPT, Self::MAX_CONTEMPT);
+        let resign_score = self.resign_score.clamp(0, Score::infinity().into());
+        let resign_moves = self.resign_moves.clamp(1, Self::MAX_RESIGN_MOVES);
 
-        match clock.time_control() {
-            Infinite => {
-                debug!("time remaining: infinite");
+        vec![
+            EngineOption::spin("Hash", (Self::DEFAULT_HASH_SIZE / MB) as i64,
+                1, (Self::MAX_HASH_SIZE / MB) as i64),
+            EngineOption::spin("Contempt", contempt.into(),
+                (-Self::MAX_CONTEMPT).into(), Self::MAX_CONTEMPT.into()),
+            EngineOption::check("Rating Contempt", self.rating_contempt),
+            EngineOption::spin("Resign Score", resign_score.into(),
+                0, i16::from(Score::infinity()).into()),
+            EngineOption::spin("Resign Moves", resign_moves.into(),
+                1, Self::MAX_RESIGN_MOVES.into()),
+            EngineOption::file("Eval File", PathBuf::new()),
+            EngineOption::file("Hash File", self.hash_file.clone()),
+            EngineOption::button("Save Hash"),
+            EngineOption::button("Load Hash"),
+        ]
+    }
 
-                self.stop_times = None;
-            },
-            Exact(time) => {
-                debug!("time remaining: {:?}", time);
+    /// Sets an option declared by `options`.
+    fn set_option(&mut self, name: &str, value: OptionValue) {
+        debug!("setting option {}: {:?}", name, value);
 
-                let stop_time = self.start_time + time;
-                self.stop_times = Some((stop_time, stop_time));
+        match (name, value) {
+            ("Hash", OptionValue::Spin(size)) => {
+                self.hash.resize(size as usize * 0x10_0000);
             },
-            Incremental{ inc, .. } => {
-                let time = clock.remaining(self.color);
-                debug!("time remaining: {:?}", time);
-                debug!("time increment: {:?}", inc);
-
-                let search_time = if time > inc * 6 {
-                    time/30 + inc
+            ("Contempt", OptionValue::Spin(contempt)) => {
+                self.contempt = contempt as i16;
+            },
+            ("Rating Contempt", OptionValue::Check(enabled)) => {
+                self.rating_contempt = enabled;
+            },
+            ("Resign Score", OptionValue::Spin(centipawns)) => {
+                self.resign_score = centipawns as i16;
+            },
+            ("Resign Moves", OptionValue::Spin(moves)) => {
+                self.resign_moves = moves as u16;
+            },
+            ("Eval File", OptionValue::File(path)) => {
+                if path.as_os_str().is_empty() {
+                    self.weights = EvalWeights::default();
                 } else {
-                    time/5
-                };
-                self.stop_times = Some((
-                    self.start_time + search_time,
-                    self.start_time + search_time * 2
-                ));
+                    let result = File::open(&path)
+                        .and_then(|file| EvalWeights::read(BufReader::new(file)));
+
+                    match result {
+                        Ok(weights) => {
+                            info!("loaded evaluation weights from {}", path.display());
+                            self.weights = weights;
+                        },
+                        Err(err) => self.report_error(&format!(
+                            "cannot load evaluation weights from {}: {}", path.display(), err)),
+                    }
+                }
+            },
+            ("Hash File", OptionValue::File(path)) => {
+                self.hash_file = path;
+            },
+            ("Save Hash", OptionValue::Button) => {
+                self.save_hash(&self.hash_file.clone());
             },
-            _ => {
-                let time = clock.remaining(self.color);
-                let search_time = time / 30;
-                debug!("time remaining: {:?}", time);
-
-                self.stop_times = Some((
-                    self.start_time + search_time,
-                    self.start_time + search_time * 2
-                ));
+            ("Load Hash", OptionValue::Button) => {
+                self.load_hash(&self.hash_file.clone());
             },
+            (name, value) => warn!("unexpected value for option {}: {:?}", name, value),
+        }
+    }
 
+    /// Sends the move found by the search, along with any request to resign, or to offer, accept or
+    /// claim a draw.
+    fn send_move(&mut self, mut thinking: Thinking) {
+        let request = self.request(&thinking);
+        thinking.set_request(request);
+        self.protocol.send_move(&thinking);
+    }
+
+    /// Decides what to request along with the move in `thinking`. The engine resigns after being
+    /// lost for `resign_moves` moves in a row. Otherwise, if a draw is no worse than its score, it
+    /// claims a draw if it can or accepts an offered draw. It offers a draw itself once its move
+    /// leaves insufficient material for checkmate, or after the position has been scored close to a
+    /// draw for a while.
+    fn request(&mut self, thinking: &Thinking) -> Option<Request> {
+        let score = Score::from(thinking.score());
+        let game = self.protocol.game();
+
+        // a game which is shorter than last time is a new one, or has been taken back
+        let ply = game.history().len();
+        if ply <= self.last_ply {
+            self.losing_moves = 0;
+            self.drawn_moves = 0;
+        }
+        self.last_ply = ply;
+
+        if self.resign_score > 0 && score <= -Score::from(self.resign_score) {
+            self.losing_moves += 1;
+        } else {
+            self.losing_moves = 0;
+        }
+        if score >= self.draw_score - Self::DRAW_OFFER_MARGIN
+            && score <= self.draw_score + Self::DRAW_OFFER_MARGIN {
+            self.drawn_moves += 1;
+        } else {
+            self.drawn_moves = 0;
+        }
+
+        // the game after the move, if it's legal
+        let history = thinking.best_move().and_then(|mv| {
+            let mut history = game.history().clone();
+            history.push(mv.clone()).ok()?;
+            Some(history)
+        });
+
+        if self.losing_moves > 0 && self.losing_moves >= self.resign_moves {
+            debug!("resigning with a score of {:?}", score);
+            Some(Request::Resign)
+        } else if score <= self.draw_score && (game.claimable_draw().is_some()
+            || history.as_ref().is_some_and(|history| {
+                history.three_fold_repetition() || history.final_position().fifty_moves()
+            })) {
+            Some(Request::ClaimDraw)
+        } else if score <= self.draw_score && self.protocol.draw_offered() {
+            Some(Request::OfferDraw)
+        } else if history.as_ref().is_some_and(|history| {
+            history.final_position().insufficient_material()
+        }) || self.drawn_moves >= Self::DRAW_OFFER_MOVES {
+            self.drawn_moves = 0;
+            Some(Request::OfferDraw)
+        } else {
+            None
+        }
+    }
+
+    /// Saves the transposition table to `path`.
+    fn save_hash(&mut self, path: &Path) {
+        let result = File::create(path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            self.hash.save(&mut writer)?;
+            writer.flush()
+        });
+
+        match result {
+            Ok(()) => info!("saved hash table to {}", path.display()),
+            Err(err) => self.report_error(&format!(
+                "cannot save hash table to {}: {}", path.display(), err)),
+        }
+    }
+
+    /// Replaces the transposition table with one loaded from `path`.
+    fn load_hash(&mut self, path: &Path) {
+        let result = File::open(path).and_then(|file| {
+            HashTable::load(&mut BufReader::new(file))
+        });
+
+        match result {
+            Ok(hash) => {
+                info!("loaded hash table from {}", path.display());
+                self.hash = hash;
+            },
+            Err(err) => self.report_error(&format!(
+                "cannot load hash table from {}: {}", path.display(), err)),
+        }
+    }
+
+    /// Logs an error which doesn't prevent the engine from continuing, and reports it to the
+    /// client.
+    fn report_error(&mut self, msg: &str) {
+        warn!("{}", msg);
+        self.protocol.send_debug_msg(msg);
+    }
+
+    /// Calculate the amount of time that the engine should search.
+    fn calc_search_time(&mut self) {
+        self.time_manager = if let Some(time) = self.limits.move_time() {
+            Some(TimeManager::fixed(time, self.move_overhead))
+        } else {
+            let clock = self.protocol.game().clock();
+            debug!("time control: {:?}", clock.time_control());
+            debug!("time remaining: {:?}", clock.remaining(self.color));
+
+            TimeManager::new(clock, self.color, self.history.final_position().move_number(),
+                self.move_overhead)
+        };
+
+        if let Some(time_manager) = &self.time_manager {
+            debug!("soft limit: {:?}", time_manager.soft_limit());
+            debug!("hard limit: {:?}", time_manager.hard_limit());
+        }
+    }
+
+    /// Returns the time elapsed since the start of the search. If a node rate is set, the time is
+    /// calculated from the number of nodes searched rather than measured by the clock.
+    fn elapsed(&self) -> Duration {
+        match self.limits.nps() {
+            Some(nps) if nps > 0 => Duration::from_millis(self.nodes.saturating_mul(1000) / nps),
+            _ => self.start_time.elapsed(),
+        }
+    }
+
+    /// Calculates the contempt for the next search.
+    fn calc_contempt(&self) -> i16 {
+        let mut contempt = i64::from(self.contempt);
+        if self.rating_contempt {
+            if let Some((engine, opponent)) = self.protocol.ratings() {
+                let max = Self::MAX_RATING_CONTEMPT;
+                contempt += ((i64::from(engine) - i64::from(opponent)) / Self::RATING_PER_CONTEMPT)
+                    .clamp(-max, max);
+            }
+        }
+        debug!("contempt: {}", contempt);
+
+        contempt.try_into().unwrap_or(self.contempt)
+    }
+
+    /// Returns the score of a draw in `pos`, from the perspective of the side to move.
+    fn draw_score(&self, pos: &Position) -> Score {
+        if pos.turn() == self.color {
+            self.draw_score
+        } else {
+            -self.draw_score
         }
     }
 
@@ -187,16 +478,35 @@ impl<T> Engine<T> where T: Protocol {
         thinking.set_nodes(1);
         let mut move_list: VecDeque<MoveSequence> = VecDeque::new();
         self.search_count += 1;
-        self.nodes = 1;
 
         // make and store all legal moves
         debug!("searching: {}", self.history.final_position());
+        let search_moves = if self.pondering { None } else { self.protocol.search_moves() };
+        let mut excluded: VecDeque<MoveSequence> = VecDeque::new();
         for mv in self.history.final_position().moves() {
+            let is_search_move = search_moves.is_none_or(|search_moves| {
+                search_moves.iter().any(|m| {
+                    m.piece() == mv.piece()
+                    && m.origin() == mv.origin()
+                    && m.destination() == mv.destination()
+                    && m.promotion() == mv.promotion()
+                })
+            });
+
             if let Ok(seq) = std::iter::once(mv.into()).collect() {
-                move_list.push_back(seq);
+                if is_search_move {
+                    move_list.push_back(seq);
+                } else {
+                    excluded.push_back(seq);
+                }
             }
         }
 
+        // if every legal move has been excluded, search them all rather than never moving
+        if move_list.is_empty() {
+            move_list = excluded;
+        }
+
         // if no legal moves
         if move_list.is_empty() {
             // TODO: set the score depending on if it's stalemate or checkmate
@@ -206,7 +516,7 @@ impl<T> Engine<T> where T: Protocol {
         // iterative deepening
         let mut best_move = 0;
         let max_depth = if move_list.len() > 1 {
-            if let Some(depth) = self.protocol.max_depth() {
+            if let Some(depth) = self.limits.depth() {
                 depth.try_into().unwrap_or(u8::max_value())
             } else {
                 u8::max_value()
@@ -216,6 +526,9 @@ impl<T> Engine<T> where T: Protocol {
         };
         for depth in 1 ..= max_depth {
             let mut best_val = -Score::infinity();
+            let iteration_start = self.elapsed();
+            let iteration_nodes = self.nodes;
+            let mut best_move_nodes = 0;
 
             if best_move > 0 {
                 // put previous best move at the front
@@ -225,6 +538,7 @@ impl<T> Engine<T> where T: Protocol {
 
             // search each move
             for (n, seq) in move_list.iter().enumerate() {
+                let move_nodes = self.nodes;
                 self.history.append(&mut seq.clone()).expect("INFALLIBLE");
                 let search_result = if best_val == -Score::infinity() {
                     self.search(1, depth-1, -Score::infinity(), -best_val, false)
@@ -244,6 +558,7 @@ impl<T> Engine<T> where T: Protocol {
                     if val > best_val {
                         best_val = val;
                         best_move = n;
+                        best_move_nodes = self.nodes - move_nodes;
                         let mut pv = seq.clone();
                         if let Some(mut child_pv) = child_pv {
                             pv.append(&mut child_pv).expect("INFALLIBLE");
@@ -257,6 +572,7 @@ impl<T> Engine<T> where T: Protocol {
                     thinking.set_depth(depth);
                     thinking.set_time(self.start_time.elapsed());
                     thinking.set_nodes(self.nodes);
+                    thinking.set_hashfull(self.hash.hashfull(self.search_count));
                     return Some(thinking);
                 }
             }
@@ -276,11 +592,31 @@ impl<T> Engine<T> where T: Protocol {
             thinking.set_depth(depth);
             thinking.set_time(self.start_time.elapsed());
             thinking.set_nodes(self.nodes);
+            thinking.set_hashfull(self.hash.hashfull(self.search_count));
             self.protocol.send_thinking(&thinking);
+
+            // stop once a short enough mate has been found
+            if let Some(moves) = self.limits.mate() {
+                if Score::from(thinking.score()) >= Score::mates_in((moves * 2).saturating_sub(1)) {
+                    break;
+                }
+            }
+
+            if !self.pondering {
+                let elapsed = self.elapsed();
+                if let Some(time_manager) = &mut self.time_manager {
+                    time_manager.record_iteration(depth > 1 && best_move != 0, best_val,
+                        best_move_nodes, self.nodes - iteration_nodes);
+                    if !time_manager.start_iteration(elapsed, elapsed - iteration_start) {
+                        break;
+                    }
+                }
+            }
         }
 
         thinking.set_time(self.start_time.elapsed());
         thinking.set_nodes(self.nodes);
+        thinking.set_hashfull(self.hash.hashfull(self.search_count));
 
         Some(thinking)
     }
@@ -295,13 +631,18 @@ impl<T> Engine<T> where T: Protocol {
     -> Option<(Score, Option<MoveSequence>)> {
         let pos = Rc::clone(self.history.final_position());
         let mut pv = None;
+        self.hash.prefetch(pos.zobrist_key());
 
         if self.time_to_stop() {
             return None;
         }
 
+        if let Some(score) = variant_win_score(&pos, ply) {
+            return Some((score, pv));
+        }
+
         if pos.fifty_moves() || self.history.repetition() {
-            return Some((Score::draw(), pv));
+            return Some((self.draw_score(&pos), pv));
         }
 
         // check extension
@@ -315,27 +656,37 @@ impl<T> Engine<T> where T: Protocol {
             if hash.depth() >= depth {
                 if (hash.score() >= beta && hash.bound() != Bound::Upper)
                 || (hash.score() <= alpha && hash.bound() != Bound::Lower) {
+                    self.hash.record_cutoff();
                     return Some((hash.score(), pv));
                 } else if hash.bound() == Bound::Exact && ply > 1 {
                     // alpha < score < beta due to previous conditions
                     if let Some(mv) = hash.best_move() {
                         if let Ok(mv) = mv.validate(&pos) {
                             pv = Some(MoveRc::from(mv).try_into().expect("INFALLIBLE"));
+                        } else {
+                            self.hash.record_collision();
                         }
                     }
 
+                    self.hash.record_cutoff();
                     return Some((hash.score(), pv));
                 }
             }
 
-            hash_move = hash.best_move().map(|mv| mv.validate(&pos).ok()).flatten();
+            hash_move = hash.best_move().and_then(|mv| {
+                let mv = mv.validate(&pos).ok();
+                if mv.is_none() {
+                    self.hash.record_collision();
+                }
+                mv
+            });
         } else {
             hash_move = None;
         }
 
         // leaf node
         if depth == 0 {
-            if let Some(score) = self.qsearch(&pos, alpha, beta) {
+            if let Some(score) = self.qsearch(&pos, ply, alpha, beta) {
                 return Some((score, pv));
             } else {
                 return None;
@@ -344,7 +695,8 @@ impl<T> Engine<T> where T: Protocol {
 
         // null move
         if null_move_allowed && !pos.in_check() && alpha + 1 == beta
-        && (depth < 4 || evaluate(&pos) >= beta)
+        && !pos.variant().has_forced_captures()
+        && (depth < 4 || evaluate(&pos, &self.weights) >= beta)
         && !(pos.occupied_by(pos.turn()) & !pos.occupied_by_piece(pos.turn(), Piece::Pawn)
         & !pos.occupied_by_piece(pos.turn(), Piece::King)).is_empty() {
             let mv = Move::null_move(&pos);
@@ -403,11 +755,7 @@ impl<T> Engine<T> where T: Protocol {
         let hash_entry;
         if best_val == -Score::infinity() {
             // no moves found
-            if pos.in_check() {
-                best_val = Score::mated_in(ply);
-            } else {
-                best_val = Score::draw();
-            }
+            best_val = self.no_moves_score(&pos, ply);
             hash_entry = HashEntry::new_without_move(
                 pos.zobrist_key(),
                 self.search_count, depth,
@@ -435,14 +783,23 @@ impl<T> Engine<T> where T: Protocol {
     /// Search all material-gaining moves from the current search position looking for a maximum
     /// score of `beta` and a minumum score of `alpha`. Returns the estimated score for the
     /// either the best move searched or the current search position, whichever is better.
-    fn qsearch(&mut self, pos: &Position, mut alpha: Score, beta: Score) -> Option<Score> {
-        let eval = evaluate(pos);
+    fn qsearch(&mut self, pos: &Position, ply: usize, mut alpha: Score, beta: Score)
+    -> Option<Score> {
+        let eval = evaluate(pos, &self.weights);
         let mut best_val = eval;
 
         if self.time_to_stop() {
             return None;
         }
 
+        if let Some(score) = variant_win_score(pos, ply) {
+            return Some(score);
+        }
+
+        if pos.variant().has_forced_captures() {
+            return self.qsearch_forced_captures(pos, ply, alpha, beta, eval);
+        }
+
         if eval >= beta {
             return Some(eval);
         }
@@ -450,21 +807,22 @@ impl<T> Engine<T> where T: Protocol {
         alpha = max(alpha, best_val);
 
         // check if its even possible to improve on alpha
-        if eval + 2*piece_val(Piece::Queen) <= alpha {
-            return Some(eval + 2*piece_val(Piece::Queen));
+        if eval + 2*self.weights.piece_val(Piece::Queen) <= alpha {
+            return Some(eval + 2*self.weights.piece_val(Piece::Queen));
         }
 
         for mv in pos.promotions_and_captures() {
             // check if it's no longer possible to raise alpha
             if !mv.is_promotion() {
-                let max_val = eval + piece_val(mv.captured_piece().expect("INFALLIBLE"));
+                let captured = mv.captured_piece().expect("INFALLIBLE");
+                let max_val = eval + self.weights.piece_val(captured);
                 if max_val < alpha {
                     return Some(max(best_val, max_val));
                 }
             }
 
             if let Ok(new_pos) = mv.make() {
-                if let Some(val) = self.qsearch(&new_pos, -beta, -alpha) {
+                if let Some(val) = self.qsearch(&new_pos, ply+1, -beta, -alpha) {
                     let val = -val;
 
                     if val >= beta {
@@ -482,10 +840,43 @@ impl<T> Engine<T> where T: Protocol {
         Some(best_val)
     }
 
+    /// Searches the captures from a position in the losing variants, where captures are
+    /// compulsory. Only captures which are the sole move are followed, since the choices of
+    /// capture otherwise multiply too quickly. `eval` is the static evaluation of the position.
+    fn qsearch_forced_captures(&mut self, pos: &Position, ply: usize, alpha: Score, beta: Score,
+        eval: Score) -> Option<Score> {
+        let mut moves = pos.moves();
+        match (moves.next(), moves.next()) {
+            (None, _) => Some(self.no_moves_score(pos, ply)),
+            (Some(mv), None) if mv.is_capture() => match mv.make() {
+                Ok(new_pos) => Some(-self.qsearch(&new_pos, ply+1, -beta, -alpha)?),
+                Err(_) => Some(eval),
+            },
+            _ => Some(eval),
+        }
+    }
+
+    /// Returns the score of `pos`, where the player to move has no legal moves, `ply` plies from
+    /// the root of the search.
+    fn no_moves_score(&self, pos: &Position, ply: usize) -> Score {
+        match pos.winner_without_moves() {
+            Some(winner) if winner == pos.turn() => Score::mates_in(ply),
+            Some(_) => Score::mated_in(ply),
+            None => self.draw_score(pos),
+        }
+    }
+
     /// Check if it's time to stop. Should be called exactly once per node.
     fn time_to_stop(&mut self) -> bool {
         self.nodes += 1;
 
+        if !self.pondering {
+            match self.max_nodes {
+                Some(max_nodes) if self.nodes >= max_nodes => return true,
+                _ => { },
+            }
+        }
+
         if self.nodes%1000 == 0 {
             match self.protocol.check_input() {
                 Some(SearchAction::PonderHit) => {
@@ -507,8 +898,8 @@ impl<T> Engine<T> where T: Protocol {
             }
 
             if !self.pondering {
-                match self.stop_times {
-                    Some((stop_time, _)) if Instant::now() >= stop_time => return true,
+                match &self.time_manager {
+                    Some(time_manager) if time_manager.out_of_time(self.elapsed()) => return true,
                     _ => return false,
                 }
             }
@@ -518,7 +909,160 @@ impl<T> Engine<T> where T: Protocol {
     }
 }
 
+/// Returns the score of `pos` if the game has already been won by a rule of its variant, such as
+/// giving a third check in Three-check, `ply` plies from the root of the search.
+fn variant_win_score(pos: &Position, ply: usize) -> Option<Score> {
+    pos.variant_winner().map(|winner| {
+        if winner == pos.turn() {
+            Score::mates_in(ply)
+        } else {
+            Score::mated_in(ply)
+        }
+    })
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// An engine error
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 struct Error;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use chess::game::Game;
+
+    /// A protocol which makes one search of `game`, restricted to `search_moves`, and records the
+    /// thinking sent with the move.
+    struct SearchMoves {
+        game: Game,
+        search_moves: Option<Vec<MoveRc>>,
+        searched: bool,
+        thinking: Rc<RefCell<Option<Thinking>>>,
+    }
+
+    impl Protocol for SearchMoves {
+        fn wait_for_direction(&mut self) -> Action {
+            if self.searched {
+                Action::Quit
+            } else {
+                self.searched = true;
+                Action::Search
+            }
+        }
+
+        fn send_move(&mut self, thinking: &Thinking) {
+            *self.thinking.borrow_mut() = Some(thinking.clone());
+        }
+
+        fn send_thinking(&mut self, _thinking: &Thinking) { }
+
+        fn send_debug_msg(&mut self, _msg: &str) { }
+
+        fn check_input(&mut self) -> Option<SearchAction> {
+            None
+        }
+
+        fn game(&self) -> &Game {
+            &self.game
+        }
+
+        fn ponder_move(&self) -> Option<&MoveRc> {
+            None
+        }
+
+        fn search_limits(&self) -> SearchLimits {
+            let mut limits = SearchLimits::new();
+            limits.set_depth(Some(3));
+
+            limits
+        }
+
+        fn search_moves(&self) -> Option<&[MoveRc]> {
+            self.search_moves.as_deref()
+        }
+
+        fn ratings(&self) -> Option<(u32, u32)> {
+            None
+        }
+
+        fn draw_offered(&self) -> bool {
+            false
+        }
+
+        fn declare_options(&mut self, _options: &[EngineOption]) { }
+    }
+
+    /// Searches the position given by `fen`, restricted to `search_moves`, and returns the thinking
+    /// sent with the move along with the search moves.
+    fn search(fen: &str, search_moves: Option<&[&str]>) -> (Thinking, Option<Vec<MoveRc>>) {
+        let pos: Position = fen.parse().expect(fen);
+        let search_moves = search_moves.map(|moves| {
+            moves.iter()
+                .map(|mv| MoveRc::from(mv.parse::<chess::MoveBuilder>().expect(mv)
+                    .validate(&pos).expect(mv)))
+                .collect::<Vec<_>>()
+        });
+        let thinking = Rc::new(RefCell::new(None));
+
+        let mut engine = Engine::new(SearchMoves {
+            game: Game::starting_at(pos),
+            search_moves: search_moves.clone(),
+            searched: false,
+            thinking: thinking.clone(),
+        });
+        engine.hash.resize(0x10_0000);
+        engine.run();
+
+        let thinking = thinking.borrow().clone().expect("a move");
+        (thinking, search_moves)
+    }
+
+    #[test]
+    fn search_moves() {
+        // the queen can be taken, but only quiet moves may be searched
+        let fen = "4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1";
+        let (thinking, search_moves) = search(fen, Some(&["e1f1", "d2a2", "d2h2"]));
+        let best_move = thinking.best_move().expect("a move");
+        assert!(search_moves.expect("search moves").contains(best_move), "{}", best_move);
+
+        let (thinking, _) = search(fen, None);
+        assert_eq!(thinking.best_move().expect("a move").to_string(), "Rxd5");
+        assert_eq!(thinking.request(), None);
+    }
+
+    #[test]
+    fn all_moves_excluded() {
+        let (thinking, _) = search("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", Some(&[]));
+        assert_eq!(thinking.best_move().expect("a move").to_string(), "Rxd5");
+    }
+
+    #[test]
+    fn draw_offers() {
+        // taking the last black piece leaves bare kings
+        let (thinking, _) = search("4k3/8/8/8/8/8/3n4/4K3 w - - 0 1", None);
+        assert_eq!(thinking.best_move().expect("a move").to_string(), "Kxd2");
+        assert_eq!(thinking.request(), Some(Request::OfferDraw));
+
+        // a score close to a draw for long enough leads to an offer
+        let mut engine = Engine::new(SearchMoves {
+            game: Game::new(),
+            search_moves: None,
+            searched: false,
+            thinking: Rc::new(RefCell::new(None)),
+        });
+        let mut thinking = Thinking::new();
+        thinking.set_pv(MoveSequence::new(), protocols::Score::Val(5));
+        let moves = ["a3", "a6", "b3", "b6", "c3", "c6", "d3", "d6", "e3", "e6", "f3"];
+        for (i, mv) in moves.iter().enumerate() {
+            engine.protocol.game.make_move_from_str(mv).expect(mv);
+            if i + 1 == Engine::<SearchMoves>::DRAW_OFFER_MOVES.into() {
+                assert_eq!(engine.request(&thinking), Some(Request::OfferDraw));
+                thinking.set_pv(MoveSequence::new(), protocols::Score::Val(50));
+            } else {
+                assert_eq!(engine.request(&thinking), None, "{}", mv);
+            }
+        }
+    }
+}
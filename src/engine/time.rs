@@ -0,0 +1,307 @@
+//! Decides how long the engine should search, based on the clock and the stability of the search.
+//!
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::cmp::{min, max};
+use std::time::Duration;
+use chess::Color;
+use chess::game::{Clock, TimeControl};
+use super::eval::Score;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Allocates time for a single search.
+///
+/// The time manager has two limits. The soft limit is the amount of time the search is expected to
+/// take, and is only checked between iterations. It is scaled up when the search is unstable, and
+/// down when one move clearly dominates. The hard limit is never exceeded, and is checked
+/// throughout the search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeManager {
+    soft: Duration,
+    hard: Duration,
+    adjustable: bool,
+    instability: f64,
+    score_drop: i16,
+    stable_iterations: usize,
+    dominant: bool,
+    prev_score: Option<Score>,
+}
+
+impl TimeManager {
+    /// Number of moves assumed to remain in the game when the time control doesn't say.
+    const DEFAULT_MOVES_TO_GO: u32 = 30;
+    /// Maximum ratio between the hard limit and the soft limit.
+    const MAX_HARD_RATIO: u32 = 4;
+    /// Expected ratio between the time taken by an iteration and the one before it.
+    const BRANCHING_FACTOR: f64 = 4.0;
+    /// Score drop (in centipawns) at which the maximum extension is given.
+    const MAX_SCORE_DROP: i16 = 150;
+    /// Fraction of an iteration's nodes spent on the best move for it to be considered dominant.
+    const DOMINANT_FRACTION: f64 = 0.9;
+    /// Number of iterations a dominant move must remain best before the search is cut short.
+    const DOMINANT_ITERATIONS: usize = 4;
+
+    /// Creates a `TimeManager` for the player `color`, whose next move is number `move_num`, based
+    /// on the `clock`. `overhead` is subtracted from the remaining time to allow for communication
+    /// delays. Returns `None` if the time control is `Infinite`.
+    pub fn new(clock: &Clock, color: Color, move_num: usize, overhead: Duration) -> Option<Self> {
+        use TimeControl::*;
+
+        let available = clock.remaining(color).checked_sub(overhead).unwrap_or_default();
+        let (moves_to_go, inc) = match clock.time_control() {
+            Infinite => return None,
+            Exact(time) => return Some(Self::fixed(time, overhead)),
+            SuddenDeath(_) => (Self::DEFAULT_MOVES_TO_GO, Duration::default()),
+            Incremental{ inc, .. } => (Self::DEFAULT_MOVES_TO_GO, inc),
+            Session{ mps, .. } => {
+                let mps = max(mps, 1);
+                let played = move_num.saturating_sub(1) % mps;
+                ((mps - played) as u32, Duration::default())
+            },
+            // time that doesn't come off the clock can be planned for like an increment
+            Delay{ delay, .. } | Bronstein{ delay, .. } => (Self::DEFAULT_MOVES_TO_GO, delay),
+            Stages(_) => match clock.stage(move_num) {
+                Some((stage, Some(moves_left))) => (moves_left as u32, stage.inc),
+                Some((stage, None)) => (Self::DEFAULT_MOVES_TO_GO, stage.inc),
+                None => return None,
+            },
+        };
+
+        // keep a reserve if more moves must be made before the next time control
+        let reserve = if moves_to_go > 1 { available / (moves_to_go * 2) } else { available / 20 };
+        let usable = available - reserve;
+
+        let soft = min(available / moves_to_go + inc, usable / 2);
+        let hard = min(soft * Self::MAX_HARD_RATIO, max(usable / 2, soft));
+        let soft = if moves_to_go == 1 { usable } else { soft };
+        let hard = if moves_to_go == 1 { usable } else { hard };
+
+        Some(TimeManager {
+            adjustable: true,
+            ..Self::with_limits(soft, hard)
+        })
+    }
+
+    /// Creates a `TimeManager` which searches for exactly `time` (less `overhead`) unless the next
+    /// iteration is not expected to finish in time.
+    pub fn fixed(time: Duration, overhead: Duration) -> Self {
+        let time = time.checked_sub(overhead).unwrap_or_default();
+
+        Self::with_limits(time, time)
+    }
+
+    fn with_limits(soft: Duration, hard: Duration) -> Self {
+        TimeManager {
+            soft,
+            hard,
+            adjustable: false,
+            instability: 0.0,
+            score_drop: 0,
+            stable_iterations: 0,
+            dominant: false,
+            prev_score: None,
+        }
+    }
+
+    /// Returns the soft limit, before being adjusted for the stability of the search.
+    pub fn soft_limit(&self) -> Duration {
+        self.soft
+    }
+
+    /// Returns the hard limit, which the search must not exceed.
+    pub fn hard_limit(&self) -> Duration {
+        self.hard
+    }
+
+    /// Returns the soft limit, adjusted for the stability of the search.
+    pub fn target(&self) -> Duration {
+        if !self.adjustable {
+            return self.soft;
+        }
+
+        let mut scale = 1.0 + self.instability * 0.5;
+        scale *= 1.0 + f64::from(self.score_drop) / f64::from(Self::MAX_SCORE_DROP) * 0.5;
+        if self.dominant {
+            scale *= 0.5;
+        }
+
+        min(self.soft.mul_f64(scale), self.hard)
+    }
+
+    /// Records the results of a completed iteration. `best_move_changed` indicates whether the
+    /// best move differs from the previous iteration, and `best_move_nodes` is the number of the
+    /// iteration's `total_nodes` which were spent searching the best move.
+    pub fn record_iteration(&mut self, best_move_changed: bool, score: Score,
+        best_move_nodes: u64, total_nodes: u64)
+    {
+        self.instability *= 0.5;
+        if best_move_changed {
+            self.instability += 1.0;
+            self.stable_iterations = 0;
+        } else {
+            self.stable_iterations += 1;
+        }
+
+        if let Some(prev_score) = self.prev_score {
+            let drop = i32::from(i16::from(prev_score)) - i32::from(i16::from(score));
+            self.score_drop = drop.max(0).min(i32::from(Self::MAX_SCORE_DROP)) as i16;
+        }
+        self.prev_score = Some(score);
+
+        self.dominant = self.stable_iterations >= Self::DOMINANT_ITERATIONS
+            && total_nodes > 0
+            && best_move_nodes as f64 >= total_nodes as f64 * Self::DOMINANT_FRACTION;
+    }
+
+    /// Returns true if there is time to start another iteration, given that `elapsed` time has
+    /// been spent so far and the last iteration took `last_iteration`.
+    pub fn start_iteration(&self, elapsed: Duration, last_iteration: Duration) -> bool {
+        elapsed < self.target()
+            && elapsed + last_iteration.mul_f64(Self::BRANCHING_FACTOR) < self.hard
+    }
+
+    /// Returns true if the hard limit has been reached.
+    pub fn out_of_time(&self, elapsed: Duration) -> bool {
+        elapsed >= self.hard
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chess::game::TimeStage;
+
+    fn clock(tc: TimeControl, remaining: Duration) -> Clock {
+        let mut clock = Clock::new(tc);
+        clock.set(Color::White, remaining);
+        clock
+    }
+
+    #[test]
+    fn infinite() {
+        let clock = Clock::new(TimeControl::Infinite);
+        assert_eq!(TimeManager::new(&clock, Color::White, 1, Duration::default()), None);
+    }
+
+    #[test]
+    fn exact() {
+        let clock = Clock::new(TimeControl::Exact(Duration::from_secs(5)));
+        let tm = TimeManager::new(&clock, Color::White, 20, Duration::from_millis(100))
+            .expect("expected time manager");
+
+        assert_eq!(tm.soft_limit(), Duration::from_millis(4900));
+        assert_eq!(tm.hard_limit(), Duration::from_millis(4900));
+    }
+
+    #[test]
+    fn limits_within_remaining_time() {
+        let base = Duration::from_secs(60);
+        let controls = [
+            TimeControl::SuddenDeath(base),
+            TimeControl::Incremental{ base, inc: Duration::from_secs(1) },
+            TimeControl::Incremental{ base, inc: Duration::from_secs(10) },
+            TimeControl::Session{ base, mps: 40 },
+            TimeControl::Session{ base, mps: 1 },
+            TimeControl::Delay{ base, delay: Duration::from_secs(5) },
+            TimeControl::Bronstein{ base, delay: Duration::from_secs(5) },
+            TimeControl::Stages(vec![
+                TimeStage::new(Some(40), base, Duration::from_secs(1)),
+                TimeStage::new(None, base, Duration::from_secs(1)),
+            ]),
+        ];
+        let overhead = Duration::from_millis(50);
+
+        for tc in controls.iter() {
+            for &remaining in [60_000, 5_000, 500, 10, 0].iter() {
+                let remaining = Duration::from_millis(remaining);
+                for &move_num in [1, 39, 40, 41].iter() {
+                    let tm = TimeManager::new(&clock(tc.clone(), remaining), Color::White,
+                        move_num, overhead)
+                        .expect("expected time manager");
+                    assert!(tm.soft_limit() <= tm.hard_limit(), "{:?}", tm);
+                    assert!(tm.hard_limit() + overhead <= max(remaining, overhead), "{:?}", tm);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn session_moves_to_go() {
+        let base = Duration::from_secs(300);
+        let clock = clock(TimeControl::Session{ base, mps: 40 }, Duration::from_secs(30));
+        let overhead = Duration::default();
+
+        let first = TimeManager::new(&clock, Color::White, 1, overhead).expect("INFALLIBLE");
+        let middle = TimeManager::new(&clock, Color::White, 31, overhead).expect("INFALLIBLE");
+        let last = TimeManager::new(&clock, Color::White, 40, overhead).expect("INFALLIBLE");
+        let next = TimeManager::new(&clock, Color::White, 41, overhead).expect("INFALLIBLE");
+
+        // with fewer moves to go, more time should be allocated per move
+        assert!(first.soft_limit() < middle.soft_limit());
+        assert!(middle.soft_limit() < last.soft_limit());
+        assert_eq!(first, next);
+        // on the last move before the time control, nearly all the time can be used
+        assert!(last.hard_limit() >= Duration::from_secs(28));
+    }
+
+    #[test]
+    fn increment_adds_time() {
+        let base = Duration::from_secs(60);
+        let remaining = Duration::from_secs(60);
+        let overhead = Duration::default();
+        let sudden_death = clock(TimeControl::SuddenDeath(base), remaining);
+        let incremental = clock(
+            TimeControl::Incremental{ base, inc: Duration::from_secs(1) }, remaining);
+
+        let sudden_death = TimeManager::new(&sudden_death, Color::White, 1, overhead)
+            .expect("INFALLIBLE");
+        let incremental = TimeManager::new(&incremental, Color::White, 1, overhead)
+            .expect("INFALLIBLE");
+
+        assert_eq!(sudden_death.soft_limit(), Duration::from_secs(2));
+        assert_eq!(incremental.soft_limit(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn stability() {
+        let clock = clock(TimeControl::SuddenDeath(Duration::from_secs(60)),
+            Duration::from_secs(60));
+        let mut tm = TimeManager::new(&clock, Color::White, 1, Duration::default())
+            .expect("INFALLIBLE");
+        let soft = tm.soft_limit();
+
+        tm.record_iteration(true, Score::from(20), 100, 1000);
+        assert!(tm.target() > soft);
+
+        // score drops
+        tm.record_iteration(false, Score::from(-100), 100, 1000);
+        let dropped = tm.target();
+        assert!(dropped > soft);
+        tm.record_iteration(false, Score::from(-100), 100, 1000);
+        assert!(tm.target() < dropped);
+
+        // a single move dominates the search
+        for _ in 0 .. TimeManager::DOMINANT_ITERATIONS {
+            tm.record_iteration(false, Score::from(-100), 950, 1000);
+        }
+        assert!(tm.target() < soft);
+        assert!(tm.target() <= tm.hard_limit());
+    }
+
+    #[test]
+    fn unfinishable_iteration() {
+        let tm = TimeManager::fixed(Duration::from_secs(10), Duration::default());
+
+        assert!(tm.start_iteration(Duration::from_secs(2), Duration::from_secs(1)));
+        assert!(!tm.start_iteration(Duration::from_secs(6), Duration::from_secs(3)));
+        assert!(!tm.start_iteration(Duration::from_secs(10), Duration::from_millis(1)));
+        assert!(tm.out_of_time(Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,202 @@
+//! Implements a runner for test suites in Extended Position Description (EPD) format.
+//!
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::time::Duration;
+use chess::{MoveRc, ValidMove};
+use chess::epd::Epd;
+use chess::game::Game;
+use protocols::options::EngineOption;
+use protocols::{Protocol, Action, SearchAction, SearchLimits, Thinking, Score};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A `Protocol` which directs the engine to search each position from an EPD test suite, with the
+/// hash table cleared before each search.
+///
+/// The result of each search is printed as it completes, followed by a summary once all positions
+/// have been searched. A position's time to solution is the time at which the engine first chose a
+/// correct move, without changing to an incorrect move afterwards.
+///
+/// # Example
+/// ```no_run
+/// use tinman::Engine;
+/// use tinman::epd::EpdSuite;
+/// use protocols::SearchLimits;
+///
+/// let positions = vec!["6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - bm Rd8#; id \"back rank\";".parse()?];
+/// let mut limits = SearchLimits::new();
+/// limits.set_depth(Some(4));
+///
+/// Engine::new(EpdSuite::new(positions, limits)).run();
+/// # Ok::<(), chess::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct EpdSuite {
+    positions: Vec<Epd>,
+    limits: SearchLimits,
+    game: Game,
+    next: usize,
+    hash_cleared: bool,
+    solved_at: Option<Duration>,
+    solved: usize,
+    solution_time: Duration,
+}
+
+impl EpdSuite {
+    /// Creates an `EpdSuite` which searches each of `positions` using `limits`.
+    pub fn new(positions: Vec<Epd>, limits: SearchLimits) -> Self {
+        EpdSuite {
+            positions,
+            limits,
+            game: Game::new(),
+            next: 0,
+            hash_cleared: false,
+            solved_at: None,
+            solved: 0,
+            solution_time: Duration::default(),
+        }
+    }
+
+    /// Returns the number of positions solved so far.
+    pub fn solved(&self) -> usize {
+        self.solved
+    }
+
+    /// Returns the number of positions failed so far.
+    pub fn failed(&self) -> usize {
+        self.next - self.solved
+    }
+
+    fn current(&self) -> &Epd {
+        &self.positions[self.next - 1]
+    }
+}
+
+impl Protocol for EpdSuite {
+    fn wait_for_direction(&mut self) -> Action {
+        if let Some(epd) = self.positions.get(self.next) {
+            if !self.hash_cleared {
+                self.hash_cleared = true;
+                return Action::ClearHash;
+            }
+
+            self.game = Game::starting_at(epd.position().as_ref().clone());
+            self.hash_cleared = false;
+            self.solved_at = None;
+            self.next += 1;
+
+            Action::Search
+        } else {
+            println!();
+            println!("Solved          : {}/{}", self.solved(), self.positions.len());
+            println!("Failed          : {}", self.failed());
+            println!("Time to solve   : {:.3}s", self.solution_time.as_secs_f64());
+
+            Action::Quit
+        }
+    }
+
+    fn send_move(&mut self, thinking: &Thinking) {
+        let epd = self.current();
+        let solved = is_solved(epd, thinking);
+        let time = self.solved_at.unwrap_or_else(|| thinking.time());
+
+        let id = epd.id().map_or_else(|| self.next.to_string(), str::to_owned);
+        let best_move = thinking.best_move().map_or("(none)".to_owned(), |mv| mv.to_string());
+        let expected: Vec<_> = epd.best_moves().iter().map(|mv| mv.to_string())
+            .chain(epd.avoid_moves().iter().map(|mv| format!("!{}", mv)))
+            .chain(epd.direct_mate().map(|moves| format!("#{}", moves)))
+            .collect();
+
+        if solved {
+            println!("{:20} solved  {:8} ({}) in {:.3}s",
+                id, best_move, expected.join(" "), time.as_secs_f64());
+            self.solved += 1;
+            self.solution_time += time;
+        } else {
+            println!("{:20} FAILED  {:8} ({})", id, best_move, expected.join(" "));
+        }
+    }
+
+    fn send_thinking(&mut self, thinking: &Thinking) {
+        if is_solved(self.current(), thinking) {
+            if self.solved_at.is_none() {
+                self.solved_at = Some(thinking.time());
+            }
+        } else {
+            self.solved_at = None;
+        }
+    }
+
+    fn send_debug_msg(&mut self, _msg: &str) { }
+
+    fn check_input(&mut self) -> Option<SearchAction> {
+        None
+    }
+
+    fn game(&self) -> &Game {
+        &self.game
+    }
+
+    fn ponder_move(&self) -> Option<&MoveRc> {
+        None
+    }
+
+    fn search_limits(&self) -> SearchLimits {
+        let mut limits = self.limits;
+        if limits.mate().is_none() {
+            limits.set_mate(self.current().direct_mate());
+        }
+
+        limits
+    }
+
+    fn search_moves(&self) -> Option<&[MoveRc]> {
+        None
+    }
+
+    fn ratings(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn draw_offered(&self) -> bool {
+        false
+    }
+
+    fn declare_options(&mut self, _options: &[EngineOption]) { }
+}
+
+/// Returns true if the search results in `thinking` solve the test position `epd`.
+fn is_solved(epd: &Epd, thinking: &Thinking) -> bool {
+    let best_move = match thinking.best_move() {
+        Some(mv) => mv,
+        None => return false,
+    };
+    let matches = |mv: &MoveRc| {
+        mv.origin() == best_move.origin()
+        && mv.destination() == best_move.destination()
+        && mv.promotion() == best_move.promotion()
+    };
+
+    if !epd.best_moves().is_empty() && !epd.best_moves().iter().any(matches) {
+        return false;
+    }
+    if epd.avoid_moves().iter().any(matches) {
+        return false;
+    }
+    if let Some(moves) = epd.direct_mate() {
+        match thinking.score() {
+            Score::MateIn(plies)
+                if plies > 0 && plies as usize <= (moves * 2).saturating_sub(1) => { },
+            _ => return false,
+        }
+    }
+
+    true
+}
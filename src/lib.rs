@@ -31,3 +31,7 @@
 
 mod engine;
 pub use engine::Engine;
+
+pub mod bench;
+pub mod epd;
+pub mod analysis;
@@ -31,10 +31,17 @@
 
 use std::fs::File;
 use std::path::PathBuf;
-use clap::{App, Arg, SubCommand, crate_version};
+use std::time::Duration;
+use clap::{App, Arg, ArgMatches, SubCommand, crate_version};
 use simplelog::{WriteLogger, LevelFilter, Config};
-use chess::variations;
+use chess::ValidMove;
+use chess::perft::Perft;
 use tinman::Engine;
+use tinman::bench::Bench;
+use tinman::epd::EpdSuite;
+use tinman::analysis::Analysis;
+use chess::epd::Epd;
+use protocols::SearchLimits;
 use protocols::xboard::Xboard;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -69,6 +76,35 @@ fn main() -> Result<(), Error> {
                 .takes_value(true)
                 .default_value("tinman.log")
                 .help("Sets the log file if logging is turned on"))
+            .arg(Arg::with_name("move-overhead")
+                .long("move-overhead")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .default_value("50")
+                .help("Sets the time reserved for communication delays on each move"))
+            .arg(Arg::with_name("contempt")
+                .long("contempt")
+                .value_name("CENTIPAWNS")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .default_value("0")
+                .help("Sets how much worse than equal the engine considers a draw"))
+            .arg(Arg::with_name("rating-contempt")
+                .long("rating-contempt")
+                .help("Adjusts the contempt by the difference between the engine's rating \
+                       and its opponent's"))
+            .arg(Arg::with_name("resign-score")
+                .long("resign-score")
+                .value_name("CENTIPAWNS")
+                .takes_value(true)
+                .default_value("1000")
+                .help("Sets how far behind the engine must be to resign, or 0 to never resign"))
+            .arg(Arg::with_name("resign-moves")
+                .long("resign-moves")
+                .value_name("MOVES")
+                .takes_value(true)
+                .default_value("5")
+                .help("Sets how many moves in a row the engine must be that far behind to resign"))
             .subcommand(SubCommand::with_name("counts")
                 .about("Counts the number of variations from a given starting position \
                         to a specified depth.")
@@ -79,6 +115,23 @@ fn main() -> Result<(), Error> {
                     .takes_value(true)
                     .required(true)
                     .help("Depth to search the position"))
+                .arg(Arg::with_name("divide")
+                    .long("divide")
+                    .help("Prints the count following each legal move as 'e2e4: 20', instead of \
+                           the default layout"))
+                .arg(Arg::with_name("threads")
+                    .long("threads")
+                    .short("t")
+                    .value_name("THREADS")
+                    .takes_value(true)
+                    .default_value("1")
+                    .help("Number of threads to use"))
+                .arg(Arg::with_name("hash")
+                    .long("hash")
+                    .value_name("MEGABYTES")
+                    .takes_value(true)
+                    .default_value("0")
+                    .help("Size of the hash table, or 0 for no hash table"))
                 .arg(Arg::with_name("fen")
                     .value_name("FEN_STRING")
                     .default_value("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
@@ -86,6 +139,71 @@ fn main() -> Result<(), Error> {
                     .multiple(true)
                     .help("Position(s) to search in Forsyth-Edwards Notation (FEN) [defaults to the \
                            standard starting position]")))
+            .subcommand(SubCommand::with_name("bench")
+                .about("Searches a built-in set of positions to a fixed depth, and reports the \
+                        total nodes, time and nodes per second.")
+                .arg(Arg::with_name("depth")
+                    .value_name("DEPTH")
+                    .default_value("7")
+                    .help("Depth to search each position")))
+            .subcommand(SubCommand::with_name("epd")
+                .about("Searches each position from a test suite in Extended Position Description \
+                        (EPD) format, and reports which were solved.")
+                .arg(Arg::with_name("file")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("The EPD file to run"))
+                .arg(Arg::with_name("time")
+                    .long("time")
+                    .value_name("SECONDS")
+                    .takes_value(true)
+                    .help("Time to search each position [default: 5 if no other limit is given]"))
+                .arg(Arg::with_name("depth")
+                    .long("depth")
+                    .short("d")
+                    .value_name("DEPTH")
+                    .takes_value(true)
+                    .help("Depth to search each position"))
+                .arg(Arg::with_name("nodes")
+                    .long("nodes")
+                    .value_name("NODES")
+                    .takes_value(true)
+                    .help("Number of nodes to search in each position")))
+            .subcommand(SubCommand::with_name("analyze")
+                .about("Searches a single position and prints the engine's thinking. The \
+                        transposition table can be saved and loaded to resume analysis later.")
+                .arg(Arg::with_name("fen")
+                    .value_name("FEN_STRING")
+                    .default_value("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                    .hide_default_value(true)
+                    .help("Position to search in Forsyth-Edwards Notation (FEN) [defaults to the \
+                           standard starting position]"))
+                .arg(Arg::with_name("time")
+                    .long("time")
+                    .value_name("SECONDS")
+                    .takes_value(true)
+                    .help("Time to search [default: 10 if no other limit is given]"))
+                .arg(Arg::with_name("depth")
+                    .long("depth")
+                    .short("d")
+                    .value_name("DEPTH")
+                    .takes_value(true)
+                    .help("Depth to search"))
+                .arg(Arg::with_name("nodes")
+                    .long("nodes")
+                    .value_name("NODES")
+                    .takes_value(true)
+                    .help("Number of nodes to search"))
+                .arg(Arg::with_name("load-hash")
+                    .long("load-hash")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .help("Loads the transposition table from FILE before searching"))
+                .arg(Arg::with_name("save-hash")
+                    .long("save-hash")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .help("Saves the transposition table to FILE after searching")))
             .get_matches();
 
     let log_file = PathBuf::from(matches.value_of_os("log-file").expect("INFALLIBLE"));
@@ -112,7 +230,39 @@ fn main() -> Result<(), Error> {
     };
 
     match matches.subcommand() {
-        (_, None) => Engine::new(Xboard::new()).run(),
+        (_, None) => {
+            let move_overhead = matches
+                .value_of("move-overhead")
+                .expect("INFALLIBLE")
+                .parse()
+                .map_err(|_| {Error("move overhead must be numeric".to_owned())})?;
+
+            let contempt = matches
+                .value_of("contempt")
+                .expect("INFALLIBLE")
+                .parse()
+                .map_err(|_| {Error("contempt must be numeric".to_owned())})?;
+
+            let resign_score = matches
+                .value_of("resign-score")
+                .expect("INFALLIBLE")
+                .parse()
+                .map_err(|_| {Error("resign score must be numeric".to_owned())})?;
+
+            let resign_moves = matches
+                .value_of("resign-moves")
+                .expect("INFALLIBLE")
+                .parse()
+                .map_err(|_| {Error("resign moves must be numeric".to_owned())})?;
+
+            let mut engine = Engine::new(Xboard::new());
+            engine.set_move_overhead(Duration::from_millis(move_overhead))
+                .set_contempt(contempt)
+                .set_rating_contempt(matches.is_present("rating-contempt"))
+                .set_resign_score(resign_score)
+                .set_resign_moves(resign_moves);
+            engine.run();
+        },
         ("counts", Some(matches)) => {
             let depth = matches
                 .value_of("depth")
@@ -120,14 +270,89 @@ fn main() -> Result<(), Error> {
                 .parse()
                 .map_err(|_| {Error("depth must be numeric".to_owned())})?;
 
+            let threads = matches
+                .value_of("threads")
+                .expect("INFALLIBLE")
+                .parse()
+                .map_err(|_| {Error("threads must be numeric".to_owned())})?;
+            let hash_size: usize = matches
+                .value_of("hash")
+                .expect("INFALLIBLE")
+                .parse()
+                .map_err(|_| {Error("hash size must be numeric".to_owned())})?;
+
+            let hash_size = hash_size.checked_mul(1 << 20)
+                .ok_or_else(|| {Error("hash size is too large".to_owned())})?;
+
+            let mut perft = Perft::new();
+            perft.threads(threads).hash_size(hash_size);
+
             println!();
             for fen in matches.values_of("fen").expect("INFALLIBLE") {
                 let pos = fen.parse().map_err(|err| {Error(format!("{}: {}", fen, err))})?;
                 println!("{}", fen);
-                let count = variations::print(&pos, depth);
+                let divide = perft.divide(&pos, depth);
+                if matches.is_present("divide") {
+                    for (mv, count) in divide.iter() {
+                        println!("{}: {}", mv, count);
+                    }
+                    println!();
+                } else {
+                    // each move, the variations following it, and the position it leads to
+                    let children = pos.moves()
+                        .filter_map(|mv| mv.make().ok().map(|child| (mv, child)));
+                    for ((mv, child), (_, count)) in children.zip(divide.iter()) {
+                        println!("\t{:7}\t{:12}\t{}", mv, count, child);
+                    }
+                }
+                let count = if divide.is_empty() {
+                    perft.count(&pos, depth)
+                } else {
+                    divide.iter().map(|(_, count)| count).sum()
+                };
                 println!("Depth {} total:\t{:12}\n", depth, count);
             }
         },
+        ("bench", Some(matches)) => {
+            let depth = matches
+                .value_of("depth")
+                .expect("INFALLIBLE")
+                .parse()
+                .map_err(|_| {Error("depth must be numeric".to_owned())})?;
+
+            Engine::new(Bench::new(depth)).run();
+        },
+        ("epd", Some(matches)) => {
+            let file = PathBuf::from(matches.value_of_os("file").expect("INFALLIBLE"));
+            let contents = std::fs::read_to_string(&file)
+                .map_err(|err| {Error(format!("{}: {}", file.display(), err))})?;
+            let positions = contents.lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(n, line)| line.parse::<Epd>().map_err(|err| {
+                    Error(format!("{}:{}: {}", file.display(), n + 1, err))
+                }))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let limits = search_limits(matches, Duration::from_secs(5))?;
+
+            Engine::new(EpdSuite::new(positions, limits)).run();
+        },
+        ("analyze", Some(matches)) => {
+            let fen = matches.value_of("fen").expect("INFALLIBLE");
+            let pos = fen.parse().map_err(|err| {Error(format!("{}: {}", fen, err))})?;
+            let limits = search_limits(matches, Duration::from_secs(10))?;
+
+            let mut analysis = Analysis::new(pos, limits);
+            if let Some(path) = matches.value_of_os("load-hash") {
+                analysis.load_hash(path.into());
+            }
+            if let Some(path) = matches.value_of_os("save-hash") {
+                analysis.save_hash(path.into());
+            }
+
+            Engine::new(analysis).run();
+        },
         _ => unreachable!(),
     }
 
@@ -135,6 +360,33 @@ fn main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Reads the `time`, `depth` and `nodes` arguments. If none are given, the search time defaults to
+/// `default_time`.
+fn search_limits(matches: &ArgMatches<'_>, default_time: Duration) -> Result<SearchLimits, Error> {
+    let mut limits = SearchLimits::new();
+
+    if let Some(time) = matches.value_of("time") {
+        let time = time.parse::<f64>()
+            .ok()
+            .filter(|time| *time >= 0.0 && time.is_finite())
+            .ok_or_else(|| {Error("time must be a non-negative number".to_owned())})?;
+        limits.set_move_time(Some(Duration::from_secs_f64(time)));
+    }
+    if let Some(depth) = matches.value_of("depth") {
+        let depth = depth.parse().map_err(|_| {Error("depth must be numeric".to_owned())})?;
+        limits.set_depth(Some(depth));
+    }
+    if let Some(nodes) = matches.value_of("nodes") {
+        let nodes = nodes.parse().map_err(|_| {Error("nodes must be numeric".to_owned())})?;
+        limits.set_nodes(Some(nodes));
+    }
+    if limits == SearchLimits::new() {
+        limits.set_move_time(Some(default_time));
+    }
+
+    Ok(limits)
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 /// An error message that is printed if execution fails.
 struct Error(String);
@@ -12,3 +12,5 @@
 #![warn(clippy::option_unwrap_used, clippy::result_unwrap_used)]
 
 pub mod pgn;
+pub mod stats;
+pub mod report;
@@ -14,9 +14,11 @@
 use std::path::{Path, PathBuf};
 use std::fs::{read_to_string, write, create_dir, read_dir, File, OpenOptions};
 use std::io::Write;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::cmp::max;
 use std::collections::hash_map::Entry;
-use std::time::Duration;
 use clap::{App, Arg, SubCommand, AppSettings, ArgMatches, crate_version};
 use log::error;
 use simplelog::{WriteLogger, LevelFilter, Config};
@@ -24,8 +26,28 @@ use rand::seq::SliceRandom;
 use chrono::Local;
 use protocols::xboard::XboardClient;
 use protocols::client::GameSetup;
-use chess::game::{MoveSequence, TimeControl};
+use chess::Color;
+use chess::game::{MoveSequence, TimeControl, GameResult};
 use tinman_test::pgn::read_pgn_games;
+use tinman_test::stats::{Outcome, Results, Sprt, SprtStatus};
+use tinman_test::report::{Crosstable, Format};
+
+/// The time control used if the test configuration doesn't give one.
+const DEFAULT_TIME_CONTROL: &str = "60+1";
+/// The number of games played at once if the test configuration doesn't say.
+const DEFAULT_CONCURRENCY: usize = 1;
+/// The number of times each game is repeated if the test configuration doesn't say.
+const DEFAULT_ROUNDS: usize = 1;
+/// The number of games played from each opening, in alternating colors, for each pairing of
+/// engines, if the test configuration doesn't say.
+const DEFAULT_GAMES_PER_OPENING: usize = 2;
+/// The SPRT error rates used if the test configuration doesn't give them.
+const DEFAULT_SPRT_ALPHA: f64 = 0.05;
+const DEFAULT_SPRT_BETA: f64 = 0.05;
+/// The command line arguments for the SPRT, and the keys they're saved under in the configuration.
+const SPRT_ARGS: [(&str, &str); 4] = [
+    ("elo0", "sprt-elo0"), ("elo1", "sprt-elo1"), ("alpha", "sprt-alpha"), ("beta", "sprt-beta"),
+];
 
 fn main() -> Result<(), Error> {
     let matches =
@@ -58,7 +80,50 @@ fn main() -> Result<(), Error> {
                     .value_name("LOG_FILE")
                     .takes_value(true)
                     .default_value("testing.log")
-                    .help("Sets the log file if logging is turned on")))
+                    .help("Sets the log file if logging is turned on"))
+                .arg(Arg::with_name("tc")
+                    .long("tc")
+                    .value_name("TIME_CONTROL")
+                    .takes_value(true)
+                    .help("Sets the time control, such as 60+1 or 40/300 (default: 60+1)"))
+                .arg(Arg::with_name("concurrency")
+                    .long("concurrency")
+                    .short("c")
+                    .value_name("N")
+                    .takes_value(true)
+                    .help("Sets the number of games to play at once (default: 1)"))
+                .arg(Arg::with_name("rounds")
+                    .long("rounds")
+                    .value_name("N")
+                    .takes_value(true)
+                    .help("Sets the number of times to play each game (default: 1)"))
+                .arg(Arg::with_name("games-per-opening")
+                    .long("games-per-opening")
+                    .value_name("N")
+                    .takes_value(true)
+                    .help("Sets the number of games played from each opening by each pair of \
+                           engines, alternating colors (default: 2)"))
+                .args(&sprt_args())
+                .after_help("Options given to run are saved in config.yaml for later runs."))
+            .subcommand(SubCommand::with_name("stats")
+                .about("Shows the results of each candidate against each opponent")
+                .args(&sprt_args()))
+            .subcommand(SubCommand::with_name("report")
+                .about("Shows a crosstable of all the games played")
+                .arg(Arg::with_name("format")
+                    .long("format")
+                    .short("f")
+                    .value_name("FORMAT")
+                    .takes_value(true)
+                    .possible_values(&["text", "markdown", "html"])
+                    .default_value("text")
+                    .help("Sets the format of the crosstable"))
+                .arg(Arg::with_name("output")
+                    .long("output")
+                    .short("o")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .help("Writes the crosstable to a file instead of the standard output")))
             .subcommand(SubCommand::with_name("add")
                 .about("Adds a new engine")
                 .arg(Arg::with_name("candidate")
@@ -114,6 +179,7 @@ fn main() -> Result<(), Error> {
         candidates_file: dir.join("candidates.yaml"),
         opponents_file: dir.join("opponents.yaml"),
         opening_file: dir.join("openings.yaml"),
+        config_file: dir.join("config.yaml"),
         bin_dir: dir.join("bin"),
         games_dir: dir.join("games"),
         dir,
@@ -130,6 +196,8 @@ fn main() -> Result<(), Error> {
                 return Err(Error(format!("'{}' already exists", paths.opponents_file.display())));
             } else if paths.opening_file.exists() {
                 return Err(Error(format!("'{}' already exists", paths.opening_file.display())));
+            } else if paths.config_file.exists() {
+                return Err(Error(format!("'{}' already exists", paths.config_file.display())));
             }
 
             if !paths.bin_dir.is_dir() {
@@ -149,6 +217,10 @@ fn main() -> Result<(), Error> {
             let openings: HashMap<String, String> = HashMap::new();
             println!("Creating '{}'.", paths.opening_file.display());
             write_opening_file(&paths.opening_file, &openings)?;
+            let mut config = HashMap::new();
+            config.insert("time-control".to_owned(), DEFAULT_TIME_CONTROL.to_owned());
+            println!("Creating '{}'.", paths.config_file.display());
+            write_config_file(&paths.config_file, &config)?;
         },
         ("add", Some(matches)) => {
             let bin = matches.value_of("bin").expect("INFALLIBLE").to_string();
@@ -199,6 +271,31 @@ fn main() -> Result<(), Error> {
         ("run", Some(matches)) => {
             run(matches, &paths)?;
         },
+        ("stats", Some(matches)) => {
+            let mut config = read_config_file(&paths.config_file)?;
+            apply_args(&mut config, matches, &SPRT_ARGS);
+            let sprt = config_sprt(&config)?;
+
+            let tally = tally(&read_played_games(&paths.games_dir)?);
+            let mut pairs: Vec<_> = tally.keys().collect();
+            pairs.sort_unstable();
+            for pair in pairs {
+                report(pair, &tally[pair], sprt.as_ref());
+            }
+        },
+        ("report", Some(matches)) => {
+            let format: Format = matches.value_of("format").expect("INFALLIBLE").parse()?;
+            let mut table = Crosstable::new();
+            for game in read_played_games(&paths.games_dir)? {
+                table.add_game(&game.white, &game.black, &game.opening, &game.result);
+            }
+
+            let table = table.to_string(format);
+            match matches.value_of("output") {
+                Some(file) => write(file, table)?,
+                None => print!("{}", table),
+            }
+        },
         _ => unreachable!(),
     }
 
@@ -210,6 +307,7 @@ struct Paths {
     candidates_file: PathBuf,
     opponents_file: PathBuf,
     opening_file: PathBuf,
+    config_file: PathBuf,
     bin_dir: PathBuf,
     games_dir: PathBuf,
 }
@@ -241,126 +339,363 @@ fn run(matches: &ArgMatches, paths: &Paths) -> Result<(), Error> {
     let opponents: HashMap<String, Vec<String>> = read_engine_file(&paths.opponents_file)?;
     let openings = read_opening_file(&paths.opening_file)?;
 
-    // create set of games to be played
-    let mut white_games = HashSet::new();
-    let mut black_games = HashSet::new();
-    for eng in candidates.keys() {
-        for opp in opponents.keys() {
+    // options given on the command line are saved for later runs
+    let mut config = read_config_file(&paths.config_file)?;
+    let changed = apply_args(&mut config, matches, &[("tc", "time-control"),
+        ("concurrency", "concurrency"), ("rounds", "rounds"),
+        ("games-per-opening", "games-per-opening")]);
+    let changed = apply_args(&mut config, matches, &SPRT_ARGS) || changed;
+
+    let tc = config.get("time-control").map_or(DEFAULT_TIME_CONTROL, String::as_str);
+    let tc = match tc.parse() {
+        Ok(TimeControl::Infinite) | Ok(TimeControl::Exact(_)) =>
+            return Err(Error(format!("{}: time control can't be used for games", tc))),
+        Ok(tc) => tc,
+        Err(_) => return Err(Error(format!("{}: invalid time control", tc))),
+    };
+    let concurrency = config_count(&config, "concurrency", DEFAULT_CONCURRENCY)?;
+    let rounds = config_count(&config, "rounds", DEFAULT_ROUNDS)?;
+    let games_per_opening = config_count(&config, "games-per-opening", DEFAULT_GAMES_PER_OPENING)?;
+    let sprt = config_sprt(&config)?;
+    if changed {
+        write_config_file(&paths.config_file, &config)?;
+    }
+
+    // count the games that have already been played
+    let played_games = read_played_games(&paths.games_dir)?;
+    let mut played = HashMap::new();
+    for game in &played_games {
+        let key = (game.white.clone(), game.black.clone(), game.opening.clone());
+        *played.entry(key).or_insert(0) += 1;
+    }
+    let tally = tally(&played_games);
+    let mut pairs: Vec<_> = tally.keys().collect();
+    pairs.sort_unstable();
+    for pair in pairs {
+        report(pair, &tally[pair], sprt.as_ref());
+    }
+    let decided = |eng: &str, opp: &str| {
+        let pair = (eng.to_owned(), opp.to_owned());
+        match (&sprt, tally.get(&pair)) {
+            (Some(sprt), Some(results)) => sprt.status(results) != SprtStatus::Continue,
+            _ => false,
+        }
+    };
+
+    // create the set of games still to be played, with each candidate playing white in the first
+    // of each opening's games against an opponent, and alternating colors after that
+    let white_games = rounds * games_per_opening.div_ceil(2);
+    let black_games = rounds * (games_per_opening / 2);
+    let mut single_games = Vec::new();
+    let mut game_pairs = Vec::new();
+    for (eng, eng_cmd) in &candidates {
+        for (opp, opp_cmd) in opponents.iter().filter(|(opp, _)| !decided(eng, opp)) {
             for opening in openings.keys() {
-                white_games.insert((eng.to_owned(), opp.to_owned(), opening.to_owned()));
-                black_games.insert((eng.to_owned(), opp.to_owned(), opening.to_owned()));
+                let count = |white: &str, black: &str| {
+                    played.get(&(white.to_owned(), black.to_owned(), opening.to_owned()))
+                        .copied().unwrap_or(0)
+                };
+                let as_white = white_games.saturating_sub(count(eng, opp));
+                let as_black = black_games.saturating_sub(count(opp, eng));
+                let job = |white: (&String, &Vec<String>), black: (&String, &Vec<String>)| Job {
+                    white: white.0.to_owned(),
+                    white_cmd: white.1.to_owned(),
+                    black: black.0.to_owned(),
+                    black_cmd: black.1.to_owned(),
+                    opening: opening.to_owned(),
+                    candidate: eng.to_owned(),
+                    opponent: opp.to_owned(),
+                };
+
+                for i in 0..max(as_white, as_black) {
+                    let mut games = Vec::new();
+                    if i < as_white {
+                        games.push(job((eng, eng_cmd), (opp, opp_cmd)));
+                    }
+                    if i < as_black {
+                        games.push(job((opp, opp_cmd), (eng, eng_cmd)));
+                    }
+                    if games.len() == 1 {
+                        single_games.push(games);
+                    } else {
+                        game_pairs.push(games);
+                    }
+                }
             }
         }
     }
 
-    // remove games that have already been played
-    for entry in read_dir(&paths.games_dir)? {
+    // finish any unpaired games first, but otherwise keep both colors of an opening together
+    single_games.shuffle(&mut rand::thread_rng());
+    game_pairs.shuffle(&mut rand::thread_rng());
+    let jobs: VecDeque<Job> = single_games.into_iter().chain(game_pairs).flatten().collect();
+    println!("{} games to play, {} at a time, at {}\n", jobs.len(), concurrency, tc);
+
+    if let Some(sprt) = &sprt {
+        println!("{}\n", sprt);
+    }
+
+    let jobs = Arc::new(Mutex::new(jobs));
+    let openings = Arc::new(openings);
+    let pgn_lock = Arc::new(Mutex::new(()));
+    let tally = Arc::new(Mutex::new(tally));
+    let workers: Vec<_> = (0..concurrency).map(|_| {
+        let jobs = Arc::clone(&jobs);
+        let openings = Arc::clone(&openings);
+        let pgn_lock = Arc::clone(&pgn_lock);
+        let tally = Arc::clone(&tally);
+        let games_dir = paths.games_dir.clone();
+        let tc = tc.clone();
+
+        thread::spawn(move || -> Result<(), Error> {
+            loop {
+                let job = match jobs.lock().expect("job queue").pop_front() {
+                    Some(job) => job,
+                    None => return Ok(()),
+                };
+
+                let mut game_setup = GameSetup::new();
+                game_setup.time_control(tc.clone());
+                game_setup.opening(openings[&job.opening].parse::<MoveSequence>()?);
+
+                println!("{} vs {} ({:#})", job.white, job.black, job.opening);
+                match play_game(
+                    &job.white, &job.white_cmd,
+                    &job.black, &job.black_cmd,
+                    &job.opening, &game_setup)
+                {
+                    Ok((pgn, result)) => {
+                        println!("{}", pgn);
+
+                        // append the whole game at once, so games finished together don't mix
+                        {
+                            let _lock = pgn_lock.lock().expect("PGN lock");
+                            let pgn_file = games_dir.join(job.candidate.clone() + ".pgn");
+                            let mut pgn_file = OpenOptions::new()
+                                .append(true)
+                                .create(true) // create if doesn't already exist
+                                .open(pgn_file)?;
+                            pgn_file.write_all(format!("{}\n", pgn).as_bytes())?;
+                        }
+
+                        if let Some(result) = result {
+                            let color = if job.white == job.candidate {
+                                Color::White
+                            } else {
+                                Color::Black
+                            };
+                            let pair = (job.candidate, job.opponent);
+                            let mut tally = tally.lock().expect("results");
+                            let results = tally.entry(pair.clone()).or_default();
+                            results.add(&job.opening, color, Outcome::from_result(result, color));
+
+                            // stop testing the pair as soon as the SPRT reaches a decision
+                            if report(&pair, results, sprt.as_ref()) {
+                                jobs.lock().expect("job queue").retain(|job| {
+                                    job.candidate != pair.0 || job.opponent != pair.1
+                                });
+                            }
+                        }
+                    },
+                    Err(error) => {
+                        println!("{}", error);
+                        error!("{}", error);
+                    }
+                }
+            }
+        })
+    }).collect();
+
+    for worker in workers {
+        worker.join().map_err(|_| Error("a game thread panicked".to_owned()))??;
+    }
+
+    // TODO:
+    // if any input files have changed, re-read them
+
+    Ok(())
+}
+
+/// A game to be played by `run`.
+#[derive(Debug, Clone)]
+struct Job {
+    white: String,
+    white_cmd: Vec<String>,
+    black: String,
+    black_cmd: Vec<String>,
+    opening: String,
+    /// The candidate whose PGN file the game is written to.
+    candidate: String,
+    opponent: String,
+}
+
+/// A game read from the PGN files in the games directory.
+#[derive(Debug, Clone)]
+struct PlayedGame {
+    /// The candidate whose PGN file the game was read from.
+    candidate: String,
+    white: String,
+    black: String,
+    opening: String,
+    result: String,
+}
+
+/// Reads the games that have been played from the PGN files in `games_dir`.
+fn read_played_games(games_dir: &Path) -> Result<Vec<PlayedGame>, Error> {
+    let mut games = Vec::new();
+    for entry in read_dir(games_dir)? {
         let pgn_file = entry?.path();
-        println!("reading {}\n", pgn_file.display());
-        for game in read_pgn_games(File::open(pgn_file)?) {
-            let mut white = String::new();
-            let mut black = String::new();
-            let mut opening = String::new();
+        let candidate = match pgn_file.file_stem() {
+            Some(stem) => stem.to_string_lossy().into_owned(),
+            None => continue,
+        };
+        eprintln!("reading {}\n", pgn_file.display());
+        for game in read_pgn_games(File::open(&pgn_file)?) {
+            let mut played = PlayedGame {
+                candidate: candidate.clone(),
+                white: String::new(),
+                black: String::new(),
+                opening: String::new(),
+                result: String::new(),
+            };
 
             for (tag, value) in game?.tags()? {
                 match tag.as_str() {
-                    "White" => white = value,
-                    "Black" => black = value,
-                    "TestOpening" => opening = value,
+                    "White" => played.white = value,
+                    "Black" => played.black = value,
+                    "TestOpening" => played.opening = value,
+                    "Result" => played.result = value,
                     _ => {},
                 }
             }
 
-            let entry = (white.clone(), black.clone(), opening.clone());
-            white_games.remove(&entry);
-            let entry = (black.clone(), white.clone(), opening.clone());
-            black_games.remove(&entry);
+            games.push(played);
         }
     }
 
-    let mut single_games: Vec<_> = white_games
-        .symmetric_difference(&black_games)
-        .cloned()
-        .collect();
-    single_games.shuffle(&mut rand::thread_rng());
-    let mut game_pairs: Vec<_> = white_games
-        .intersection(&black_games)
-        .cloned()
-        .collect();
-    game_pairs.shuffle(&mut rand::thread_rng());
-    let all_games = [single_games, game_pairs].concat();
-
-    let mut game_setup = GameSetup::new();
-    game_setup.time_control(TimeControl::Incremental{
-            base: Duration::from_secs(60),
-            inc: Duration::from_secs(1), });
-
-    for game in all_games {
-        let (eng_name, opp_name, opening) = &game;
-        game_setup.opening(openings[opening].parse::<MoveSequence>()?);
-
-        // open engine's pgn file
-        let pgn_file = paths.games_dir.join(eng_name.to_owned() + ".pgn");
-        let mut pgn_file = OpenOptions::new()
-            .append(true)
-            .create(true) // create if doesn't already exist
-            .open(pgn_file)?;
-
-        let eng_cmd = &candidates[eng_name];
-        let opp_cmd = &opponents[opp_name];
-
-        println!("{} vs {} ({:#})", eng_name, opp_name, opening);
-        if white_games.contains(&game) {
-            match play_game(
-                eng_name, eng_cmd,
-                opp_name, opp_cmd,
-                opening, &game_setup)
-            {
-                Ok(pgn) => {
-                    println!("{}", pgn);
-                    writeln!(pgn_file, "{}", pgn)?;
-                },
-                Err(error) => {
-                    println!("{}", error);
-                    error!("{}", error);
-                }
-            }
+    Ok(games)
+}
+
+/// Adds up the results of the games for each candidate and opponent.
+fn tally(games: &[PlayedGame]) -> HashMap<(String, String), Results> {
+    let mut tally = HashMap::new();
+    for game in games {
+        let (opponent, color) = if game.white == game.candidate {
+            (&game.black, Color::White)
         } else {
-            println!("\t*** skipping, already been played ***\n");
+            (&game.white, Color::Black)
+        };
+
+        if let Some(outcome) = Outcome::from_result_tag(&game.result, color) {
+            tally.entry((game.candidate.clone(), opponent.clone()))
+                .or_insert_with(Results::new)
+                .add(&game.opening, color, outcome);
         }
+    }
 
-        println!("{} vs {} ({:#})", opp_name, eng_name, opening);
-        if black_games.contains(&game) {
-            match play_game(
-                opp_name, opp_cmd,
-                eng_name, eng_cmd,
-                opening, &game_setup)
-            {
-                Ok(pgn) => {
-                    println!("{}", pgn);
-                    writeln!(pgn_file, "{}", pgn)?;
-                },
-                Err(error) => {
-                    println!("{}", error);
-                    error!("{}", error);
-                }
-            }
-        } else {
-            println!("\t*** skipping, already been played ***\n");
+    tally
+}
+
+/// Prints the results of a candidate against an opponent, along with the state of the SPRT if
+/// there is one. Returns `true` if the SPRT has reached a decision.
+fn report(pair: &(String, String), results: &Results, sprt: Option<&Sprt>) -> bool {
+    println!("{} vs {}: {}", pair.0, pair.1, results);
+    if let Some(sprt) = sprt {
+        let (lower, upper) = sprt.bounds();
+        let status = sprt.status(results);
+        let decision = match status {
+            SprtStatus::H0Accepted => "H0 accepted",
+            SprtStatus::H1Accepted => "H1 accepted",
+            SprtStatus::Continue => "continuing",
+        };
+        println!("    LLR: {:.2} [{:.2}, {:.2}] {}", sprt.llr(results), lower, upper, decision);
+        status != SprtStatus::Continue
+    } else {
+        false
+    }
+}
+
+/// Copies the values of the command line arguments in `args` into the configuration under the
+/// matching keys. Returns `true` if the configuration changed.
+fn apply_args(config: &mut HashMap<String, String>, matches: &ArgMatches, args: &[(&str, &str)])
+-> bool {
+    let mut changed = false;
+    for &(arg, key) in args {
+        if let Some(value) = matches.value_of(arg) {
+            changed |= config.insert(key.to_owned(), value.to_owned()).as_deref() != Some(value);
         }
+    }
+
+    changed
+}
 
-        // TODO:
-        // if any input files have changed, re-read them
+/// Returns the command line arguments which configure the SPRT.
+fn sprt_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("elo0")
+            .long("elo0")
+            .value_name("ELO")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .requires("elo1")
+            .help("Sets the Elo difference of the SPRT's null hypothesis"),
+        Arg::with_name("elo1")
+            .long("elo1")
+            .value_name("ELO")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .requires("elo0")
+            .help("Sets the Elo difference of the SPRT's alternative hypothesis"),
+        Arg::with_name("alpha")
+            .long("alpha")
+            .value_name("PROBABILITY")
+            .takes_value(true)
+            .help("Sets the SPRT's chance of accepting elo1 when elo0 is true (default: 0.05)"),
+        Arg::with_name("beta")
+            .long("beta")
+            .value_name("PROBABILITY")
+            .takes_value(true)
+            .help("Sets the SPRT's chance of accepting elo0 when elo1 is true (default: 0.05)"),
+    ]
+}
+
+/// Returns the SPRT set up by the configuration, if any.
+fn config_sprt(config: &HashMap<String, String>) -> Result<Option<Sprt>, Error> {
+    let number = |key: &str| match config.get(key).map(|value| value.parse::<f64>()) {
+        None => Ok(None),
+        Some(Ok(value)) if value.is_finite() => Ok(Some(value)),
+        Some(_) => Err(Error(format!("{}: {} must be a number", config[key], key))),
+    };
+    let probability = |key: &str, default: f64| match number(key)? {
+        None => Ok(default),
+        Some(value) if value > 0.0 && value < 1.0 => Ok(value),
+        Some(value) => Err(Error(format!("{}: {} must be between 0 and 1", value, key))),
+    };
+
+    match (number("sprt-elo0")?, number("sprt-elo1")?) {
+        (None, None) => Ok(None),
+        (Some(elo0), Some(elo1)) if elo0 < elo1 => Ok(Some(Sprt::new(elo0, elo1,
+            probability("sprt-alpha", DEFAULT_SPRT_ALPHA)?,
+            probability("sprt-beta", DEFAULT_SPRT_BETA)?))),
+        _ => Err(Error("the SPRT needs elo0 to be less than elo1".to_owned())),
     }
+}
 
-    Ok(())
+/// Reads a positive number from the configuration, or returns `default` if it isn't there.
+fn config_count(config: &HashMap<String, String>, key: &str, default: usize)
+-> Result<usize, Error> {
+    match config.get(key).map(|value| value.parse()) {
+        None => Ok(default),
+        Some(Ok(count)) if count > 0 => Ok(count),
+        Some(_) => Err(Error(format!("{}: {} must be a positive number", config[key], key))),
+    }
 }
 
 fn play_game(
     white_name: &str, white_cmd: &[String],
     black_name: &str, black_cmd: &[String],
     opening: &str, game_setup: &GameSetup)
--> std::io::Result<String> {
+-> std::io::Result<(String, Option<GameResult>)> {
     let white = Box::new(XboardClient::new(
         &white_cmd[0],
         &white_cmd[1..],
@@ -384,7 +719,8 @@ fn play_game(
     pgn_tags.insert("White".to_owned(), white_name.to_owned());
     pgn_tags.insert("Black".to_owned(), black_name.to_owned());
 
-    Ok(game_setup.play_game(white, black).0.to_pgn(&pgn_tags))
+    let game = game_setup.play_game(white, black).0;
+    Ok((game.to_pgn(&pgn_tags), game.result()))
 }
 
 fn read_engine_file(path: &Path) -> Result<HashMap<String, Vec<String>>, Error> {
@@ -409,6 +745,21 @@ fn write_opening_file(path: &Path, openings: &HashMap<String, String>) -> Result
     Ok(())
 }
 
+fn read_config_file(path: &Path) -> Result<HashMap<String, String>, Error> {
+    // test environments set up before there was a config file use the defaults
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let s = read_to_string(path)?;
+    Ok(serde_yaml::from_str(&s)?)
+}
+
+fn write_config_file(path: &Path, config: &HashMap<String, String>) -> Result<(), Error> {
+    let s = serde_yaml::to_string(config)?;
+    write(path, s)?;
+    Ok(())
+}
+
 fn read_pgn_openings(path: &Path) -> Result<Vec<String>, Error> {
     let mut list = Vec::new();
     let s = read_to_string(path)?;
@@ -473,3 +824,9 @@ impl From<tinman_test::pgn::PgnParseError> for Error {
         Error(err.to_string())
     }
 }
+
+impl From<tinman_test::report::ParseFormatError> for Error {
+    fn from(err: tinman_test::report::ParseFormatError) -> Self {
+        Error(err.to_string())
+    }
+}
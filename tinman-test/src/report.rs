@@ -0,0 +1,373 @@
+//! Crosstables of the results of games between a number of engines.
+//
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+use std::fmt;
+use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
+use chess::Color;
+use crate::stats::Outcome;
+
+/// The number of passes made over the players when calculating performance ratings.
+const RATING_ITERATIONS: usize = 100;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// The formats in which a crosstable can be written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Plain text, with the columns lined up.
+    Text,
+    /// A Markdown table.
+    Markdown,
+    /// An HTML table.
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Format, ParseFormatError> {
+        match s {
+            "text" => Ok(Format::Text),
+            "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            _ => Err(ParseFormatError),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// An error returned when a crosstable format isn't recognized.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ParseFormatError;
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "unknown crosstable format".fmt(f)
+    }
+}
+
+impl std::error::Error for ParseFormatError { }
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// Points scored out of a number of games. Points are counted in halves, so they can be compared
+/// exactly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+struct Points {
+    half_points: u32,
+    games: u32,
+}
+
+impl Points {
+    fn add(&mut self, outcome: Outcome) {
+        self.half_points += (outcome.score() * 2.0) as u32;
+        self.games += 1;
+    }
+
+    fn points(self) -> f64 {
+        f64::from(self.half_points) / 2.0
+    }
+
+    fn fraction(self) -> f64 {
+        self.points() / f64::from(self.games)
+    }
+}
+
+impl fmt::Display for Points {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.games == 0 {
+            "".fmt(f)
+        } else {
+            format!("{}/{}", self.points(), self.games).fmt(f)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A player's results in the crosstable.
+#[derive(Debug, Clone, Default)]
+struct Player {
+    name: String,
+    white: Points,
+    black: Points,
+    against: HashMap<usize, Points>,
+    openings: HashSet<String>,
+}
+
+impl Player {
+    fn total(&self) -> Points {
+        Points {
+            half_points: self.white.half_points + self.black.half_points,
+            games: self.white.games + self.black.games,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// The results of all the games between a number of engines, showing each engine's score against
+/// every other, and its performance rating.
+///
+/// Performance ratings are relative, with an average of zero, since the engines' real ratings are
+/// unknown. They are found by repeatedly setting each engine's rating to the average rating of the
+/// opponents it played plus the Elo difference its score implies, until they settle. Each score
+/// is counted as if half a point had been scored in one extra game, so that winning or losing
+/// every game doesn't give an infinite rating.
+///
+/// # Example
+/// ```
+/// # use tinman_test::report::{Crosstable, Format};
+/// let mut table = Crosstable::new();
+/// table.add_game("tinman", "other", "e4 e5", "1-0");
+/// table.add_game("other", "tinman", "e4 e5", "1/2-1/2");
+/// print!("{}", table.to_string(Format::Markdown));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Crosstable {
+    players: Vec<Player>,
+    index: HashMap<String, usize>,
+}
+
+impl Crosstable {
+    /// Creates an empty crosstable.
+    pub fn new() -> Self {
+        Crosstable::default()
+    }
+
+    /// Adds a game between `white` and `black`, played from `opening`, with the given PGN `Result`
+    /// tag. Games which didn't finish are ignored.
+    pub fn add_game(&mut self, white: &str, black: &str, opening: &str, result: &str) {
+        let (white_outcome, black_outcome) = match (
+            Outcome::from_result_tag(result, Color::White),
+            Outcome::from_result_tag(result, Color::Black),
+        ) {
+            (Some(white), Some(black)) => (white, black),
+            _ => return,
+        };
+
+        let white = self.player(white);
+        let black = self.player(black);
+        for &(player, opponent, outcome) in &[
+            (white, black, white_outcome),
+            (black, white, black_outcome),
+        ] {
+            let entry = &mut self.players[player];
+            if player == white {
+                entry.white.add(outcome);
+            } else {
+                entry.black.add(outcome);
+            }
+            entry.against.entry(opponent).or_default().add(outcome);
+            entry.openings.insert(opening.to_owned());
+        }
+    }
+
+    /// Returns the crosstable written in the given format.
+    pub fn to_string(&self, format: Format) -> String {
+        let ratings = self.ratings();
+        let order = self.standings(&ratings);
+
+        let mut header: Vec<String> = ["#", "Engine", "Rating", "Score", "Games", "%", "White",
+            "Black", "Openings"].iter().map(|s| s.to_string()).collect();
+        header.extend((1..=order.len()).map(|rank| rank.to_string()));
+
+        let rows: Vec<Vec<String>> = order.iter().enumerate().map(|(rank, &i)| {
+            let player = &self.players[i];
+            let total = player.total();
+            let mut row = vec![
+                (rank + 1).to_string(),
+                player.name.clone(),
+                format!("{:+.0}", ratings[i]),
+                total.points().to_string(),
+                total.games.to_string(),
+                format!("{:.1}", total.fraction() * 100.0),
+                player.white.to_string(),
+                player.black.to_string(),
+                player.openings.len().to_string(),
+            ];
+            row.extend(order.iter().map(|&j| {
+                if i == j {
+                    "-".to_owned()
+                } else {
+                    player.against.get(&j).map(Points::to_string).unwrap_or_default()
+                }
+            }));
+            row
+        }).collect();
+
+        match format {
+            Format::Text => text_table(&header, &rows),
+            Format::Markdown => markdown_table(&header, &rows),
+            Format::Html => html_table(&header, &rows),
+        }
+    }
+
+    /// Returns the index of the player named `name`, adding the player if needed.
+    fn player(&mut self, name: &str) -> usize {
+        let players = &mut self.players;
+        *self.index.entry(name.to_owned()).or_insert_with(|| {
+            players.push(Player{ name: name.to_owned(), ..Player::default() });
+            players.len() - 1
+        })
+    }
+
+    /// Returns the indices of the players, from the highest score to the lowest, with ties broken
+    /// by rating.
+    fn standings(&self, ratings: &[f64]) -> Vec<usize> {
+        let mut order: Vec<_> = (0..self.players.len()).collect();
+        order.sort_by(|&a, &b| {
+            let fraction = |i: usize| self.players[i].total().fraction();
+            fraction(b).partial_cmp(&fraction(a)).expect("finite score")
+                .then(ratings[b].partial_cmp(&ratings[a]).expect("finite rating"))
+                .then(self.players[a].name.cmp(&self.players[b].name))
+        });
+        order
+    }
+
+    /// Returns the performance ratings of the players.
+    fn ratings(&self) -> Vec<f64> {
+        let mut ratings = vec![0.0; self.players.len()];
+        for _ in 0..RATING_ITERATIONS {
+            for (i, player) in self.players.iter().enumerate() {
+                let total = player.total();
+                let opponents = player.against.iter()
+                    .map(|(&j, points)| ratings[j] * f64::from(points.games))
+                    .sum::<f64>() / f64::from(total.games);
+                let score = (total.points() + 0.5) / f64::from(total.games + 1);
+
+                ratings[i] = opponents - 400.0 * (1.0 / score - 1.0).log10();
+            }
+
+            let mean = ratings.iter().sum::<f64>() / ratings.len() as f64;
+            for rating in &mut ratings {
+                *rating -= mean;
+            }
+        }
+        ratings
+    }
+}
+
+/// Writes a table with each column padded to the width of its widest entry. The engine names are
+/// aligned to the left, and everything else to the right.
+fn text_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<_> = header.iter().map(|s| s.chars().count()).collect();
+    for row in rows {
+        for (width, s) in widths.iter_mut().zip(row) {
+            *width = (*width).max(s.chars().count());
+        }
+    }
+
+    let line = |row: &[String]| {
+        let cells: Vec<_> = row.iter().zip(&widths).enumerate().map(|(col, (s, &width))| {
+            if col == 1 {
+                format!("{:<1$}", s, width)
+            } else {
+                format!("{:>1$}", s, width)
+            }
+        }).collect();
+        cells.join("  ").trim_end().to_owned() + "\n"
+    };
+
+    let mut s = line(header);
+    s += &(widths.iter().map(|&width| "-".repeat(width)).collect::<Vec<_>>().join("  ") + "\n");
+    for row in rows {
+        s += &line(row);
+    }
+    s
+}
+
+/// Writes a Markdown table, with the engine names aligned to the left.
+fn markdown_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let line = |row: &[String]| {
+        let cells: Vec<_> = row.iter().map(|s| s.replace('|', "\\|")).collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut s = line(header);
+    let align: Vec<_> = (0..header.len()).map(|col| if col == 1 { ":--" } else { "--:" }).collect();
+    s += &format!("|{}|\n", align.join("|"));
+    for row in rows {
+        s += &line(row);
+    }
+    s
+}
+
+/// Writes an HTML table.
+fn html_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let line = |row: &[String], tag: &str| {
+        let cells: String = row.iter()
+            .map(|s| format!("<{0}>{1}</{0}>", tag, escape_html(s)))
+            .collect();
+        format!("  <tr>{}</tr>\n", cells)
+    };
+
+    let mut s = "<table>\n".to_owned();
+    s += &line(header, "th");
+    for row in rows {
+        s += &line(row, "td");
+    }
+    s + "</table>\n"
+}
+
+/// Escapes the characters which have special meanings in HTML.
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Crosstable {
+        let mut table = Crosstable::new();
+        table.add_game("a", "b", "o1", "1-0");
+        table.add_game("b", "a", "o1", "1/2-1/2");
+        table.add_game("a", "c", "o2", "1-0");
+        table.add_game("c", "a", "o2", "0-1");
+        table.add_game("b", "c", "o1", "1/2-1/2");
+        table.add_game("c", "b", "o1", "*");
+        table
+    }
+
+    #[test]
+    fn standings_and_ratings() {
+        let table = table();
+        let ratings = table.ratings();
+        let names: Vec<_> = table.standings(&ratings).iter()
+            .map(|&i| table.players[i].name.as_str())
+            .collect();
+        assert_eq!(names, ["a", "b", "c"]);
+
+        let rating = |name: &str| ratings[table.index[name]];
+        assert!(rating("a") > rating("b") && rating("b") > rating("c"));
+        assert!((rating("a") + rating("b") + rating("c")).abs() < 1e-6);
+    }
+
+    #[test]
+    fn formats() {
+        let text = table().to_string(Format::Text);
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 5);
+        let cells: Vec<_> = lines[2].split_whitespace().collect();
+        assert_eq!(cells[..2], ["1", "a"]);
+        assert_eq!(cells[3..], ["3.5", "4", "87.5", "2/2", "1.5/2", "2", "-", "1.5/2", "2/2"]);
+        assert_eq!(lines[4].split_whitespace().last(), Some("-"));
+
+        let markdown = table().to_string(Format::Markdown);
+        assert!(markdown.starts_with("| # | Engine | Rating |"));
+        assert!(markdown.contains("\n| 3 | c |"));
+
+        let html = table().to_string(Format::Html);
+        assert!(html.starts_with("<table>\n  <tr><th>#</th><th>Engine</th>"));
+        assert!(html.contains("<td>b</td>"));
+        assert_eq!(escape_html("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+
+        assert_eq!("html".parse(), Ok(Format::Html));
+        assert_eq!("pdf".parse::<Format>(), Err(ParseFormatError));
+    }
+}
@@ -0,0 +1,390 @@
+//! Statistics for the results of games between two engines.
+//
+//  Copyright 2020 Michael Leany
+//
+//  This Source Code Form is subject to the terms of the Mozilla Public
+//  License, v. 2.0. If a copy of the MPL was not distributed with this
+//  file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////
+use std::fmt;
+use std::collections::HashMap;
+use chess::Color;
+use chess::game::GameResult;
+
+/// The number of standard deviations either side of the mean in a 95% confidence interval.
+const Z_95: f64 = 1.959_964;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// The outcome of a game for one of its players.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player won.
+    Win,
+    /// The game was drawn.
+    Draw,
+    /// The player lost.
+    Loss,
+}
+
+impl Outcome {
+    /// Returns the outcome of a game with the given `result` for the player of `color`.
+    pub fn from_result(result: GameResult, color: Color) -> Outcome {
+        match result {
+            GameResult::Win(winner, _) if winner == color => Outcome::Win,
+            GameResult::Win(_, _) => Outcome::Loss,
+            GameResult::Draw(_) => Outcome::Draw,
+        }
+    }
+
+    /// Returns the outcome of a game with the given PGN `Result` tag for the player of `color`, or
+    /// `None` if the game didn't finish.
+    pub fn from_result_tag(tag: &str, color: Color) -> Option<Outcome> {
+        match (tag, color) {
+            ("1-0", Color::White) | ("0-1", Color::Black) => Some(Outcome::Win),
+            ("1-0", Color::Black) | ("0-1", Color::White) => Some(Outcome::Loss),
+            ("1/2-1/2", _) => Some(Outcome::Draw),
+            _ => None,
+        }
+    }
+
+    /// Returns the points scored: 1 for a win, ½ for a draw and 0 for a loss.
+    pub fn score(self) -> f64 {
+        match self {
+            Outcome::Win => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// An Elo difference, along with its 95% confidence interval.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Estimate {
+    /// The estimated Elo difference.
+    pub elo: f64,
+    /// The lower end of the confidence interval.
+    pub lower: f64,
+    /// The upper end of the confidence interval.
+    pub upper: f64,
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format!("{:+.1} [{:+.1}, {:+.1}]", self.elo, self.lower, self.upper).fmt(f)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// The results of the games between an engine and one opponent, from the engine's point of view.
+///
+/// Games played from the same opening with opposite colors are paired, and the pairs are counted
+/// by the points the engine scored in them: 0, ½, 1, 1½ or 2. This is the pentanomial
+/// distribution, which takes into account how much the result depends on the opening.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Results {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    pairs: [u32; 5],
+    unpaired: HashMap<String, Vec<(Color, Outcome)>>,
+}
+
+impl Results {
+    /// Creates an empty set of results.
+    pub fn new() -> Self {
+        Results::default()
+    }
+
+    /// Adds the `outcome` of a game the engine played as `color` from `opening`.
+    pub fn add(&mut self, opening: &str, color: Color, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+
+        let unpaired = self.unpaired.entry(opening.to_owned()).or_default();
+        if let Some(i) = unpaired.iter().position(|&(c, _)| c != color) {
+            let (_, other) = unpaired.remove(i);
+            self.pairs[((outcome.score() + other.score()) * 2.0) as usize] += 1;
+        } else {
+            unpaired.push((color, outcome));
+        }
+    }
+
+    /// Returns the number of games won.
+    pub fn wins(&self) -> u32 {
+        self.wins
+    }
+
+    /// Returns the number of games drawn.
+    pub fn draws(&self) -> u32 {
+        self.draws
+    }
+
+    /// Returns the number of games lost.
+    pub fn losses(&self) -> u32 {
+        self.losses
+    }
+
+    /// Returns the number of games played.
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Returns the number of game pairs in which the engine scored 0, ½, 1, 1½ and 2 points.
+    pub fn pentanomial(&self) -> [u32; 5] {
+        self.pairs
+    }
+
+    /// Returns the fraction of the available points which the engine scored, or `None` if no
+    /// games have been played.
+    pub fn score(&self) -> Option<f64> {
+        self.trinomial().map(|(mean, _, _)| mean)
+    }
+
+    /// Returns the estimated Elo difference, based on the results of individual games.
+    pub fn elo(&self) -> Option<Estimate> {
+        self.trinomial().map(|(mean, var, n)| estimate(mean, var, n))
+    }
+
+    /// Returns the estimated Elo difference, based on the results of game pairs.
+    pub fn pentanomial_elo(&self) -> Option<Estimate> {
+        self.penta().map(|(mean, var, n)| estimate(mean, var, n))
+    }
+
+    /// Returns the likelihood of superiority: the probability that the engine is stronger than its
+    /// opponent.
+    pub fn los(&self) -> f64 {
+        let (wins, losses) = (f64::from(self.wins), f64::from(self.losses));
+        if wins + losses == 0.0 {
+            0.5
+        } else {
+            0.5 * (1.0 + erf((wins - losses) / (2.0 * (wins + losses)).sqrt()))
+        }
+    }
+
+    /// Returns the mean score per game, its variance, and the number of games.
+    fn trinomial(&self) -> Option<(f64, f64, f64)> {
+        distribution(&[
+            (0.0, self.losses),
+            (0.5, self.draws),
+            (1.0, self.wins),
+        ])
+    }
+
+    /// Returns the mean score per game in game pairs, its variance (per pair), and the number of
+    /// pairs.
+    fn penta(&self) -> Option<(f64, f64, f64)> {
+        distribution(&[
+            (0.0, self.pairs[0]),
+            (0.25, self.pairs[1]),
+            (0.5, self.pairs[2]),
+            (0.75, self.pairs[3]),
+            (1.0, self.pairs[4]),
+        ])
+    }
+}
+
+impl fmt::Display for Results {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = format!("+{} ={} -{}", self.wins, self.draws, self.losses);
+        if let (Some(score), Some(elo)) = (self.score(), self.elo()) {
+            s += &format!(" ({:.1}%)  Elo: {}  LOS: {:.1}%",
+                score * 100.0, elo, self.los() * 100.0);
+        }
+        if let Some(elo) = self.pentanomial_elo() {
+            let p = self.pairs;
+            s += &format!("  pairs: {} {} {} {} {}  Elo: {}", p[0], p[1], p[2], p[3], p[4], elo);
+        }
+
+        s.fmt(f)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+/// A sequential probability ratio test, which decides between the hypotheses that the engine is
+/// `elo0` stronger than its opponent (H0), or `elo1` stronger (H1), while limiting the chance of
+/// accepting H1 when H0 is true to `alpha`, and the chance of accepting H0 when H1 is true to
+/// `beta`.
+///
+/// The log-likelihood ratio is calculated with the normal approximation, using the results of
+/// individual games.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprt {
+    elo0: f64,
+    elo1: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+/// The decision reached by an SPRT.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SprtStatus {
+    /// The engine is no better than `elo0` stronger than its opponent.
+    H0Accepted,
+    /// The engine is at least `elo1` stronger than its opponent.
+    H1Accepted,
+    /// More games are needed.
+    Continue,
+}
+
+impl Sprt {
+    /// Creates a test of the hypotheses that the Elo difference is `elo0` or `elo1`, with the
+    /// error rates `alpha` and `beta`.
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Self {
+        Sprt{ elo0, elo1, alpha, beta }
+    }
+
+    /// Returns the lower and upper bounds of the log-likelihood ratio, at which H0 and H1 are
+    /// accepted, respectively.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// Returns the log-likelihood ratio of H1 to H0 for the given results.
+    pub fn llr(&self, results: &Results) -> f64 {
+        match results.trinomial() {
+            Some((mean, var, n)) if var > 0.0 => {
+                let (s0, s1) = (expected_score(self.elo0), expected_score(self.elo1));
+                n * (s1 - s0) * (2.0 * mean - s0 - s1) / (2.0 * var)
+            },
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the decision reached for the given results.
+    pub fn status(&self, results: &Results) -> SprtStatus {
+        let llr = self.llr(results);
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            SprtStatus::H0Accepted
+        } else if llr >= upper {
+            SprtStatus::H1Accepted
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
+
+impl fmt::Display for Sprt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format!("SPRT elo0={} elo1={} alpha={} beta={}",
+            self.elo0, self.elo1, self.alpha, self.beta).fmt(f)
+    }
+}
+
+/// Returns the mean, variance and count of the values, each given with how many times it occurs,
+/// or `None` if there are none.
+fn distribution(counts: &[(f64, u32)]) -> Option<(f64, f64, f64)> {
+    let n: f64 = counts.iter().map(|&(_, count)| f64::from(count)).sum();
+    if n == 0.0 {
+        return None;
+    }
+    let mean = counts.iter().map(|&(value, count)| value * f64::from(count)).sum::<f64>() / n;
+    let var = counts.iter()
+        .map(|&(value, count)| (value - mean).powi(2) * f64::from(count))
+        .sum::<f64>() / n;
+
+    Some((mean, var, n))
+}
+
+/// Returns the estimated Elo difference and its confidence interval for a mean score with the
+/// given variance over `n` samples.
+fn estimate(mean: f64, var: f64, n: f64) -> Estimate {
+    let margin = Z_95 * (var / n).sqrt();
+    Estimate {
+        elo: elo(mean),
+        lower: elo(mean - margin),
+        upper: elo(mean + margin),
+    }
+}
+
+/// Returns the Elo difference which gives the expected `score`. A score of 0 or 1 gives an
+/// infinite difference.
+fn elo(score: f64) -> f64 {
+    let score = score.clamp(0.0, 1.0);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Returns the expected score for the given Elo difference.
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Approximates the error function, with a maximum error of 1.5e-7.
+fn erf(x: f64) -> f64 {
+    // formula 7.1.26 of Abramowitz and Stegun
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741
+        + t * (-1.453_152_027 + t * 1.061_405_429))));
+
+    (1.0 - poly * (-x * x).exp()).copysign(x)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(wins: u32, draws: u32, losses: u32) -> Results {
+        let mut results = Results::new();
+        let outcomes = (0..wins).map(|_| Outcome::Win)
+            .chain((0..draws).map(|_| Outcome::Draw))
+            .chain((0..losses).map(|_| Outcome::Loss));
+        for (i, outcome) in outcomes.enumerate() {
+            results.add(&i.to_string(), Color::White, outcome);
+        }
+        results
+    }
+
+    #[test]
+    fn elo_and_los() {
+        assert_eq!(results(0, 0, 0).elo(), None);
+
+        let even = results(10, 20, 10);
+        let elo = even.elo().expect("games played");
+        assert!(elo.elo.abs() < 1e-9);
+        assert!(elo.lower < -50.0 && elo.upper > 50.0 && (elo.lower + elo.upper).abs() < 1e-9);
+        assert!((even.los() - 0.5).abs() < 1e-9);
+
+        let ahead = results(30, 10, 0);
+        assert!((ahead.elo().expect("games played").elo - 338.04).abs() < 0.01);
+        assert!(ahead.los() > 0.999);
+        assert!(results(0, 1, 3).los() < 0.05);
+        assert_eq!(results(3, 0, 0).elo().expect("games played").elo, f64::INFINITY);
+    }
+
+    #[test]
+    fn pentanomial_pairs() {
+        let mut results = Results::new();
+        results.add("a", Color::White, Outcome::Win);
+        results.add("a", Color::White, Outcome::Draw);
+        results.add("b", Color::Black, Outcome::Draw);
+        assert_eq!(results.pentanomial(), [0, 0, 0, 0, 0]);
+        assert_eq!(results.pentanomial_elo(), None);
+
+        results.add("a", Color::Black, Outcome::Loss);
+        results.add("b", Color::White, Outcome::Draw);
+        results.add("a", Color::Black, Outcome::Win);
+        assert_eq!(results.pentanomial(), [0, 0, 2, 1, 0]);
+        assert_eq!((results.wins(), results.draws(), results.losses()), (2, 3, 1));
+        assert_eq!(Outcome::from_result_tag("0-1", Color::Black), Some(Outcome::Win));
+        assert_eq!(Outcome::from_result_tag("*", Color::Black), None);
+    }
+
+    #[test]
+    fn sprt() {
+        let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 0.001 && (upper - 2.944).abs() < 0.001);
+
+        assert_eq!(sprt.status(&results(0, 0, 0)), SprtStatus::Continue);
+        assert_eq!(sprt.status(&results(60, 80, 60)), SprtStatus::Continue);
+        assert!(sprt.llr(&results(60, 80, 60)) < 0.0);
+        assert_eq!(sprt.status(&results(600, 800, 400)), SprtStatus::H1Accepted);
+        assert_eq!(sprt.status(&results(400, 800, 600)), SprtStatus::H0Accepted);
+    }
+}